@@ -0,0 +1,472 @@
+// binary format for compiled programs, so scripts can be compiled once and run many times.
+//
+// all integers are big-endian, like instruction operands.
+//
+//   magic        4 bytes  "MNKY"
+//   version      u16      FormatVersion
+//   checksum     u32      crc-32 of everything after it
+//   locals       u32      local slots of the main program
+//   globals      u32      global slots of the program
+//   instructions u32 length, then the bytes of the main program
+//   constants    u32 count, then per constant a tag byte followed by
+//     TagInteger   i64
+//     TagString    u32 length, utf-8 bytes
+//     TagFunction  u32 locals, u32 parameters, u32 name length, name, u32 length, instructions
+use crate::{builtins, code, compiler, object};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+pub const Magic: &[u8; 4] = b"MNKY";
+// bump whenever the layout or the instruction set changes.
+pub const FormatVersion: u16 = 7;
+
+const TagInteger: u8 = 0;
+const TagString: u8 = 1;
+const TagFunction: u8 = 2;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    BadMagic,
+    VersionMismatch { Found: u16, Expected: u16 },
+    ChecksumMismatch,
+    Truncated,
+    InvalidUtf8,
+    UnknownConstantTag(u8),
+    UnsupportedConstant(object::ObjectType),
+    InvalidInstructions(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::BadMagic => write!(f, "not a compiled Monkey program (bad magic header)"),
+            Error::VersionMismatch { Found, Expected } => write!(
+                f,
+                "compiled program has format version {}, expected version {}. recompile it",
+                Found, Expected
+            ),
+            Error::ChecksumMismatch => {
+                write!(f, "compiled program is corrupted (checksum mismatch)")
+            }
+            Error::Truncated => write!(f, "compiled program is truncated"),
+            Error::InvalidUtf8 => write!(f, "compiled program contains a string that is not utf-8"),
+            Error::UnknownConstantTag(tag) => write!(f, "unknown constant tag {}", tag),
+            Error::UnsupportedConstant(t) => write!(f, "cannot serialize constant of type {}", t),
+            Error::InvalidInstructions(msg) => write!(f, "invalid instructions: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+pub fn Save(bytecode: &compiler::Bytecode, path: impl AsRef<Path>) -> Result<(), Error> {
+    fs::write(path, Encode(bytecode)?)?;
+    Ok(())
+}
+
+pub fn Load(path: impl AsRef<Path>) -> Result<compiler::Bytecode, Error> {
+    Decode(&fs::read(path)?)
+}
+
+pub fn Encode(bytecode: &compiler::Bytecode) -> Result<Vec<u8>, Error> {
+    let mut out = Magic.to_vec();
+    out.extend_from_slice(&FormatVersion.to_be_bytes());
+    let header = out.len();
+    writeLen(&mut out, bytecode.NumLocals);
+    writeLen(&mut out, bytecode.NumGlobals);
+    writeBytes(&mut out, &bytecode.Instructions);
+    writeLen(&mut out, bytecode.Constants.len());
+    for c in bytecode.Constants.iter() {
+        match c {
+            object::Object::Integer { Value } => {
+                out.push(TagInteger);
+                out.extend_from_slice(&Value.to_be_bytes());
+            }
+            object::Object::String { Value } => {
+                out.push(TagString);
+                writeBytes(&mut out, Value.as_bytes());
+            }
+            object::Object::CompiledFunction { Fn } => {
+                out.push(TagFunction);
+                writeLen(&mut out, Fn.NumLocals);
+                writeLen(&mut out, Fn.NumParameters);
+                writeBytes(&mut out, Fn.Name.as_bytes());
+                writeBytes(&mut out, &Fn.Instructions);
+            }
+            _ => return Err(Error::UnsupportedConstant(c.Type())),
+        }
+    }
+    let sum = checksum(&out[header..]);
+    out.splice(header..header, sum.to_be_bytes());
+    Ok(out)
+}
+
+// Decode reads a compiled program and checks its checksum and that its instructions are well
+// formed, so a corrupted or malformed file is rejected here instead of crashing the vm.
+pub fn Decode(data: &[u8]) -> Result<compiler::Bytecode, Error> {
+    let mut r = Reader { data, pos: 0 };
+    if r.take(Magic.len()).map_err(|_| Error::BadMagic)? != Magic {
+        return Err(Error::BadMagic);
+    }
+    let version = u16::from_be_bytes([r.u8()?, r.u8()?]);
+    if version != FormatVersion {
+        return Err(Error::VersionMismatch {
+            Found: version,
+            Expected: FormatVersion,
+        });
+    }
+
+    let sum = r.u32()?;
+    let payload = r.pos;
+    let numLocals = r.u32()? as usize;
+    // locals are addressed by a single byte.
+    if numLocals > 256 {
+        return Err(Error::InvalidInstructions(format!(
+            "main program has {} locals",
+            numLocals
+        )));
+    }
+    let numGlobals = r.u32()? as usize;
+    let instructions = r.bytes()?.to_vec();
+    let count = r.u32()? as usize;
+    let mut constants = vec![];
+    for _ in 0..count {
+        let c = match r.u8()? {
+            TagInteger => object::Object::Integer {
+                Value: i64::from_be_bytes(r.take(8)?.try_into().unwrap()),
+            },
+            TagString => object::Object::String { Value: r.string()? },
+            TagFunction => {
+                let numLocals = r.u32()? as usize;
+                let numParameters = r.u32()? as usize;
+                let name = r.string()?;
+                let instructions = r.bytes()?.to_vec();
+                object::Object::CompiledFunction {
+                    Fn: Rc::new(object::CompiledFunction {
+                        Instructions: instructions,
+                        NumLocals: numLocals,
+                        NumParameters: numParameters,
+                        Name: name,
+                    }),
+                }
+            }
+            tag => return Err(Error::UnknownConstantTag(tag)),
+        };
+        constants.push(c);
+    }
+    if r.pos != data.len() {
+        return Err(Error::InvalidInstructions(String::from(
+            "trailing bytes after constant pool",
+        )));
+    }
+
+    if checksum(&data[payload..]) != sum {
+        return Err(Error::ChecksumMismatch);
+    }
+
+    let bytecode = compiler::Bytecode {
+        Instructions: instructions,
+        Constants: constants,
+        NumLocals: numLocals,
+        NumGlobals: numGlobals,
+    };
+    verifyProgram(&bytecode)?;
+    Ok(bytecode)
+}
+
+// Instruction is one decoded instruction of a body being verified.
+struct Instruction {
+    pos: usize,
+    op: code::Opcode,
+    operands: Vec<usize>,
+}
+
+// Program is what the bodies of a program are verified against.
+struct Program<'a> {
+    constants: &'a [object::Object],
+    numGlobals: usize,
+    numBuiltins: usize,
+}
+
+// verifyProgram checks the main program and every function in the constant pool, so a
+// program that gets past Decode cannot make the vm index out of bounds.
+fn verifyProgram(bytecode: &compiler::Bytecode) -> Result<(), Error> {
+    let program = Program {
+        constants: &bytecode.Constants,
+        numGlobals: bytecode.NumGlobals,
+        numBuiltins: builtins::Names().len(),
+    };
+    let mut bodies = vec![(&bytecode.Instructions[..], bytecode.NumLocals, None)];
+    for (i, c) in bytecode.Constants.iter().enumerate() {
+        if let object::Object::CompiledFunction { Fn } = c {
+            if Fn.NumLocals > 256 || Fn.NumParameters > Fn.NumLocals {
+                return Err(Error::InvalidInstructions(format!(
+                    "function {} has {} locals for {} parameters",
+                    i, Fn.NumLocals, Fn.NumParameters
+                )));
+            }
+            bodies.push((&Fn.Instructions[..], Fn.NumLocals, Some(i)));
+        }
+    }
+    let decoded = bodies
+        .iter()
+        .map(|(ins, ..)| decode(ins))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // a function's body may only use the free variables every OpClosure for it captures.
+    let mut numFree = vec![None; bytecode.Constants.len()];
+    for ins in decoded.iter().flatten() {
+        if ins.op != code::OpClosure {
+            continue;
+        }
+        let (idx, n) = (ins.operands[0], ins.operands[1]);
+        if !matches!(
+            bytecode.Constants.get(idx),
+            Some(object::Object::CompiledFunction { .. })
+        ) {
+            return Err(Error::InvalidInstructions(format!(
+                "OpClosure at {} refers to constant {}, which is not a function",
+                ins.pos, idx
+            )));
+        }
+        match numFree[idx] {
+            Some(m) if m != n => {
+                return Err(Error::InvalidInstructions(format!(
+                    "function {} is closed over both {} and {} variables",
+                    idx, m, n
+                )))
+            }
+            _ => numFree[idx] = Some(n),
+        }
+    }
+
+    for ((ins, numLocals, idx), body) in bodies.iter().zip(decoded.iter()) {
+        let free = idx.and_then(|i| numFree[i]).unwrap_or(0);
+        verify(&program, body, ins.len(), *numLocals, free, idx.is_none())?;
+    }
+    Ok(())
+}
+
+// decode splits ins into instructions, checking that every opcode is known and no operand
+// runs past the end.
+fn decode(ins: &[u8]) -> Result<Vec<Instruction>, Error> {
+    let mut out = vec![];
+    let mut i = 0;
+    while i < ins.len() {
+        let def = code::Lookup(ins[i])
+            .ok_or_else(|| Error::InvalidInstructions(format!("unknown opcode {}", ins[i])))?;
+        let width: usize = def.OperandWidths.iter().sum();
+        if i + 1 + width > ins.len() {
+            return Err(Error::InvalidInstructions(format!(
+                "truncated {} at {}",
+                def.Name, i
+            )));
+        }
+        let (operands, read) = code::ReadOperands(&def, &ins[i + 1..]);
+        out.push(Instruction {
+            pos: i,
+            op: ins[i],
+            operands,
+        });
+        i += 1 + read;
+    }
+    Ok(out)
+}
+
+// verify checks that every constant, builtin, global, local and free variable a body refers to
+// exists, that jumps land on the start of an instruction and that no instruction pops more
+// values than the body pushed before it.
+fn verify(
+    program: &Program,
+    body: &[Instruction],
+    len: usize,
+    numLocals: usize,
+    numFree: usize,
+    main: bool,
+) -> Result<(), Error> {
+    // the index of the instruction at each position, and of the end of the body.
+    let mut at: HashMap<usize, usize> = body
+        .iter()
+        .enumerate()
+        .map(|(i, ins)| (ins.pos, i))
+        .collect();
+    at.insert(len, body.len());
+
+    let mut targets = vec![None; body.len()];
+    for (i, ins) in body.iter().enumerate() {
+        let def = code::Lookup(ins.op).unwrap();
+        let operands = &ins.operands;
+        let mut valid = match ins.op {
+            code::OpConstant => operands[0] < program.constants.len(),
+            code::OpGetBuiltin => operands[0] < program.numBuiltins,
+            code::OpGetGlobal | code::OpSetGlobal => operands[0] < program.numGlobals,
+            code::OpGetLocal | code::OpSetLocal | code::OpCaptureLocal => operands[0] < numLocals,
+            code::OpFreshLocals => operands[0] + operands[1] <= numLocals,
+            code::OpGetFree | code::OpSetFree | code::OpCaptureFree => operands[0] < numFree,
+            code::OpHash => operands[0] % 2 == 0,
+            // the main program has no caller to return to.
+            code::OpReturn => !main,
+            _ => true,
+        };
+        if let Some(j) = def.JumpOperand {
+            targets[i] = at.get(&operands[j]).copied();
+            valid = valid && targets[i].is_some();
+        }
+        if !valid {
+            return Err(Error::InvalidInstructions(format!(
+                "invalid {} {:?} at {}",
+                def.Name, operands, ins.pos
+            )));
+        }
+    }
+
+    // the fewest values on the stack when each instruction runs, over every path to it. the
+    // heights only go down, so this stops even for loops.
+    let mut heights: Vec<Option<usize>> = vec![None; body.len() + 1];
+    heights[0] = Some(0);
+    let mut work = vec![0];
+    while let Some(i) = work.pop() {
+        if i == body.len() {
+            continue;
+        }
+        let ins = &body[i];
+        let height = heights[i].unwrap();
+        let (pops, pushes) = stackEffect(ins.op, &ins.operands);
+        if height < pops {
+            return Err(Error::InvalidInstructions(format!(
+                "{} at {} pops {} values from a stack of {}",
+                code::Lookup(ins.op).unwrap().Name,
+                ins.pos,
+                pops,
+                height
+            )));
+        }
+        let height = height - pops;
+        let next = match ins.op {
+            code::OpReturnValue | code::OpReturn => vec![],
+            code::OpJump => vec![(targets[i].unwrap(), height)],
+            code::OpJumpNotTruthy => vec![(i + 1, height), (targets[i].unwrap(), height)],
+            // pushes only when it does not jump to the end of the loop.
+            code::OpIterNext => vec![(i + 1, height + pushes), (targets[i].unwrap(), height)],
+            _ => vec![(i + 1, height + pushes)],
+        };
+        for (j, h) in next {
+            if heights[j].is_none_or(|old| h < old) {
+                heights[j] = Some(h);
+                work.push(j);
+            }
+        }
+    }
+    Ok(())
+}
+
+// stackEffect is how many values an instruction pops and then pushes.
+fn stackEffect(op: code::Opcode, operands: &[usize]) -> (usize, usize) {
+    match op {
+        code::OpConstant
+        | code::OpTrue
+        | code::OpFalse
+        | code::OpNull
+        | code::OpGetGlobal
+        | code::OpGetLocal
+        | code::OpGetBuiltin
+        | code::OpGetFree
+        | code::OpClosure => (0, 1),
+        code::OpPop
+        | code::OpSetGlobal
+        | code::OpSetLocal
+        | code::OpSetFree
+        | code::OpJumpNotTruthy
+        | code::OpReturnValue => (1, 0),
+        code::OpAdd
+        | code::OpSub
+        | code::OpMul
+        | code::OpDiv
+        | code::OpMod
+        | code::OpEqual
+        | code::OpNotEqual
+        | code::OpGreaterThan
+        | code::OpLessThan
+        | code::OpGreaterThanOrEqual
+        | code::OpLessThanOrEqual
+        | code::OpRange
+        | code::OpIndex => (2, 1),
+        code::OpMinus | code::OpBang | code::OpIter => (1, 1),
+        code::OpArray | code::OpHash => (operands[0], 1),
+        code::OpCall => (operands[0] + 1, 1),
+        // the step it pops, then the next step and each loop variable.
+        code::OpIterNext => (1, 1 + operands[1]),
+        _ => (0, 0),
+    }
+}
+
+// checksum is the crc-32 used by zip and png.
+fn checksum(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn writeLen(out: &mut Vec<u8>, n: usize) {
+    out.extend_from_slice(&(n as u32).to_be_bytes());
+}
+
+fn writeBytes(out: &mut Vec<u8>, b: &[u8]) {
+    writeLen(out, b.len());
+    out.extend_from_slice(b);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() - self.pos < n {
+            return Err(Error::Truncated);
+        }
+        let b = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(b)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let n = self.u32()? as usize;
+        self.take(n)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| Error::InvalidUtf8)
+    }
+}
@@ -3,22 +3,56 @@ use std::fmt;
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Statement {
+    // let x = value, or const x = value when Token is const.
     LetStatement {
         Token: token::Token,
         Name: Identifier,
         Value: Expression,
+        Span: token::Span,
+    },
+    // x = value, or x += value and the like. Token is the operator.
+    AssignStatement {
+        Token: token::Token,
+        Name: Identifier,
+        Value: Expression,
+        Span: token::Span,
     },
     ReturnStatement {
         Token: token::Token,
         ReturnValue: Expression,
+        Span: token::Span,
     },
     ExpressionStatement {
         Token: token::Token,
         Expression: Expression,
+        Span: token::Span,
     },
     BlockStatement {
         Token: token::Token,
         Statements: Vec<Statement>,
+        Span: token::Span,
+    },
+    WhileStatement {
+        Token: token::Token,
+        Condition: Expression,
+        Body: Box<Statement>,
+        Span: token::Span,
+    },
+    // for (x in xs) { ... } or, with two variables, for (k, v in xs) { ... }.
+    ForStatement {
+        Token: token::Token,
+        Variables: Vec<Identifier>,
+        Iterable: Expression,
+        Body: Box<Statement>,
+        Span: token::Span,
+    },
+    BreakStatement {
+        Token: token::Token,
+        Span: token::Span,
+    },
+    ContinueStatement {
+        Token: token::Token,
+        Span: token::Span,
     },
     Nil,
 }
@@ -26,7 +60,9 @@ pub enum Statement {
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Statement::LetStatement { Token, Name, Value } => write!(
+            Statement::LetStatement {
+                Token, Name, Value, ..
+            } => write!(
                 f,
                 "Statement::LetStatement{{Token: {}, Name: {}, Value: {}}}",
                 Token, Name, Value
@@ -37,9 +73,26 @@ impl fmt::Display for Statement {
 }
 
 impl Statement {
+    pub fn Span(&self) -> token::Span {
+        match self {
+            Statement::LetStatement { Span, .. } => *Span,
+            Statement::AssignStatement { Span, .. } => *Span,
+            Statement::ReturnStatement { Span, .. } => *Span,
+            Statement::ExpressionStatement { Span, .. } => *Span,
+            Statement::BlockStatement { Span, .. } => *Span,
+            Statement::WhileStatement { Span, .. } => *Span,
+            Statement::ForStatement { Span, .. } => *Span,
+            Statement::BreakStatement { Span, .. } => *Span,
+            Statement::ContinueStatement { Span, .. } => *Span,
+            Statement::Nil => token::Span::default(),
+        }
+    }
+
     pub fn into_string(&self) -> String {
         match self {
-            Statement::LetStatement { Token, Name, Value } => {
+            Statement::LetStatement {
+                Token, Name, Value, ..
+            } => {
                 Token.Literal.clone()
                     + " "
                     + &Name.into_string()
@@ -47,18 +100,40 @@ impl Statement {
                     + &Value.into_string()
                     + ";"
             }
-            Statement::ReturnStatement { Token, ReturnValue } => {
-                Token.Literal.clone() + " " + &ReturnValue.into_string() + ";"
-            }
-            Statement::ExpressionStatement { Token, Expression } => Expression.into_string(),
-            Statement::BlockStatement { Token, Statements } => {
+            Statement::AssignStatement {
+                Token, Name, Value, ..
+            } => Name.into_string() + " " + &Token.Literal + " " + &Value.into_string() + ";",
+            Statement::ReturnStatement {
+                Token, ReturnValue, ..
+            } => Token.Literal.clone() + " " + &ReturnValue.into_string() + ";",
+            Statement::ExpressionStatement { Expression, .. } => Expression.into_string(),
+            Statement::BlockStatement { Statements, .. } => {
                 let mut buf = "".to_string();
                 for s in Statements.iter() {
                     buf += &s.into_string();
                 }
                 buf
             }
-            Nil => "".to_string(),
+            Statement::WhileStatement {
+                Condition, Body, ..
+            } => String::from("while") + &Condition.into_string() + " " + &Body.into_string(),
+            Statement::ForStatement {
+                Variables,
+                Iterable,
+                Body,
+                ..
+            } => {
+                let vars: Vec<String> = Variables.iter().map(|v| v.into_string()).collect();
+                format!(
+                    "for({} in {}) {}",
+                    vars.join(", "),
+                    Iterable.into_string(),
+                    Body.into_string()
+                )
+            }
+            Statement::BreakStatement { .. } => String::from("break;"),
+            Statement::ContinueStatement { .. } => String::from("continue;"),
+            Statement::Nil => "".to_string(),
         }
     }
 }
@@ -93,51 +168,93 @@ pub enum Expression {
         Token: token::Token,
         Operator: String,
         Right: Box<Expression>,
+        Span: token::Span,
     },
     InfixExpression {
         Token: token::Token,
         Left: Box<Expression>,
         Operator: String,
         Right: Box<Expression>,
+        Span: token::Span,
     },
     Boolean {
         Token: token::Token,
         Value: bool,
     },
+    StringLiteral {
+        Token: token::Token,
+        Value: String,
+    },
+    ArrayLiteral {
+        Token: token::Token,
+        Elements: Vec<Expression>,
+        Span: token::Span,
+    },
+    HashLiteral {
+        Token: token::Token,
+        Pairs: Vec<(Expression, Expression)>,
+        Span: token::Span,
+    },
+    IndexExpression {
+        Token: token::Token,
+        Left: Box<Expression>,
+        Index: Box<Expression>,
+        Span: token::Span,
+    },
     IfExpression {
         Token: token::Token,
         Condition: Box<Expression>,
         Consequence: Box<Statement>,
         Alternative: Box<Statement>,
+        Span: token::Span,
     },
     FunctionLiteral {
         Token: token::Token,
         Parameters: Vec<Expression>,
         Body: Box<Statement>,
+        Span: token::Span,
     },
     CallExpression {
         Token: token::Token,
         Function: Box<Expression>,
         Arguments: Vec<Expression>,
+        Span: token::Span,
     },
 }
 
 impl Expression {
+    // Span covers the whole expression. single-token expressions use the span of their token.
+    pub fn Span(&self) -> token::Span {
+        match self {
+            Expression::Nil => token::Span::default(),
+            Expression::Identifier(Identifier) => Identifier.Token.Span,
+            Expression::IntergerLiteral { Token, .. } => Token.Span,
+            Expression::Boolean { Token, .. } => Token.Span,
+            Expression::StringLiteral { Token, .. } => Token.Span,
+            Expression::PrefixExpression { Span, .. } => *Span,
+            Expression::InfixExpression { Span, .. } => *Span,
+            Expression::ArrayLiteral { Span, .. } => *Span,
+            Expression::HashLiteral { Span, .. } => *Span,
+            Expression::IndexExpression { Span, .. } => *Span,
+            Expression::IfExpression { Span, .. } => *Span,
+            Expression::FunctionLiteral { Span, .. } => *Span,
+            Expression::CallExpression { Span, .. } => *Span,
+        }
+    }
+
     pub fn into_string(&self) -> String {
         match self {
             Expression::Nil => String::from(""),
             Expression::Identifier(Identifier) => Identifier.Value.clone(),
-            Expression::IntergerLiteral { Token, Value } => Token.Literal.clone(),
+            Expression::IntergerLiteral { Token, .. } => Token.Literal.clone(),
             Expression::PrefixExpression {
-                Token,
-                Operator,
-                Right,
+                Operator, Right, ..
             } => String::from("(") + Operator + Right.into_string().as_str() + ")",
             Expression::InfixExpression {
-                Token,
                 Left,
                 Operator,
                 Right,
+                ..
             } => {
                 String::from("(")
                     + Left.into_string().as_str()
@@ -147,12 +264,30 @@ impl Expression {
                     + Right.into_string().as_str()
                     + ")"
             }
-            Expression::Boolean { Token, Value } => Token.Literal.clone(),
+            Expression::Boolean { Token, .. } => Token.Literal.clone(),
+            Expression::StringLiteral { Token, .. } => Token.Literal.clone(),
+            Expression::ArrayLiteral { Elements, .. } => {
+                let mut elements = vec![];
+                for e in Elements.iter() {
+                    elements.push(e.into_string());
+                }
+                String::from("[") + &elements.join(", ") + "]"
+            }
+            Expression::HashLiteral { Pairs, .. } => {
+                let mut pairs = vec![];
+                for (key, value) in Pairs.iter() {
+                    pairs.push(key.into_string() + ":" + &value.into_string());
+                }
+                String::from("{") + &pairs.join(", ") + "}"
+            }
+            Expression::IndexExpression { Left, Index, .. } => {
+                String::from("(") + &Left.into_string() + "[" + &Index.into_string() + "])"
+            }
             Expression::IfExpression {
-                Token,
                 Condition,
                 Consequence,
                 Alternative,
+                ..
             } => {
                 if let Statement::Nil = Alternative.as_ref() {
                     String::from("if") + &Condition.into_string() + " " + &Consequence.into_string()
@@ -169,6 +304,7 @@ impl Expression {
                 Token,
                 Parameters,
                 Body,
+                ..
             } => {
                 let mut params = vec![];
                 for p in Parameters.iter() {
@@ -177,9 +313,9 @@ impl Expression {
                 Token.Literal.clone() + "(" + &params.join(", ") + ") " + &Body.into_string()
             }
             Expression::CallExpression {
-                Token,
                 Function,
                 Arguments,
+                ..
             } => {
                 let mut args = vec![];
                 for a in Arguments.iter() {
@@ -209,4 +345,188 @@ impl Program {
         }
         res
     }
+
+    // Dump renders the tree one node per line, children indented below their parent, with the
+    // position each node starts at.
+    pub fn Dump(&self) -> String {
+        let mut out = String::from("Program\n");
+        for s in self.Statements.iter() {
+            dumpStatement(s, 1, &mut out);
+        }
+        out
+    }
+}
+
+fn dumpLine(out: &mut String, depth: usize, kind: &str, span: token::Span, detail: &str) {
+    *out += &"  ".repeat(depth);
+    *out += &format!("{} {}", kind, span);
+    if !detail.is_empty() {
+        *out += " ";
+        *out += detail;
+    }
+    *out += "\n";
+}
+
+fn dumpStatement(stmt: &Statement, depth: usize, out: &mut String) {
+    match stmt {
+        Statement::LetStatement {
+            Token,
+            Name,
+            Value,
+            Span,
+        } => {
+            let kind = if Token.Type == token::CONST {
+                "ConstStatement"
+            } else {
+                "LetStatement"
+            };
+            dumpLine(out, depth, kind, *Span, &Name.Value);
+            dumpExpression(Value, depth + 1, out);
+        }
+        Statement::AssignStatement {
+            Token,
+            Name,
+            Value,
+            Span,
+        } => {
+            let detail = format!("{} {}", Name.Value, Token.Literal);
+            dumpLine(out, depth, "AssignStatement", *Span, &detail);
+            dumpExpression(Value, depth + 1, out);
+        }
+        Statement::ReturnStatement {
+            ReturnValue, Span, ..
+        } => {
+            dumpLine(out, depth, "ReturnStatement", *Span, "");
+            dumpExpression(ReturnValue, depth + 1, out);
+        }
+        Statement::ExpressionStatement {
+            Expression, Span, ..
+        } => {
+            dumpLine(out, depth, "ExpressionStatement", *Span, "");
+            dumpExpression(Expression, depth + 1, out);
+        }
+        Statement::BlockStatement {
+            Statements, Span, ..
+        } => {
+            dumpLine(out, depth, "BlockStatement", *Span, "");
+            for s in Statements.iter() {
+                dumpStatement(s, depth + 1, out);
+            }
+        }
+        Statement::WhileStatement {
+            Condition,
+            Body,
+            Span,
+            ..
+        } => {
+            dumpLine(out, depth, "WhileStatement", *Span, "");
+            dumpExpression(Condition, depth + 1, out);
+            dumpStatement(Body, depth + 1, out);
+        }
+        Statement::ForStatement {
+            Variables,
+            Iterable,
+            Body,
+            Span,
+            ..
+        } => {
+            let vars: Vec<String> = Variables.iter().map(|v| v.into_string()).collect();
+            dumpLine(out, depth, "ForStatement", *Span, &vars.join(", "));
+            dumpExpression(Iterable, depth + 1, out);
+            dumpStatement(Body, depth + 1, out);
+        }
+        Statement::BreakStatement { Span, .. } => dumpLine(out, depth, "BreakStatement", *Span, ""),
+        Statement::ContinueStatement { Span, .. } => {
+            dumpLine(out, depth, "ContinueStatement", *Span, "")
+        }
+        Statement::Nil => {}
+    }
+}
+
+fn dumpExpression(exp: &Expression, depth: usize, out: &mut String) {
+    let span = exp.Span();
+    match exp {
+        Expression::Nil => {}
+        Expression::Identifier(Identifier) => {
+            dumpLine(out, depth, "Identifier", span, &Identifier.Value)
+        }
+        Expression::IntergerLiteral { Value, .. } => {
+            dumpLine(out, depth, "IntegerLiteral", span, &Value.to_string())
+        }
+        Expression::Boolean { Value, .. } => {
+            dumpLine(out, depth, "Boolean", span, &Value.to_string())
+        }
+        Expression::StringLiteral { Value, .. } => {
+            dumpLine(out, depth, "StringLiteral", span, &format!("{:?}", Value))
+        }
+        Expression::PrefixExpression {
+            Operator, Right, ..
+        } => {
+            dumpLine(out, depth, "PrefixExpression", span, Operator);
+            dumpExpression(Right, depth + 1, out);
+        }
+        Expression::InfixExpression {
+            Left,
+            Operator,
+            Right,
+            ..
+        } => {
+            dumpLine(out, depth, "InfixExpression", span, Operator);
+            dumpExpression(Left, depth + 1, out);
+            dumpExpression(Right, depth + 1, out);
+        }
+        Expression::ArrayLiteral { Elements, .. } => {
+            dumpLine(out, depth, "ArrayLiteral", span, "");
+            for e in Elements.iter() {
+                dumpExpression(e, depth + 1, out);
+            }
+        }
+        Expression::HashLiteral { Pairs, .. } => {
+            dumpLine(out, depth, "HashLiteral", span, "");
+            for (key, value) in Pairs.iter() {
+                dumpExpression(key, depth + 1, out);
+                dumpExpression(value, depth + 2, out);
+            }
+        }
+        Expression::IndexExpression { Left, Index, .. } => {
+            dumpLine(out, depth, "IndexExpression", span, "");
+            dumpExpression(Left, depth + 1, out);
+            dumpExpression(Index, depth + 1, out);
+        }
+        Expression::IfExpression {
+            Condition,
+            Consequence,
+            Alternative,
+            ..
+        } => {
+            dumpLine(out, depth, "IfExpression", span, "");
+            dumpExpression(Condition, depth + 1, out);
+            dumpStatement(Consequence, depth + 1, out);
+            dumpStatement(Alternative, depth + 1, out);
+        }
+        Expression::FunctionLiteral {
+            Parameters, Body, ..
+        } => {
+            let params: Vec<String> = Parameters.iter().map(|p| p.into_string()).collect();
+            dumpLine(
+                out,
+                depth,
+                "FunctionLiteral",
+                span,
+                &format!("({})", params.join(", ")),
+            );
+            dumpStatement(Body, depth + 1, out);
+        }
+        Expression::CallExpression {
+            Function,
+            Arguments,
+            ..
+        } => {
+            dumpLine(out, depth, "CallExpression", span, "");
+            dumpExpression(Function, depth + 1, out);
+            for a in Arguments.iter() {
+                dumpExpression(a, depth + 1, out);
+            }
+        }
+    }
 }
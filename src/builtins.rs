@@ -0,0 +1,153 @@
+use crate::object;
+use crate::object::Object;
+use crate::token;
+use phf::phf_map;
+use std::io::{self, Write};
+use std::process;
+
+static builtins: phf::Map<&'static str, object::BuiltinFunction> = phf_map! {
+    "len" => len,
+    "first" => first,
+    "last" => last,
+    "rest" => rest,
+    "push" => push,
+    "puts" => puts,
+};
+
+pub fn Lookup(name: &str) -> Option<Object> {
+    builtins
+        .get_entry(name)
+        .map(|(name, f)| Object::Builtin { Name: name, Fn: *f })
+}
+
+pub fn Names() -> Vec<&'static str> {
+    let mut res: Vec<&'static str> = builtins.keys().copied().collect();
+    res.sort_unstable();
+    res
+}
+
+fn newError(format: String) -> Object {
+    Object::Error {
+        Message: format,
+        Span: token::Span::default(),
+        Trace: vec![],
+    }
+}
+
+fn checkArgs(args: &[Object], want: usize) -> Option<Object> {
+    if args.len() != want {
+        Some(newError(format!(
+            "wrong number of arguments. got={}, want={}",
+            args.len(),
+            want
+        )))
+    } else {
+        None
+    }
+}
+
+fn len(args: Vec<Object>) -> Object {
+    if let Some(err) = checkArgs(&args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::String { Value } => Object::Integer {
+            Value: Value.chars().count() as i64,
+        },
+        Object::Array { Elements } => Object::Integer {
+            Value: Elements.len() as i64,
+        },
+        Object::Hash { Pairs } => Object::Integer {
+            Value: Pairs.len() as i64,
+        },
+        other => newError(format!(
+            "argument to `len` not supported, got {}",
+            other.Type()
+        )),
+    }
+}
+
+fn first(args: Vec<Object>) -> Object {
+    if let Some(err) = checkArgs(&args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::Array { Elements } => Elements.first().cloned().unwrap_or(Object::Null),
+        other => newError(format!(
+            "argument to `first` must be ARRAY, got {}",
+            other.Type()
+        )),
+    }
+}
+
+fn last(args: Vec<Object>) -> Object {
+    if let Some(err) = checkArgs(&args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::Array { Elements } => Elements.last().cloned().unwrap_or(Object::Null),
+        other => newError(format!(
+            "argument to `last` must be ARRAY, got {}",
+            other.Type()
+        )),
+    }
+}
+
+fn rest(args: Vec<Object>) -> Object {
+    if let Some(err) = checkArgs(&args, 1) {
+        return err;
+    }
+    match &args[0] {
+        Object::Array { Elements } if Elements.is_empty() => Object::Null,
+        Object::Array { Elements } => Object::Array {
+            Elements: Elements[1..].to_vec(),
+        },
+        other => newError(format!(
+            "argument to `rest` must be ARRAY, got {}",
+            other.Type()
+        )),
+    }
+}
+
+fn push(args: Vec<Object>) -> Object {
+    if let Some(err) = checkArgs(&args, 2) {
+        return err;
+    }
+    match &args[0] {
+        Object::Array { Elements } => {
+            let mut elements = Elements.clone();
+            elements.push(args[1].clone());
+            Object::Array { Elements: elements }
+        }
+        other => newError(format!(
+            "argument to `push` must be ARRAY, got {}",
+            other.Type()
+        )),
+    }
+}
+
+// strings are printed without quotes, everything else uses Inspect.
+fn puts(args: Vec<Object>) -> Object {
+    let mut out = io::stdout().lock();
+    for arg in args.iter() {
+        let written = match arg {
+            Object::String { Value } => writeln!(out, "{}", Value),
+            _ => writeln!(out, "{}", arg.Inspect()),
+        };
+        if let Err(msg) = CheckStdout(written) {
+            return newError(msg);
+        }
+    }
+    Object::Null
+}
+
+// CheckStdout turns a failed write to stdout into an error message. when stdout is a pipe
+// whose reader has gone away, as in `monkey script.mk | head -1`, nobody wants more output,
+// so the program exits quietly instead.
+pub fn CheckStdout(written: io::Result<()>) -> Result<(), String> {
+    match written {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+        Err(err) => Err(format!("cannot write to stdout: {}", err)),
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{
+    artifact, ast, builtins, compiler, diagnostics, evaluator, formatter, lexer, object, repl, vm,
+};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+
+// exit codes, following sysexits(3) where one fits.
+pub const ExitOk: i32 = 0;
+pub const ExitRuntimeError: i32 = 1;
+pub const ExitUnformatted: i32 = 1;
+pub const ExitUsage: i32 = 64;
+pub const ExitParseError: i32 = 65;
+pub const ExitNoInput: i32 = 66;
+
+const USAGE: &str = "usage: monkey [options] [script | -e expr]
+       monkey compile <script> [-o out.mkc]
+       monkey run <program.mkc>
+       monkey fmt [--check] [script...]
+
+With no script and no -e the program is read from stdin, or the repl starts if stdin is a
+terminal. A script starting with the compiled-program header is run directly.
+
+fmt rewrites each script in the standard layout, or formats stdin to stdout if none are
+given. With --check nothing is written; the scripts that are not formatted are listed.
+
+options:
+    -e <expr>        evaluate expr and print its value
+    --dump-tokens    print the tokens of the program and stop
+    --dump-ast       print the syntax tree of the program and stop
+    --check          parse the program and report errors without running it
+    --vm             run on the bytecode vm instead of the evaluator
+    --repl           start the repl even if stdin is not a terminal
+    --no-color       do not color errors or repl input and output. setting NO_COLOR
+                     does the same
+    -h, --help       print this help
+
+exit status:
+    0   success
+    1   runtime error, or fmt --check found unformatted input
+    64  bad command line
+    65  syntax or compile error
+    66  input could not be read";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Source {
+    File(String),
+    Expr(String),
+    Stdin,
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    source: Option<Source>,
+    dumpTokens: bool,
+    dumpAst: bool,
+    check: bool,
+    useVm: bool,
+    repl: bool,
+    noColor: bool,
+    help: bool,
+}
+
+// Main runs the command line given by args (without the program name) and returns the exit
+// status.
+pub fn Main(args: &[String]) -> i32 {
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    match args.as_slice() {
+        ["compile", src] => return compile(src, &Path::new(src).with_extension("mkc")),
+        ["compile", src, "-o", out] => return compile(src, Path::new(out)),
+        ["run", path] => return runArtifact(path),
+        ["fmt", rest @ ..] => return format(rest),
+        _ => {}
+    }
+
+    let mut opts = match parseArgs(&args) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            eprintln!("monkey: {}\n\n{}", msg, USAGE);
+            return ExitUsage;
+        }
+    };
+    if opts.help {
+        println!("{}", USAGE);
+        return ExitOk;
+    }
+    // https://no-color.org
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        opts.noColor = true;
+    }
+
+    let source = match opts.source {
+        Some(ref s) => s,
+        None if opts.repl || io::stdin().is_terminal() => {
+            println!("Hello! This is the Monkey programming language!");
+            println!("Feel free to type in commands");
+            repl::Start(!opts.noColor);
+            return ExitOk;
+        }
+        None => &Source::Stdin,
+    };
+
+    let (filename, input) = match readSource(source) {
+        Ok(x) => x,
+        Err(msg) => {
+            eprintln!("monkey: {}", msg);
+            return ExitNoInput;
+        }
+    };
+    if input.starts_with(artifact::Magic) {
+        return runArtifact(&filename);
+    }
+    let input = match String::from_utf8(input) {
+        Ok(s) => s,
+        Err(_) => {
+            eprintln!("monkey: {}: not valid utf-8", filename);
+            return ExitNoInput;
+        }
+    };
+
+    runSource(&opts, &filename, &input)
+}
+
+fn parseArgs(args: &[&str]) -> Result<Options, String> {
+    let mut opts = Options::default();
+    let mut i = 0;
+    while i < args.len() {
+        let source = match args[i] {
+            "-h" | "--help" => {
+                opts.help = true;
+                None
+            }
+            "--dump-tokens" => {
+                opts.dumpTokens = true;
+                None
+            }
+            "--dump-ast" => {
+                opts.dumpAst = true;
+                None
+            }
+            "--check" => {
+                opts.check = true;
+                None
+            }
+            "--vm" => {
+                opts.useVm = true;
+                None
+            }
+            "--repl" => {
+                opts.repl = true;
+                None
+            }
+            "--no-color" => {
+                opts.noColor = true;
+                None
+            }
+            "-e" => {
+                i += 1;
+                match args.get(i) {
+                    Some(expr) => Some(Source::Expr(expr.to_string())),
+                    None => return Err(String::from("-e requires an expression")),
+                }
+            }
+            "-" => Some(Source::Stdin),
+            arg if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            path => Some(Source::File(path.to_string())),
+        };
+        if let Some(s) = source {
+            if opts.source.is_some() {
+                return Err(String::from(
+                    "only one script or -e expression can be given",
+                ));
+            }
+            opts.source = Some(s);
+        }
+        i += 1;
+    }
+    Ok(opts)
+}
+
+// readSource returns the name diagnostics refer to the program by and its contents.
+fn readSource(source: &Source) -> Result<(String, Vec<u8>), String> {
+    match source {
+        Source::File(path) => match fs::read(path) {
+            Ok(data) => Ok((path.clone(), data)),
+            Err(e) => Err(format!("{}: {}", path, e)),
+        },
+        Source::Expr(expr) => Ok((String::from("<expr>"), expr.clone().into_bytes())),
+        Source::Stdin => {
+            let mut data = vec![];
+            match io::stdin().read_to_end(&mut data) {
+                Ok(_) => Ok((String::from("<stdin>"), data)),
+                Err(e) => Err(format!("<stdin>: {}", e)),
+            }
+        }
+    }
+}
+
+fn runSource(opts: &Options, filename: &str, input: &str) -> i32 {
+    let mode = diagnosticsMode(opts.noColor);
+
+    if opts.dumpTokens && writeOut(&lexer::Dump(input)) != ExitOk {
+        return ExitRuntimeError;
+    }
+
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    if !p.Errors().is_empty() {
+        for err in p.Errors().iter() {
+            let d = diagnostics::FromParseError(err, input);
+            eprint!("{}", d.Render(input, filename, mode));
+        }
+        return ExitParseError;
+    }
+
+    if opts.dumpAst && writeOut(&program.Dump()) != ExitOk {
+        return ExitRuntimeError;
+    }
+    if opts.check || opts.dumpTokens || opts.dumpAst {
+        return ExitOk;
+    }
+
+    let printResult = matches!(opts.source, Some(Source::Expr(_)));
+    if opts.useVm {
+        runVm(&program, filename, printResult)
+    } else {
+        runEvaluator(program, filename, input, printResult, mode)
+    }
+}
+
+fn runEvaluator(
+    program: ast::Program,
+    filename: &str,
+    input: &str,
+    printResult: bool,
+    mode: diagnostics::Mode,
+) -> i32 {
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+    if let Some(d) = diagnostics::FromRuntimeError(&evaluated, &env.borrow()) {
+        eprint!("{}", d.Render(input, filename, mode));
+        return ExitRuntimeError;
+    }
+    if printResult {
+        return printValue(&evaluated);
+    }
+    ExitOk
+}
+
+fn runVm(program: &ast::Program, filename: &str, printResult: bool) -> i32 {
+    let mut comp = compiler::New();
+    if let Err(err) = comp.Compile(program) {
+        eprintln!("error: {}\n --> {}", err, filename);
+        return ExitParseError;
+    }
+    let mut machine = vm::New(comp.Bytecode());
+    if let Err(err) = machine.Run() {
+        eprintln!("error: {}", err);
+        return ExitRuntimeError;
+    }
+    if printResult {
+        return printValue(&machine.LastPoppedStackElem());
+    }
+    ExitOk
+}
+
+// printValue prints the result of -e. strings are printed raw, as puts does, so the output
+// can be used directly in a pipeline.
+fn printValue(obj: &object::Object) -> i32 {
+    match obj {
+        object::Object::Null => ExitOk,
+        object::Object::String { Value } => writeOut(&format!("{}\n", Value)),
+        _ => writeOut(&format!("{}\n", obj.Inspect())),
+    }
+}
+
+// writeOut writes text to stdout and returns the exit status.
+fn writeOut(text: &str) -> i32 {
+    let mut out = io::stdout().lock();
+    let written = out.write_all(text.as_bytes()).and_then(|_| out.flush());
+    match builtins::CheckStdout(written) {
+        Ok(()) => ExitOk,
+        Err(msg) => {
+            eprintln!("monkey: {}", msg);
+            ExitRuntimeError
+        }
+    }
+}
+
+// compile parses and compiles the script at src and writes the program to out.
+fn compile(src: &str, out: &Path) -> i32 {
+    let input = match fs::read_to_string(src) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("monkey: {}: {}", src, e);
+            return ExitNoInput;
+        }
+    };
+
+    let l = lexer::New(input.clone());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    if !p.Errors().is_empty() {
+        for err in p.Errors().iter() {
+            let d = diagnostics::FromParseError(err, &input);
+            eprint!("{}", d.Render(&input, src, diagnosticsMode(false)));
+        }
+        return ExitParseError;
+    }
+
+    let mut comp = compiler::New();
+    if let Err(err) = comp.Compile(&program) {
+        eprintln!("error: {}\n --> {}", err, src);
+        return ExitParseError;
+    }
+    if let Err(err) = artifact::Save(&comp.Bytecode(), out) {
+        eprintln!("monkey: {}: {}", out.display(), err);
+        return ExitNoInput;
+    }
+    ExitOk
+}
+
+// format formats the scripts named in args, or stdin if there are none.
+fn format(args: &[&str]) -> i32 {
+    let check = args.contains(&"--check");
+    let paths: Vec<&str> = args.iter().copied().filter(|a| *a != "--check").collect();
+    if let Some(arg) = paths.iter().find(|a| a.starts_with('-') && **a != "-") {
+        eprintln!("monkey: unknown option {}\n\n{}", arg, USAGE);
+        return ExitUsage;
+    }
+
+    if paths.is_empty() || paths == ["-"] {
+        let (filename, input) = match readSource(&Source::Stdin) {
+            Ok((f, data)) => (f, String::from_utf8_lossy(&data).into_owned()),
+            Err(msg) => {
+                eprintln!("monkey: {}", msg);
+                return ExitNoInput;
+            }
+        };
+        return match formatSource(&filename, &input) {
+            Err(status) => status,
+            Ok(formatted) if check => {
+                if formatted == input {
+                    ExitOk
+                } else {
+                    writeOut(&format!("{}\n", filename)).max(ExitUnformatted)
+                }
+            }
+            Ok(formatted) => writeOut(&formatted),
+        };
+    }
+
+    let mut status = ExitOk;
+    for path in paths {
+        let input = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("monkey: {}: {}", path, e);
+                status = status.max(ExitNoInput);
+                continue;
+            }
+        };
+        let formatted = match formatSource(path, &input) {
+            Ok(f) => f,
+            Err(s) => {
+                status = status.max(s);
+                continue;
+            }
+        };
+        if formatted == input {
+            continue;
+        }
+        if check {
+            let written = writeOut(&format!("{}\n", path));
+            status = status.max(written).max(ExitUnformatted);
+        } else if let Err(e) = fs::write(path, formatted) {
+            eprintln!("monkey: {}: {}", path, e);
+            status = status.max(ExitNoInput);
+        }
+    }
+    status
+}
+
+fn formatSource(filename: &str, input: &str) -> Result<String, i32> {
+    formatter::Format(input).map_err(|errors| {
+        for err in errors.iter() {
+            let d = diagnostics::FromParseError(err, input);
+            eprint!("{}", d.Render(input, filename, diagnosticsMode(false)));
+        }
+        ExitParseError
+    })
+}
+
+// runArtifact executes a program produced by compile.
+fn runArtifact(path: &str) -> i32 {
+    let bytecode = match artifact::Load(path) {
+        Ok(b) => b,
+        Err(err) => {
+            eprintln!("monkey: {}: {}", path, err);
+            return ExitNoInput;
+        }
+    };
+    let mut machine = vm::New(bytecode);
+    if let Err(err) = machine.Run() {
+        eprintln!("error: {}", err);
+        return ExitRuntimeError;
+    }
+    ExitOk
+}
+
+fn diagnosticsMode(noColor: bool) -> diagnostics::Mode {
+    if !noColor && io::stderr().is_terminal() {
+        diagnostics::Mode::Color
+    } else {
+        diagnostics::Mode::Plain
+    }
+}
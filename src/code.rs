@@ -0,0 +1,219 @@
+use crate::{builtins, object};
+
+pub type Instructions = Vec<u8>;
+pub type Opcode = u8;
+
+pub const OpConstant: Opcode = 0;
+pub const OpAdd: Opcode = 1;
+pub const OpSub: Opcode = 2;
+pub const OpMul: Opcode = 3;
+pub const OpDiv: Opcode = 4;
+pub const OpPop: Opcode = 5;
+pub const OpTrue: Opcode = 6;
+pub const OpFalse: Opcode = 7;
+pub const OpEqual: Opcode = 8;
+pub const OpNotEqual: Opcode = 9;
+pub const OpGreaterThan: Opcode = 10;
+pub const OpLessThan: Opcode = 11;
+pub const OpMinus: Opcode = 12;
+pub const OpBang: Opcode = 13;
+pub const OpJumpNotTruthy: Opcode = 14;
+pub const OpJump: Opcode = 15;
+pub const OpNull: Opcode = 16;
+pub const OpGetGlobal: Opcode = 17;
+pub const OpSetGlobal: Opcode = 18;
+pub const OpArray: Opcode = 19;
+pub const OpHash: Opcode = 20;
+pub const OpIndex: Opcode = 21;
+pub const OpCall: Opcode = 22;
+pub const OpReturnValue: Opcode = 23;
+pub const OpReturn: Opcode = 24;
+pub const OpGetLocal: Opcode = 25;
+pub const OpSetLocal: Opcode = 26;
+pub const OpGetBuiltin: Opcode = 27;
+pub const OpClosure: Opcode = 28;
+pub const OpGetFree: Opcode = 29;
+pub const OpMod: Opcode = 31;
+pub const OpGreaterThanOrEqual: Opcode = 32;
+pub const OpLessThanOrEqual: Opcode = 33;
+pub const OpRange: Opcode = 34;
+pub const OpIter: Opcode = 35;
+pub const OpIterNext: Opcode = 36;
+pub const OpSetFree: Opcode = 37;
+pub const OpCaptureLocal: Opcode = 38;
+pub const OpCaptureFree: Opcode = 39;
+pub const OpFreshLocals: Opcode = 40;
+
+// Definition describes an opcode: its name, the width in bytes of each of its operands and
+// which operand, if any, is a jump target.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Definition {
+    pub Name: &'static str,
+    pub OperandWidths: &'static [usize],
+    pub JumpOperand: Option<usize>,
+}
+
+pub fn Lookup(op: Opcode) -> Option<Definition> {
+    let (name, widths, jump): (&'static str, &'static [usize], Option<usize>) = match op {
+        OpConstant => ("OpConstant", &[2], None),
+        OpAdd => ("OpAdd", &[], None),
+        OpSub => ("OpSub", &[], None),
+        OpMul => ("OpMul", &[], None),
+        OpDiv => ("OpDiv", &[], None),
+        OpPop => ("OpPop", &[], None),
+        OpTrue => ("OpTrue", &[], None),
+        OpFalse => ("OpFalse", &[], None),
+        OpEqual => ("OpEqual", &[], None),
+        OpNotEqual => ("OpNotEqual", &[], None),
+        OpGreaterThan => ("OpGreaterThan", &[], None),
+        OpLessThan => ("OpLessThan", &[], None),
+        OpMinus => ("OpMinus", &[], None),
+        OpBang => ("OpBang", &[], None),
+        OpJumpNotTruthy => ("OpJumpNotTruthy", &[2], Some(0)),
+        OpJump => ("OpJump", &[2], Some(0)),
+        OpNull => ("OpNull", &[], None),
+        OpGetGlobal => ("OpGetGlobal", &[2], None),
+        OpSetGlobal => ("OpSetGlobal", &[2], None),
+        OpArray => ("OpArray", &[2], None),
+        OpHash => ("OpHash", &[2], None),
+        OpIndex => ("OpIndex", &[], None),
+        OpCall => ("OpCall", &[1], None),
+        OpReturnValue => ("OpReturnValue", &[], None),
+        OpReturn => ("OpReturn", &[], None),
+        OpGetLocal => ("OpGetLocal", &[1], None),
+        OpSetLocal => ("OpSetLocal", &[1], None),
+        OpGetBuiltin => ("OpGetBuiltin", &[1], None),
+        // constant index of the function, number of free variables.
+        OpClosure => ("OpClosure", &[2, 1], None),
+        OpGetFree => ("OpGetFree", &[1], None),
+        OpMod => ("OpMod", &[], None),
+        OpGreaterThanOrEqual => ("OpGreaterThanOrEqual", &[], None),
+        OpLessThanOrEqual => ("OpLessThanOrEqual", &[], None),
+        OpRange => ("OpRange", &[], None),
+        // number of loop variables.
+        OpIter => ("OpIter", &[1], None),
+        // where to jump when the iterator is done, number of loop variables.
+        OpIterNext => ("OpIterNext", &[2, 1], Some(0)),
+        OpSetFree => ("OpSetFree", &[1], None),
+        // a variable OpClosure will capture: a local of the current function or one of its
+        // own free variables.
+        OpCaptureLocal => ("OpCaptureLocal", &[1], None),
+        OpCaptureFree => ("OpCaptureFree", &[1], None),
+        // first local, number of locals.
+        OpFreshLocals => ("OpFreshLocals", &[1, 1], None),
+        _ => return None,
+    };
+    Some(Definition {
+        Name: name,
+        OperandWidths: widths,
+        JumpOperand: jump,
+    })
+}
+
+// Make encodes an instruction. operands are stored big-endian. an unknown opcode yields an
+// empty instruction.
+pub fn Make(op: Opcode, operands: &[usize]) -> Instructions {
+    let def = match Lookup(op) {
+        Some(def) => def,
+        None => return vec![],
+    };
+
+    let mut instruction = vec![op];
+    for (o, width) in operands.iter().zip(def.OperandWidths.iter()) {
+        match width {
+            2 => instruction.extend_from_slice(&(*o as u16).to_be_bytes()),
+            1 => instruction.push(*o as u8),
+            _ => {}
+        }
+    }
+    instruction
+}
+
+// ReadOperands decodes the operands following an opcode and returns them along with the
+// number of bytes read.
+pub fn ReadOperands(def: &Definition, ins: &[u8]) -> (Vec<usize>, usize) {
+    let mut operands = vec![];
+    let mut offset = 0;
+    for width in def.OperandWidths.iter() {
+        match width {
+            2 => operands.push(ReadUint16(&ins[offset..]) as usize),
+            1 => operands.push(ReadUint8(&ins[offset..]) as usize),
+            _ => {}
+        }
+        offset += width;
+    }
+    (operands, offset)
+}
+
+pub fn ReadUint16(ins: &[u8]) -> u16 {
+    u16::from_be_bytes([ins[0], ins[1]])
+}
+
+pub fn ReadUint8(ins: &[u8]) -> u8 {
+    ins[0]
+}
+
+// Disassemble lists instructions one per line with their byte offset and operands. operands
+// that refer to the constant pool are followed by the constant they load and jumps by their
+// target. a malformed stream is reported inline instead of aborting the listing.
+pub fn Disassemble(ins: &[u8], constants: &[object::Object]) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < ins.len() {
+        let def = match Lookup(ins[i]) {
+            Some(def) => def,
+            None => {
+                out += &format!("{:04} ERROR: unknown opcode {}\n", i, ins[i]);
+                i += 1;
+                continue;
+            }
+        };
+        let width: usize = def.OperandWidths.iter().sum();
+        if i + 1 + width > ins.len() {
+            out += &format!("{:04} ERROR: truncated {}\n", i, def.Name);
+            break;
+        }
+
+        let (operands, read) = ReadOperands(&def, &ins[i + 1..]);
+        let mut line = format!("{:04} {}", i, def.Name);
+        for o in operands.iter() {
+            line += &format!(" {}", o);
+        }
+        if let Some(note) = annotate(ins[i], &def, &operands, constants) {
+            line = format!("{:<28}; {}", line, note);
+        }
+        out += &line;
+        out += "\n";
+        i += 1 + read;
+    }
+    out
+}
+
+fn annotate(
+    op: Opcode,
+    def: &Definition,
+    operands: &[usize],
+    constants: &[object::Object],
+) -> Option<String> {
+    if let Some(j) = def.JumpOperand {
+        return Some(format!("-> {:04}", operands[j]));
+    }
+    match op {
+        OpConstant | OpClosure => Some(match constants.get(operands[0]) {
+            Some(object::Object::CompiledFunction { Fn }) => format!("fn {}", FunctionName(Fn)),
+            Some(c) => c.Inspect(),
+            None => String::from("<missing constant>"),
+        }),
+        OpGetBuiltin => builtins::Names().get(operands[0]).map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+// FunctionName is how a compiled function is referred to in listings.
+pub fn FunctionName(f: &object::CompiledFunction) -> &str {
+    if f.Name.is_empty() {
+        "<anonymous>"
+    } else {
+        &f.Name
+    }
+}
@@ -0,0 +1,674 @@
+use crate::symbol_table::{self, Symbol, SymbolTable};
+use crate::{ast, builtins, code, object, token};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// Bytecode is the output of the compiler: the instructions of the main program and the
+// constant pool they refer to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bytecode {
+    pub Instructions: code::Instructions,
+    pub Constants: Vec<object::Object>,
+    // the local slots the main program uses, for the variables of loops at the top level.
+    pub NumLocals: usize,
+    // the global slots the program defines.
+    pub NumGlobals: usize,
+}
+
+impl Bytecode {
+    // Disassemble lists the main program, the constant pool and the body of every compiled
+    // function in it.
+    pub fn Disassemble(&self) -> String {
+        let mut out = String::from("main:\n");
+        out += &code::Disassemble(&self.Instructions, &self.Constants);
+
+        if !self.Constants.is_empty() {
+            out += "\nconstants:\n";
+            for (i, c) in self.Constants.iter().enumerate() {
+                let desc = match c {
+                    object::Object::CompiledFunction { Fn } => format!(
+                        "fn {} ({} params, {} locals)",
+                        code::FunctionName(Fn),
+                        Fn.NumParameters,
+                        Fn.NumLocals
+                    ),
+                    _ => c.Inspect(),
+                };
+                out += &format!("{:4} {}\n", i, desc);
+            }
+        }
+
+        for (i, c) in self.Constants.iter().enumerate() {
+            if let object::Object::CompiledFunction { Fn } = c {
+                out += &format!("\nfn {} (constant {}):\n", code::FunctionName(Fn), i);
+                out += &code::Disassemble(&Fn.Instructions, &self.Constants);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct EmittedInstruction {
+    Opcode: code::Opcode,
+    Position: usize,
+}
+
+// a loop being compiled: where continue jumps to, and the break jumps to patch once the end of
+// the loop is known.
+#[derive(Debug, Default)]
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+// each function body is compiled into its own scope.
+#[derive(Debug, Default)]
+struct CompilationScope {
+    instructions: code::Instructions,
+    lastInstruction: EmittedInstruction,
+    previousInstruction: EmittedInstruction,
+    loops: Vec<Loop>,
+}
+
+pub struct Compiler {
+    constants: Vec<object::Object>,
+    symbolTable: SymbolTable,
+    scopes: Vec<CompilationScope>,
+    // top-level names that have been defined ahead of their let statement.
+    pendingGlobals: HashSet<String>,
+}
+
+pub fn New() -> Compiler {
+    let mut symbolTable = symbol_table::NewSymbolTable();
+    for (i, name) in builtins::Names().iter().enumerate() {
+        symbolTable.DefineBuiltin(i, name);
+    }
+    NewWithState(symbolTable, vec![])
+}
+
+// NewWithState continues compiling with the symbols and constants of an earlier compilation,
+// e.g. the previous line of a REPL session.
+pub fn NewWithState(symbolTable: SymbolTable, constants: Vec<object::Object>) -> Compiler {
+    Compiler {
+        constants,
+        symbolTable,
+        scopes: vec![CompilationScope::default()],
+        pendingGlobals: HashSet::new(),
+    }
+}
+
+impl Compiler {
+    pub fn Compile(&mut self, program: &ast::Program) -> Result<(), String> {
+        // functions look globals up when they are called, as they do in the evaluator, so they
+        // may refer to globals defined further down. the names are defined up front and only
+        // code at the top level has to wait for the binding itself.
+        for s in program.Statements.iter() {
+            if let ast::Statement::LetStatement { Token, Name, .. } = s {
+                if self.symbolTable.Resolve(&Name.Value).is_none() {
+                    self.define(&Name.Value, Token.Type == token::CONST);
+                    self.pendingGlobals.insert(Name.Value.clone());
+                }
+            }
+        }
+        for s in program.Statements.iter() {
+            self.compileStatement(s)?;
+        }
+        Ok(())
+    }
+
+    pub fn Bytecode(&self) -> Bytecode {
+        Bytecode {
+            Instructions: self.currentInstructions().clone(),
+            Constants: self.constants.clone(),
+            NumLocals: self.symbolTable.NumMainLocals(),
+            NumGlobals: self.symbolTable.NumDefinitions(),
+        }
+    }
+
+    // State hands back the symbol table and constants so a later compiler can pick up where
+    // this one stopped.
+    pub fn State(self) -> (SymbolTable, Vec<object::Object>) {
+        (self.symbolTable, self.constants)
+    }
+
+    fn compileStatement(&mut self, stmt: &ast::Statement) -> Result<(), String> {
+        match stmt {
+            ast::Statement::ExpressionStatement { Expression, .. } => {
+                self.compileExpression(Expression)?;
+                self.emit(code::OpPop, &[]);
+            }
+            ast::Statement::BlockStatement { Statements, .. } => {
+                for s in Statements.iter() {
+                    self.compileStatement(s)?;
+                }
+            }
+            ast::Statement::LetStatement {
+                Token, Name, Value, ..
+            } => {
+                // a global defined ahead of its statement is not a redeclaration.
+                let pending = self.scopes.len() == 1 && self.pendingGlobals.contains(&Name.Value);
+                if !pending && self.symbolTable.DefinesConst(&Name.Value) {
+                    return Err(format!("cannot redeclare const {}", Name.Value));
+                }
+                // the value is compiled before the name is defined, so `let x = x` refers to
+                // the outer x. a function is compiled after, as its body sees the binding it is
+                // assigned to, which is how it calls itself.
+                let constant = Token.Type == token::CONST;
+                let symbol = if let ast::Expression::FunctionLiteral { .. } = Value {
+                    let symbol = self.define(&Name.Value, constant);
+                    self.compileFunction(Value, &Name.Value)?;
+                    symbol
+                } else {
+                    self.compileExpression(Value)?;
+                    self.define(&Name.Value, constant)
+                };
+                if self.scopes.len() == 1 {
+                    self.pendingGlobals.remove(&Name.Value);
+                }
+                self.storeSymbol(&symbol);
+            }
+            ast::Statement::AssignStatement {
+                Token, Name, Value, ..
+            } => {
+                let symbol = match self.symbolTable.Resolve(&Name.Value) {
+                    Some(symbol)
+                        if self.scopes.len() > 1 || !self.pendingGlobals.contains(&Name.Value) =>
+                    {
+                        symbol
+                    }
+                    _ => return Err(format!("identifier not found: {}", Name.Value)),
+                };
+                if self.symbolTable.IsConst(&Name.Value) {
+                    return Err(format!("cannot assign to const {}", Name.Value));
+                }
+                if symbol.Scope == symbol_table::BuiltinScope {
+                    return Err(format!("cannot assign to builtin {}", Name.Value));
+                }
+                // x += y is x = x + y, with x read first.
+                let op = match Token.Literal.as_str() {
+                    "+=" => Some(code::OpAdd),
+                    "-=" => Some(code::OpSub),
+                    "*=" => Some(code::OpMul),
+                    "/=" => Some(code::OpDiv),
+                    _ => None,
+                };
+                if op.is_some() {
+                    self.loadSymbol(&symbol);
+                }
+                self.compileExpression(Value)?;
+                if let Some(op) = op {
+                    self.emit(op, &[]);
+                }
+                self.storeSymbol(&symbol);
+            }
+            ast::Statement::ReturnStatement { ReturnValue, .. } => {
+                self.compileExpression(ReturnValue)?;
+                self.emit(code::OpReturnValue, &[]);
+            }
+            ast::Statement::WhileStatement {
+                Condition, Body, ..
+            } => {
+                let start = self.currentInstructions().len();
+                self.compileExpression(Condition)?;
+                let exitPos = self.emit(code::OpJumpNotTruthy, &[9999]);
+
+                // like a for loop, each pass gets new variables for the lets in the body.
+                let outer = std::mem::take(&mut self.symbolTable);
+                self.symbolTable = symbol_table::NewBlockSymbolTable(outer);
+                let first = self.symbolTable.NextLocal();
+                let freshPos = self.emit(code::OpFreshLocals, &[first, 0]);
+
+                self.currentScope().loops.push(Loop {
+                    start,
+                    breaks: vec![],
+                });
+                let compiled = self.compileStatement(Body);
+                let l = self.currentScope().loops.pop().unwrap();
+                let count = self.symbolTable.NextLocal() - first;
+                self.changeOperands(freshPos, &[first, count]);
+                if let Some(outer) = self.symbolTable.Outer.take() {
+                    self.symbolTable = *outer;
+                }
+                compiled?;
+                self.emit(code::OpJump, &[start]);
+
+                let afterLoopPos = self.currentInstructions().len();
+                self.changeOperand(exitPos, afterLoopPos);
+                for pos in l.breaks {
+                    self.changeOperand(pos, afterLoopPos);
+                }
+                // like the evaluator, a loop's value is null, also when it ends a block.
+                self.emit(code::OpNull, &[]);
+                self.emit(code::OpPop, &[]);
+            }
+            ast::Statement::ForStatement {
+                Variables,
+                Iterable,
+                Body,
+                ..
+            } => self.compileFor(Variables, Iterable, Body)?,
+            // the parser only accepts break and continue inside a loop.
+            ast::Statement::BreakStatement { .. } => {
+                let pos = self.emit(code::OpJump, &[9999]);
+                match self.currentScope().loops.last_mut() {
+                    Some(l) => l.breaks.push(pos),
+                    None => return Err(String::from("break outside of a loop")),
+                }
+            }
+            ast::Statement::ContinueStatement { .. } => match self.currentScope().loops.last() {
+                Some(l) => {
+                    let start = l.start;
+                    self.emit(code::OpJump, &[start]);
+                }
+                None => return Err(String::from("continue outside of a loop")),
+            },
+            ast::Statement::Nil => {}
+        }
+        Ok(())
+    }
+
+    fn compileExpression(&mut self, exp: &ast::Expression) -> Result<(), String> {
+        match exp {
+            ast::Expression::IntergerLiteral { Value, .. } => {
+                let idx = self.addConstant(object::Object::Integer { Value: *Value });
+                self.emit(code::OpConstant, &[idx]);
+            }
+            ast::Expression::StringLiteral { Value, .. } => {
+                let idx = self.addConstant(object::Object::String {
+                    Value: Value.clone(),
+                });
+                self.emit(code::OpConstant, &[idx]);
+            }
+            ast::Expression::Boolean { Value, .. } => {
+                if *Value {
+                    self.emit(code::OpTrue, &[]);
+                } else {
+                    self.emit(code::OpFalse, &[]);
+                }
+            }
+            ast::Expression::PrefixExpression {
+                Operator, Right, ..
+            } => {
+                self.compileExpression(Right)?;
+                match Operator.as_str() {
+                    "!" => self.emit(code::OpBang, &[]),
+                    "-" => self.emit(code::OpMinus, &[]),
+                    _ => return Err(format!("unknown operator: {}", Operator)),
+                };
+            }
+            ast::Expression::InfixExpression {
+                Left,
+                Operator,
+                Right,
+                ..
+            } if Operator == "&&" || Operator == "||" => {
+                self.compileLogical(Operator, Left, Right)?
+            }
+            ast::Expression::InfixExpression {
+                Left,
+                Operator,
+                Right,
+                ..
+            } => {
+                self.compileExpression(Left)?;
+                self.compileExpression(Right)?;
+                match Operator.as_str() {
+                    "+" => self.emit(code::OpAdd, &[]),
+                    "-" => self.emit(code::OpSub, &[]),
+                    "*" => self.emit(code::OpMul, &[]),
+                    "/" => self.emit(code::OpDiv, &[]),
+                    "%" => self.emit(code::OpMod, &[]),
+                    ">" => self.emit(code::OpGreaterThan, &[]),
+                    "<" => self.emit(code::OpLessThan, &[]),
+                    ">=" => self.emit(code::OpGreaterThanOrEqual, &[]),
+                    "<=" => self.emit(code::OpLessThanOrEqual, &[]),
+                    ".." => self.emit(code::OpRange, &[]),
+                    "==" => self.emit(code::OpEqual, &[]),
+                    "!=" => self.emit(code::OpNotEqual, &[]),
+                    _ => return Err(format!("unknown operator: {}", Operator)),
+                };
+            }
+            ast::Expression::IfExpression {
+                Condition,
+                Consequence,
+                Alternative,
+                ..
+            } => {
+                self.compileExpression(Condition)?;
+                // the jump targets are patched once the branches have been emitted.
+                let jumpNotTruthyPos = self.emit(code::OpJumpNotTruthy, &[9999]);
+                let before = self.symbolTable.Consts();
+                self.compileBlock(Consequence)?;
+                let jumpPos = self.emit(code::OpJump, &[9999]);
+                let consequenceConsts = self.symbolTable.Consts();
+                self.symbolTable.SetConsts(before);
+
+                let afterConsequencePos = self.currentInstructions().len();
+                self.changeOperand(jumpNotTruthyPos, afterConsequencePos);
+
+                if let ast::Statement::Nil = Alternative.as_ref() {
+                    self.emit(code::OpNull, &[]);
+                } else {
+                    self.compileBlock(Alternative)?;
+                }
+                // afterwards a name is a const if either branch may have made it one.
+                let mut consts = self.symbolTable.Consts();
+                consts.extend(consequenceConsts);
+                self.symbolTable.SetConsts(consts);
+
+                let afterAlternativePos = self.currentInstructions().len();
+                self.changeOperand(jumpPos, afterAlternativePos);
+            }
+            ast::Expression::Identifier(idt) => match self.symbolTable.Resolve(&idt.Value) {
+                Some(symbol)
+                    if self.scopes.len() > 1 || !self.pendingGlobals.contains(&idt.Value) =>
+                {
+                    self.loadSymbol(&symbol)
+                }
+                _ => return Err(format!("identifier not found: {}", idt.Value)),
+            },
+            ast::Expression::ArrayLiteral { Elements, .. } => {
+                for e in Elements.iter() {
+                    self.compileExpression(e)?;
+                }
+                self.emit(code::OpArray, &[Elements.len()]);
+            }
+            ast::Expression::HashLiteral { Pairs, .. } => {
+                for (key, value) in Pairs.iter() {
+                    self.compileExpression(key)?;
+                    self.compileExpression(value)?;
+                }
+                self.emit(code::OpHash, &[Pairs.len() * 2]);
+            }
+            ast::Expression::IndexExpression { Left, Index, .. } => {
+                self.compileExpression(Left)?;
+                self.compileExpression(Index)?;
+                self.emit(code::OpIndex, &[]);
+            }
+            ast::Expression::FunctionLiteral { .. } => self.compileFunction(exp, "")?,
+            ast::Expression::CallExpression {
+                Function,
+                Arguments,
+                ..
+            } => {
+                self.compileExpression(Function)?;
+                for a in Arguments.iter() {
+                    self.compileExpression(a)?;
+                }
+                self.emit(code::OpCall, &[Arguments.len()]);
+            }
+            ast::Expression::Nil => {}
+        }
+        Ok(())
+    }
+
+    // compileFor compiles a for-in loop. the loop variables and the iterator live in a block
+    // scope, so they get slots of their own and are gone once the loop ends. each time round
+    // the iterator is called and OpIterNext either ends the loop or leaves the next iterator
+    // and the values of the variables on the stack.
+    fn compileFor(
+        &mut self,
+        variables: &[ast::Identifier],
+        iterable: &ast::Expression,
+        body: &ast::Statement,
+    ) -> Result<(), String> {
+        self.compileExpression(iterable)?;
+        self.emit(code::OpIter, &[variables.len()]);
+
+        let outer = std::mem::take(&mut self.symbolTable);
+        self.symbolTable = symbol_table::NewBlockSymbolTable(outer);
+        // not a valid identifier, so it cannot clash with a name in the body.
+        let it = self.symbolTable.Define("for iterator");
+        self.storeSymbol(&it);
+
+        let start = self.currentInstructions().len();
+        self.loadSymbol(&it);
+        self.emit(code::OpCall, &[0]);
+        let exitPos = self.emit(code::OpIterNext, &[9999, variables.len()]);
+        // like the evaluator, each pass gets new variables, so closures made in earlier passes
+        // keep the ones they captured. the body's locals are only known once it is compiled.
+        let first = self.symbolTable.NextLocal();
+        let freshPos = self.emit(code::OpFreshLocals, &[first, 0]);
+        for v in variables.iter() {
+            let symbol = self.symbolTable.Define(&v.Value);
+            self.storeSymbol(&symbol);
+        }
+        self.storeSymbol(&it);
+
+        self.currentScope().loops.push(Loop {
+            start,
+            breaks: vec![],
+        });
+        let compiled = self.compileStatement(body);
+        let l = self.currentScope().loops.pop().unwrap();
+        let count = self.symbolTable.NextLocal() - first;
+        self.changeOperands(freshPos, &[first, count]);
+        if let Some(outer) = self.symbolTable.Outer.take() {
+            self.symbolTable = *outer;
+        }
+        compiled?;
+        self.emit(code::OpJump, &[start]);
+
+        let afterLoopPos = self.currentInstructions().len();
+        self.changeOperand(exitPos, afterLoopPos);
+        for pos in l.breaks {
+            self.changeOperand(pos, afterLoopPos);
+        }
+        self.emit(code::OpNull, &[]);
+        self.emit(code::OpPop, &[]);
+        Ok(())
+    }
+
+    // compileLogical compiles `left && right` or `left || right` to jumps, so right is only
+    // run when left does not decide the result. like the evaluator, the value is a boolean.
+    fn compileLogical(
+        &mut self,
+        operator: &str,
+        left: &ast::Expression,
+        right: &ast::Expression,
+    ) -> Result<(), String> {
+        self.compileExpression(left)?;
+        let leftFalsePos = self.emit(code::OpJumpNotTruthy, &[9999]);
+        let mut decided = vec![];
+        if operator == "||" {
+            self.emit(code::OpTrue, &[]);
+            decided.push(self.emit(code::OpJump, &[9999]));
+            let rightPos = self.currentInstructions().len();
+            self.changeOperand(leftFalsePos, rightPos);
+        }
+        self.compileExpression(right)?;
+        let rightFalsePos = self.emit(code::OpJumpNotTruthy, &[9999]);
+        self.emit(code::OpTrue, &[]);
+        decided.push(self.emit(code::OpJump, &[9999]));
+
+        let falsePos = self.currentInstructions().len();
+        self.changeOperand(rightFalsePos, falsePos);
+        if operator == "&&" {
+            self.changeOperand(leftFalsePos, falsePos);
+        }
+        self.emit(code::OpFalse, &[]);
+
+        let afterPos = self.currentInstructions().len();
+        for pos in decided {
+            self.changeOperand(pos, afterPos);
+        }
+        Ok(())
+    }
+
+    // compileBlock compiles the branch of an if expression so that it leaves exactly one
+    // value on the stack: its last expression, or null.
+    fn compileBlock(&mut self, block: &ast::Statement) -> Result<(), String> {
+        self.compileStatement(block)?;
+        if self.lastInstructionIs(code::OpPop) {
+            self.removeLastPop();
+        } else {
+            self.emit(code::OpNull, &[]);
+        }
+        Ok(())
+    }
+
+    fn compileFunction(&mut self, function: &ast::Expression, name: &str) -> Result<(), String> {
+        let (parameters, body) = match function {
+            ast::Expression::FunctionLiteral {
+                Parameters, Body, ..
+            } => (Parameters, Body),
+            _ => {
+                return Err(format!(
+                    "not a function literal: {}",
+                    function.into_string()
+                ))
+            }
+        };
+
+        self.enterScope();
+        for p in parameters.iter() {
+            if let ast::Expression::Identifier(idt) = p {
+                self.symbolTable.Define(&idt.Value);
+            }
+        }
+
+        if let Err(err) = self.compileStatement(body) {
+            self.leaveScope();
+            return Err(err);
+        }
+        if self.lastInstructionIs(code::OpPop) {
+            self.replaceLastPopWithReturn();
+        }
+        if !self.lastInstructionIs(code::OpReturnValue) {
+            self.emit(code::OpReturn, &[]);
+        }
+
+        let freeSymbols = self.symbolTable.FreeSymbols.clone();
+        let numLocals = self.symbolTable.NumDefinitions();
+        let instructions = self.leaveScope();
+
+        // the closure shares the captured variables with this scope rather than copying them,
+        // so assignments on either side are seen by both.
+        for s in freeSymbols.iter() {
+            match s.Scope {
+                symbol_table::FreeScope => self.emit(code::OpCaptureFree, &[s.Index]),
+                _ => self.emit(code::OpCaptureLocal, &[s.Index]),
+            };
+        }
+
+        let compiledFn = object::Object::CompiledFunction {
+            Fn: Rc::new(object::CompiledFunction {
+                Instructions: instructions,
+                NumLocals: numLocals,
+                NumParameters: parameters.len(),
+                Name: name.to_string(),
+            }),
+        };
+        let idx = self.addConstant(compiledFn);
+        self.emit(code::OpClosure, &[idx, freeSymbols.len()]);
+        Ok(())
+    }
+
+    fn loadSymbol(&mut self, s: &Symbol) {
+        match s.Scope {
+            symbol_table::GlobalScope => self.emit(code::OpGetGlobal, &[s.Index]),
+            symbol_table::LocalScope => self.emit(code::OpGetLocal, &[s.Index]),
+            symbol_table::BuiltinScope => self.emit(code::OpGetBuiltin, &[s.Index]),
+            _ => self.emit(code::OpGetFree, &[s.Index]),
+        };
+    }
+
+    fn define(&mut self, name: &str, constant: bool) -> Symbol {
+        if constant {
+            self.symbolTable.DefineConst(name)
+        } else {
+            self.symbolTable.Define(name)
+        }
+    }
+
+    fn storeSymbol(&mut self, s: &Symbol) {
+        match s.Scope {
+            symbol_table::GlobalScope => self.emit(code::OpSetGlobal, &[s.Index]),
+            symbol_table::FreeScope => self.emit(code::OpSetFree, &[s.Index]),
+            _ => self.emit(code::OpSetLocal, &[s.Index]),
+        };
+    }
+
+    fn addConstant(&mut self, obj: object::Object) -> usize {
+        self.constants.push(obj);
+        self.constants.len() - 1
+    }
+
+    // emit appends an instruction to the current scope and returns its position.
+    fn emit(&mut self, op: code::Opcode, operands: &[usize]) -> usize {
+        let ins = code::Make(op, operands);
+        let pos = self.addInstruction(ins);
+        self.setLastInstruction(op, pos);
+        pos
+    }
+
+    fn addInstruction(&mut self, ins: code::Instructions) -> usize {
+        let scope = self.currentScope();
+        let pos = scope.instructions.len();
+        scope.instructions.extend(ins);
+        pos
+    }
+
+    fn setLastInstruction(&mut self, op: code::Opcode, pos: usize) {
+        let scope = self.currentScope();
+        scope.previousInstruction = scope.lastInstruction;
+        scope.lastInstruction = EmittedInstruction {
+            Opcode: op,
+            Position: pos,
+        };
+    }
+
+    fn lastInstructionIs(&self, op: code::Opcode) -> bool {
+        let scope = self.scopes.last().unwrap();
+        !scope.instructions.is_empty() && scope.lastInstruction.Opcode == op
+    }
+
+    fn removeLastPop(&mut self) {
+        let scope = self.currentScope();
+        scope.instructions.truncate(scope.lastInstruction.Position);
+        scope.lastInstruction = scope.previousInstruction;
+    }
+
+    fn replaceLastPopWithReturn(&mut self) {
+        let scope = self.currentScope();
+        let pos = scope.lastInstruction.Position;
+        scope.instructions[pos] = code::OpReturnValue;
+        scope.lastInstruction.Opcode = code::OpReturnValue;
+    }
+
+    // changeOperand rewrites the operand of the instruction at pos, e.g. to patch a jump.
+    fn changeOperand(&mut self, pos: usize, operand: usize) {
+        self.changeOperands(pos, &[operand]);
+    }
+
+    // changeOperands rewrites the leading operands of the instruction at pos.
+    fn changeOperands(&mut self, pos: usize, operands: &[usize]) {
+        let scope = self.currentScope();
+        let op = scope.instructions[pos];
+        let ins = code::Make(op, operands);
+        scope.instructions[pos..pos + ins.len()].copy_from_slice(&ins);
+    }
+
+    fn currentScope(&mut self) -> &mut CompilationScope {
+        self.scopes.last_mut().unwrap()
+    }
+
+    fn currentInstructions(&self) -> &code::Instructions {
+        &self.scopes.last().unwrap().instructions
+    }
+
+    fn enterScope(&mut self) {
+        self.scopes.push(CompilationScope::default());
+        let outer = std::mem::take(&mut self.symbolTable);
+        self.symbolTable = symbol_table::NewEnclosedSymbolTable(outer);
+    }
+
+    fn leaveScope(&mut self) -> code::Instructions {
+        let scope = self.scopes.pop().unwrap();
+        if let Some(outer) = self.symbolTable.Outer.take() {
+            self.symbolTable = *outer;
+        }
+        scope.instructions
+    }
+}
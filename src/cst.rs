@@ -0,0 +1,98 @@
+// lossless syntax tree: the ast together with every token of the source and the whitespace
+// and comments around it, so the exact source text can be rebuilt. tools that rewrite code
+// use it to keep the author's comments.
+use crate::token::{self, Trivia, TriviaKind};
+use crate::{ast, lexer, parser};
+
+// Token is a token with its trivia. Trailing holds what follows the token up to the end of its
+// line; everything else before the next token is that token's Leading trivia.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Token {
+    pub Token: token::Token,
+    // the source text of the token. unlike Token.Literal, string escapes are left as written.
+    pub Text: String,
+    pub Leading: Vec<Trivia>,
+    pub Trailing: Vec<Trivia>,
+}
+
+pub struct SyntaxTree {
+    pub Program: ast::Program,
+    // every token in source order, ending with EOF, whose leading trivia is whatever follows
+    // the last real token.
+    pub Tokens: Vec<Token>,
+}
+
+// Parse parses src keeping its trivia.
+pub fn Parse(src: &str) -> Result<SyntaxTree, Vec<parser::ParseError>> {
+    let mut p = lexer::New(src.to_string()).New();
+    let program = p.ParseProgram();
+    if !p.Errors().is_empty() {
+        return Err(p.Errors().to_vec());
+    }
+    Ok(SyntaxTree {
+        Program: program,
+        Tokens: Tokenize(src),
+    })
+}
+
+// Tokenize lexes src into tokens with trivia attached. it never fails: text the lexer does not
+// understand becomes ILLEGAL tokens.
+pub fn Tokenize(src: &str) -> Vec<Token> {
+    let mut l = lexer::NewLossless(src.to_string());
+    let mut tokens: Vec<Token> = vec![];
+    loop {
+        let tok = l.NextToken();
+        let mut leading = l.TakeTrivia();
+        if let Some(prev) = tokens.last_mut() {
+            let n = leading
+                .iter()
+                .position(|t| t.Text.contains('\n'))
+                .unwrap_or(leading.len());
+            prev.Trailing = leading.drain(..n).collect();
+        }
+        let eof = tok.Type == token::EOF;
+        tokens.push(Token {
+            Text: src[tok.Span.Start..tok.Span.End].to_string(),
+            Token: tok,
+            Leading: leading,
+            Trailing: vec![],
+        });
+        if eof {
+            return tokens;
+        }
+    }
+}
+
+impl SyntaxTree {
+    // Text rebuilds the source the tree was parsed from.
+    pub fn Text(&self) -> String {
+        let mut out = String::new();
+        for t in self.Tokens.iter() {
+            for trivia in t.Leading.iter() {
+                out += &trivia.Text;
+            }
+            out += &t.Text;
+            for trivia in t.Trailing.iter() {
+                out += &trivia.Text;
+            }
+        }
+        out
+    }
+
+    // Comments returns every comment in source order.
+    pub fn Comments(&self) -> Vec<&Trivia> {
+        self.Tokens
+            .iter()
+            .flat_map(|t| t.Leading.iter().chain(t.Trailing.iter()))
+            .filter(|t| t.Kind != TriviaKind::Whitespace)
+            .collect()
+    }
+
+    // TokenAt returns the token starting at byte offset start, e.g. the token of an ast node.
+    pub fn TokenAt(&self, start: usize) -> Option<&Token> {
+        self.Tokens
+            .binary_search_by_key(&start, |t| t.Token.Span.Start)
+            .ok()
+            .map(|i| &self.Tokens[i])
+    }
+}
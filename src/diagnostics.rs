@@ -0,0 +1,223 @@
+use crate::{builtins, lexer, object, parser, token};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Mode {
+    Plain,
+    Color,
+}
+
+// tracebacks longer than this show only their innermost and outermost frames.
+const MAX_FRAMES: usize = 20;
+
+// Diagnostic is an error message tied to a range of the source, with optional notes such as
+// spelling suggestions. runtime errors also carry the call stack they unwound through.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Diagnostic {
+    pub Message: String,
+    pub Span: token::Span,
+    pub Notes: Vec<String>,
+    pub Trace: Vec<object::Frame>,
+}
+
+pub fn FromParseError(err: &parser::ParseError, source: &str) -> Diagnostic {
+    let mut notes = vec![];
+    if let Some(kw) = misspelledKeyword(source, err.Span()) {
+        notes.push(format!("did you mean `{}`?", kw));
+    }
+    Diagnostic {
+        Message: err.to_string(),
+        Span: err.Span(),
+        Notes: notes,
+        Trace: vec![],
+    }
+}
+
+// FromRuntimeError converts an error object into a diagnostic. names visible in env are offered
+// as suggestions when an identifier could not be found.
+pub fn FromRuntimeError(obj: &object::Object, env: &object::Environment) -> Option<Diagnostic> {
+    if let object::Object::Error {
+        Message,
+        Span,
+        Trace,
+    } = obj
+    {
+        let mut notes = vec![];
+        if let Some(name) = Message.strip_prefix("identifier not found: ") {
+            let mut candidates: Vec<String> = env.Names();
+            candidates.extend(builtins::Names().iter().map(|s| s.to_string()));
+            candidates.extend(token::Keywords().iter().map(|s| s.to_string()));
+            if let Some(s) = Suggest(name, &candidates) {
+                notes.push(format!("did you mean `{}`?", s));
+            }
+        }
+        Some(Diagnostic {
+            Message: Message.clone(),
+            Span: *Span,
+            Notes: notes,
+            Trace: Trace.clone(),
+        })
+    } else {
+        None
+    }
+}
+
+impl Diagnostic {
+    // Render formats the diagnostic in the style of rustc: a header, the location, the offending
+    // source line with a caret underline, and any notes.
+    pub fn Render(&self, source: &str, filename: &str, mode: Mode) -> String {
+        let paint = |color: &str, text: &str| -> String {
+            match mode {
+                Mode::Plain => text.to_string(),
+                Mode::Color => format!("{}{}{}", color, text, RESET),
+            }
+        };
+
+        let mut out = format!(
+            "{}{}\n",
+            paint(RED, "error"),
+            paint(BOLD, &format!(": {}", self.Message))
+        );
+
+        if self.Span.Line == 0 {
+            out += &format!("{} {}\n", paint(BLUE, "-->"), filename);
+        } else {
+            let lineText = source.lines().nth(self.Span.Line - 1).unwrap_or("");
+            let lineNo = self.Span.Line.to_string();
+            let gutter = " ".repeat(lineNo.len());
+
+            // keep tabs in the padding so the carets line up with the source line.
+            let padding: String = lineText
+                .chars()
+                .take(self.Span.Column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let lineEnd = source[..self.Span.Start.min(source.len())]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0)
+                + lineText.len();
+            let end = self.Span.End.min(lineEnd).max(self.Span.Start);
+            let width = source
+                .get(self.Span.Start..end)
+                .map(|s| s.chars().count())
+                .unwrap_or(0)
+                .max(1);
+
+            out += &format!(
+                "{}{} {}:{}:{}\n",
+                gutter,
+                paint(BLUE, "-->"),
+                filename,
+                self.Span.Line,
+                self.Span.Column
+            );
+            out += &format!("{} {}\n", gutter, paint(BLUE, "|"));
+            out += &format!(
+                "{} {} {}\n",
+                paint(BLUE, &lineNo),
+                paint(BLUE, "|"),
+                lineText
+            );
+            out += &format!(
+                "{} {} {}{}\n",
+                gutter,
+                paint(BLUE, "|"),
+                padding,
+                paint(RED, &"^".repeat(width))
+            );
+        }
+
+        for note in self.Notes.iter() {
+            out += &format!(
+                "{}{} {}\n",
+                " ".repeat(self.Span.Line.to_string().len() + 1),
+                paint(BLUE, "="),
+                paint(BOLD, &format!("note: {}", note))
+            );
+        }
+
+        if !self.Trace.is_empty() {
+            out += &format!("{}\n", paint(BOLD, "traceback (most recent call first):"));
+            let n = self.Trace.len();
+            for (i, frame) in self.Trace.iter().enumerate() {
+                if n > MAX_FRAMES && i >= MAX_FRAMES / 2 && i < n - MAX_FRAMES / 2 {
+                    if i == MAX_FRAMES / 2 {
+                        out += &format!("  ... {} frames omitted ...\n", n - MAX_FRAMES);
+                    }
+                    continue;
+                }
+                let plural = if frame.Args == 1 { "" } else { "s" };
+                out += &format!(
+                    "  {}: {}, {} arg{}, called at {}:{}\n",
+                    i,
+                    paint(BOLD, &frame.Function),
+                    frame.Args,
+                    plural,
+                    filename,
+                    frame.Span
+                );
+            }
+        }
+
+        out
+    }
+}
+
+// Suggest returns the candidate closest to name, if any is close enough to be a likely typo.
+// single-letter names are never corrected since nearly everything is one edit away from them.
+pub fn Suggest(name: &str, candidates: &[String]) -> Option<String> {
+    let limit = match name.chars().count() {
+        0 | 1 => return None,
+        2 | 3 => 1,
+        _ => 2,
+    };
+    let mut best: Option<(usize, &String)> = None;
+    for c in candidates.iter() {
+        if c == name {
+            continue;
+        }
+        let d = editDistance(name, c);
+        if d <= limit && best.is_none_or(|(bd, _)| d < bd) {
+            best = Some((d, c));
+        }
+    }
+    best.map(|(_, c)| c.clone())
+}
+
+// misspelledKeyword looks for an identifier before the error on the same line that is one or
+// two edits away from a keyword, e.g. `let f = fnn(x) { x };`.
+fn misspelledKeyword(source: &str, span: token::Span) -> Option<String> {
+    let keywords: Vec<String> = token::Keywords().iter().map(|s| s.to_string()).collect();
+    let mut l = lexer::New(source.to_string());
+    loop {
+        let tok = l.NextToken();
+        if tok.Type == token::EOF || tok.Span.Start > span.Start {
+            return None;
+        }
+        if tok.Type == token::IDENT && tok.Span.Line == span.Line && tok.Literal.len() > 2 {
+            if let Some(kw) = Suggest(&tok.Literal, &keywords) {
+                return Some(kw);
+            }
+        }
+    }
+}
+
+fn editDistance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut cur = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
@@ -0,0 +1,212 @@
+// line input for the repl. on a terminal lines are read with rustyline, which gives cursor
+// movement, history recall and tab completion. otherwise lines are read from stdin as they are,
+// so piped input behaves exactly like typed input.
+use crate::{builtins, highlight, object, token};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const HISTORY_FILE: &str = ".monkey_history";
+
+// repl commands, completed when a line starts with ':'.
+pub const Commands: &[&str] = &[
+    ":ast",
+    ":bytecode",
+    ":env",
+    ":help",
+    ":load",
+    ":reset",
+    ":save",
+    ":tokens",
+    ":type",
+];
+
+pub enum Line {
+    Text(String),
+    // ctrl-c: the line being typed is abandoned.
+    Interrupted,
+    Eof,
+}
+
+pub enum Reader {
+    Terminal {
+        editor: Box<Editor<Helper, FileHistory>>,
+        history: Option<PathBuf>,
+    },
+    Plain,
+}
+
+// New returns a Reader completing names from env, highlighting input as it is typed if color
+// is set. history is loaded from HistoryPath.
+pub fn New(env: Rc<RefCell<object::Environment>>, color: bool) -> Reader {
+    if !io::stdin().is_terminal() {
+        return Reader::Plain;
+    }
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut editor = match Editor::with_config(config) {
+        Ok(editor) => editor,
+        Err(_) => return Reader::Plain,
+    };
+    editor.set_helper(Some(Helper { env, color }));
+    let history = HistoryPath();
+    if let Some(path) = &history {
+        // a missing history file is normal on first use.
+        let _ = editor.load_history(path);
+    }
+    Reader::Terminal {
+        editor: Box::new(editor),
+        history,
+    }
+}
+
+// HistoryPath is $MONKEY_HISTORY if set, otherwise ~/.monkey_history. an empty
+// $MONKEY_HISTORY turns persistent history off.
+pub fn HistoryPath() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("MONKEY_HISTORY") {
+        if path.is_empty() {
+            return None;
+        }
+        return Some(PathBuf::from(path));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+impl Reader {
+    // ReadLine prints prompt and returns the next line, ending in '\n'.
+    pub fn ReadLine(&mut self, prompt: &str) -> Line {
+        match self {
+            Reader::Terminal { editor, .. } => match editor.readline(prompt) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        let _ = editor.add_history_entry(line.as_str());
+                    }
+                    Line::Text(line + "\n")
+                }
+                Err(ReadlineError::Interrupted) => Line::Interrupted,
+                Err(_) => Line::Eof,
+            },
+            Reader::Plain => {
+                print!("{}", prompt);
+                io::stdout().flush().unwrap();
+                let mut line = String::new();
+                match io::stdin().read_line(&mut line) {
+                    Ok(0) | Err(_) => Line::Eof,
+                    Ok(_) => {
+                        if !line.ends_with('\n') {
+                            line.push('\n');
+                        }
+                        Line::Text(line)
+                    }
+                }
+            }
+        }
+    }
+
+    // SetEnv points completion at env, e.g. after the environment was reset.
+    pub fn SetEnv(&mut self, env: Rc<RefCell<object::Environment>>) {
+        if let Reader::Terminal { editor, .. } = self {
+            if let Some(h) = editor.helper_mut() {
+                h.env = env;
+            }
+        }
+    }
+
+    // SaveHistory writes the history file. failing to do so is not worth interrupting the
+    // user over.
+    pub fn SaveHistory(&mut self) {
+        if let Reader::Terminal {
+            editor,
+            history: Some(path),
+        } = self
+        {
+            let _ = editor.save_history(path);
+        }
+    }
+}
+
+// Complete returns where the word before pos starts and the names it could be completed to:
+// repl commands at the start of the line, otherwise keywords, builtins and bound names.
+pub fn Complete(line: &str, pos: usize, env: &object::Environment) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    if before.starts_with(':') && !before.contains(char::is_whitespace) {
+        let res = Commands
+            .iter()
+            .filter(|c| c.starts_with(before))
+            .map(|c| c.to_string())
+            .collect();
+        return (0, res);
+    }
+
+    let start = before
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let word = &before[start..];
+    if word.is_empty() || word.starts_with(|c: char| c.is_ascii_digit()) {
+        return (pos, vec![]);
+    }
+
+    let mut res: Vec<String> = token::Keywords()
+        .into_iter()
+        .chain(builtins::Names())
+        .map(String::from)
+        .chain(env.Names())
+        .filter(|name| name.starts_with(word))
+        .collect();
+    res.sort();
+    res.dedup();
+    (start, res)
+}
+
+pub struct Helper {
+    env: Rc<RefCell<object::Environment>>,
+    color: bool,
+}
+
+impl Completer for Helper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(Complete(line, pos, &self.env.borrow()))
+    }
+}
+
+impl Hinter for Helper {
+    type Hint = String;
+}
+
+impl Highlighter for Helper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if self.color {
+            Cow::Owned(highlight::Source(line))
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    // redraw on every keystroke: typing one character can change how the whole line lexes,
+    // e.g. opening a string.
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        self.color
+    }
+}
+
+impl Validator for Helper {}
+
+impl rustyline::Helper for Helper {}
@@ -1,34 +1,38 @@
-use crate::{ast, object};
+use crate::{ast, builtins, iterator, object, token};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 
 const TRUE: object::Object = object::Object::Boolean { Value: true };
 const FALSE: object::Object = object::Object::Boolean { Value: false };
 const NULL: object::Object = object::Object::Null;
 
-pub fn Eval(node: ast::Program, env: &mut object::Environment) -> object::Object {
+pub fn Eval(node: ast::Program, env: &Rc<RefCell<object::Environment>>) -> object::Object {
     evalStatements(&node.Statements, env)
 }
 
-fn evalStatements(stmts: &Vec<ast::Statement>, env: &mut object::Environment) -> object::Object {
+fn evalStatements(
+    stmts: &[ast::Statement],
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
     let mut result = object::Object::Null;
     for statement in stmts.iter() {
         result = evalStatement(statement, env);
 
         if let object::Object::ReturnValue { Value } = result {
             return *Value;
-        } else if let object::Object::Error { Message } = &result {
+        } else if let object::Object::Error { .. } = &result {
             return result;
         }
     }
     result
 }
 
-fn evalStatement(stmt: &ast::Statement, env: &mut object::Environment) -> object::Object {
+fn evalStatement(stmt: &ast::Statement, env: &Rc<RefCell<object::Environment>>) -> object::Object {
     match stmt {
-        ast::Statement::ExpressionStatement { Token, Expression } => {
-            evalExpression(Expression, env)
-        }
-        ast::Statement::BlockStatement { Token, Statements } => evalBlockStatement(stmt, env),
-        ast::Statement::ReturnStatement { Token, ReturnValue } => {
+        ast::Statement::ExpressionStatement { Expression, .. } => evalExpression(Expression, env),
+        ast::Statement::BlockStatement { .. } => evalBlockStatement(stmt, env),
+        ast::Statement::ReturnStatement { ReturnValue, .. } => {
             let val = evalExpression(ReturnValue, env);
             if isError(&val) {
                 return val;
@@ -37,33 +41,49 @@ fn evalStatement(stmt: &ast::Statement, env: &mut object::Environment) -> object
                 Value: Box::new(val),
             }
         }
-        ast::Statement::LetStatement { Token, Name, Value } => {
+        ast::Statement::LetStatement {
+            Token, Name, Value, ..
+        } => {
             let val = evalExpression(Value, env);
-            if isError(&val) {
+            if isError(&val) || isLoopSignal(&val) {
                 return val;
             }
-            env.Set(&Name.Value, val)
+            let constant = Token.Type == token::CONST;
+            match env.borrow_mut().Define(&Name.Value, val, constant) {
+                Ok(val) => val,
+                Err(msg) => withSpan(newError(msg), Name.Token.Span),
+            }
         }
+        ast::Statement::AssignStatement {
+            Token,
+            Name,
+            Value,
+            Span,
+        } => evalAssignStatement(&Token.Literal, Name, Value, *Span, env),
+        ast::Statement::WhileStatement {
+            Condition, Body, ..
+        } => evalWhileStatement(Condition, Body, env),
+        ast::Statement::ForStatement {
+            Variables,
+            Iterable,
+            Body,
+            ..
+        } => evalForStatement(Variables, Iterable, Body, env),
+        ast::Statement::BreakStatement { .. } => object::Object::Break,
+        ast::Statement::ContinueStatement { .. } => object::Object::Continue,
         _ => object::Object::Null,
     }
 }
 
-fn evalExpression(exp: &ast::Expression, env: &mut object::Environment) -> object::Object {
-    match exp {
-        ast::Expression::IntergerLiteral { Token, Value } => {
-            object::Object::Integer { Value: *Value }
-        }
-        ast::Expression::Boolean { Token, Value } => {
-            if *Value {
-                TRUE
-            } else {
-                FALSE
-            }
-        }
+fn evalExpression(exp: &ast::Expression, env: &Rc<RefCell<object::Environment>>) -> object::Object {
+    let result = match exp {
+        ast::Expression::IntergerLiteral { Value, .. } => object::Object::Integer { Value: *Value },
+        ast::Expression::Boolean { Value, .. } => nativeBoolToBooleanObject(*Value),
+        ast::Expression::StringLiteral { Value, .. } => object::Object::String {
+            Value: Value.clone(),
+        },
         ast::Expression::PrefixExpression {
-            Token,
-            Operator,
-            Right,
+            Operator, Right, ..
         } => {
             let right = evalExpression(Right, env);
             if isError(&right) {
@@ -76,54 +96,99 @@ fn evalExpression(exp: &ast::Expression, env: &mut object::Environment) -> objec
             }
         }
         ast::Expression::InfixExpression {
-            Token,
             Left,
             Operator,
             Right,
+            ..
         } => {
             let left = evalExpression(Left, env);
-            if isError(&left) {
+            // an if starting a statement may break out of a loop before it has a value.
+            if isError(&left) || isLoopSignal(&left) {
                 return left;
             }
+            // the right operand of && and || is only evaluated if it decides the result.
+            match (Operator.as_str(), isTruthy(&left)) {
+                ("&&", false) => return FALSE,
+                ("||", true) => return TRUE,
+                _ => {}
+            }
             let right = evalExpression(Right, env);
             if isError(&right) {
                 return right;
             }
-            evalInfixExpression(Operator, left, right)
+            match Operator.as_str() {
+                "&&" | "||" => nativeBoolToBooleanObject(isTruthy(&right)),
+                _ => evalInfixExpression(Operator, left, right),
+            }
         }
-        ast::Expression::IfExpression {
-            Token,
-            Condition,
-            Consequence,
-            Alternative,
-        } => evalIfExpression(exp, env),
+        ast::Expression::ArrayLiteral { Elements, .. } => {
+            let elements = evalExpressions(Elements, env);
+            if elements.len() == 1 && isError(&elements[0]) {
+                return elements[0].clone();
+            }
+            object::Object::Array { Elements: elements }
+        }
+        ast::Expression::HashLiteral { Pairs, .. } => evalHashLiteral(Pairs, env),
+        ast::Expression::IndexExpression { Left, Index, .. } => {
+            let left = evalExpression(Left, env);
+            if isError(&left) || isLoopSignal(&left) {
+                return left;
+            }
+            let index = evalExpression(Index, env);
+            if isError(&index) {
+                return index;
+            }
+            evalIndexExpression(left, index)
+        }
+        ast::Expression::IfExpression { .. } => evalIfExpression(exp, env),
         ast::Expression::Identifier(idt) => evalIdentifier(idt, env),
         ast::Expression::FunctionLiteral {
-            Token,
-            Parameters,
-            Body,
+            Parameters, Body, ..
         } => object::Object::Function {
             Parameters: Parameters.clone(),
             Body: Body.clone(),
-            Env: env.clone(),
+            Env: Rc::clone(env),
         },
         ast::Expression::CallExpression {
-            Token,
             Function,
             Arguments,
+            ..
         } => {
             let function = evalExpression(Function, env);
-            if isError(&function) {
+            if isError(&function) || isLoopSignal(&function) {
                 return function;
             }
-            let mut args = evalExpressions(Arguments, env);
+            let args = evalExpressions(Arguments, env);
             if args.len() == 1 && isError(&args[0]) {
                 return args[0].clone();
             }
 
-            applyFunction(function, args)
+            let frame = object::Frame {
+                Function: callName(Function),
+                Span: exp.Span(),
+                Args: args.len(),
+            };
+            applyFunction(function, args, frame)
         }
         _ => object::Object::Null,
+    };
+    withSpan(result, exp.Span())
+}
+
+// withSpan records where an error was raised. errors that already carry a span came from a
+// nested node and are passed through unchanged.
+fn withSpan(obj: object::Object, span: token::Span) -> object::Object {
+    match obj {
+        object::Object::Error {
+            Message,
+            Span,
+            Trace,
+        } if Span == token::Span::default() => object::Object::Error {
+            Message,
+            Span: span,
+            Trace,
+        },
+        _ => obj,
     }
 }
 
@@ -137,16 +202,18 @@ fn evalPrefixExpression(operator: &String, right: object::Object) -> object::Obj
 
 fn evalBangOperatorExpression(right: object::Object) -> object::Object {
     match right {
-        TRUE => FALSE,
-        FALSE => TRUE,
-        NULL => TRUE,
+        object::Object::Boolean { Value } => nativeBoolToBooleanObject(!Value),
+        object::Object::Null => TRUE,
         _ => FALSE,
     }
 }
 
 fn evalMinusPrefixOperatorExpression(right: object::Object) -> object::Object {
     if let object::Object::Integer { Value } = right {
-        object::Object::Integer { Value: -Value }
+        match Value.checked_neg() {
+            Some(Value) => object::Object::Integer { Value },
+            None => newError(String::from("integer overflow")),
+        }
     } else {
         newError(format!("unknown operator: -{}", right.Type()))
     }
@@ -163,10 +230,26 @@ fn evalInfixExpression(
         }
     }
 
-    if *operator == String::from("==") {
-        return if left == right { TRUE } else { FALSE };
-    } else if *operator == String::from("!=") {
-        return if left != right { TRUE } else { FALSE };
+    if let (object::Object::String { Value: lv }, object::Object::String { Value: rv }) =
+        (&left, &right)
+    {
+        return evalStringInfixExpression(operator, lv, rv);
+    }
+
+    if let (object::Object::Array { Elements: lv }, object::Object::Array { Elements: rv }) =
+        (&left, &right)
+    {
+        if operator == "+" {
+            return object::Object::Array {
+                Elements: [lv.as_slice(), rv.as_slice()].concat(),
+            };
+        }
+    }
+
+    if operator == "==" {
+        return nativeBoolToBooleanObject(left == right);
+    } else if operator == "!=" {
+        return nativeBoolToBooleanObject(left != right);
     }
 
     if left.Type() != right.Type() {
@@ -187,47 +270,28 @@ fn evalInfixExpression(
 }
 
 fn evalIntegerInfixExpression(operator: &String, left: i64, right: i64) -> object::Object {
+    // results that don't fit in an i64, like i64::MIN / -1, are an error rather than wrapping.
+    let checked = |value: Option<i64>| match value {
+        Some(Value) => object::Object::Integer { Value },
+        None => newError(String::from("integer overflow")),
+    };
     match operator.as_ref() {
-        "+" => object::Object::Integer {
-            Value: left + right,
+        "+" => checked(left.checked_add(right)),
+        "-" => checked(left.checked_sub(right)),
+        "*" => checked(left.checked_mul(right)),
+        "/" | "%" if right == 0 => newError(String::from("division by zero")),
+        "/" => checked(left.checked_div(right)),
+        "%" => checked(left.checked_rem(right)),
+        "<" => nativeBoolToBooleanObject(left < right),
+        ">" => nativeBoolToBooleanObject(left > right),
+        ".." => object::Object::Range {
+            Start: left,
+            End: right,
         },
-        "-" => object::Object::Integer {
-            Value: left - right,
-        },
-        "*" => object::Object::Integer {
-            Value: left * right,
-        },
-        "/" => object::Object::Integer {
-            Value: left / right,
-        },
-        "<" => {
-            if left < right {
-                TRUE
-            } else {
-                FALSE
-            }
-        }
-        ">" => {
-            if left > right {
-                TRUE
-            } else {
-                FALSE
-            }
-        }
-        "==" => {
-            if left == right {
-                TRUE
-            } else {
-                FALSE
-            }
-        }
-        "!=" => {
-            if left != right {
-                TRUE
-            } else {
-                FALSE
-            }
-        }
+        "<=" => nativeBoolToBooleanObject(left <= right),
+        ">=" => nativeBoolToBooleanObject(left >= right),
+        "==" => nativeBoolToBooleanObject(left == right),
+        "!=" => nativeBoolToBooleanObject(left != right),
         _ => newError(format!(
             "unknown operator: {} {} {}",
             object::INTEGER_OBJ,
@@ -237,12 +301,106 @@ fn evalIntegerInfixExpression(operator: &String, left: i64, right: i64) -> objec
     }
 }
 
-fn evalIfExpression(ie: &ast::Expression, env: &mut object::Environment) -> object::Object {
+fn evalStringInfixExpression(operator: &str, left: &str, right: &str) -> object::Object {
+    match operator {
+        "+" => object::Object::String {
+            Value: left.to_string() + right,
+        },
+        "==" => nativeBoolToBooleanObject(left == right),
+        "!=" => nativeBoolToBooleanObject(left != right),
+        _ => newError(format!(
+            "unknown operator: {} {} {}",
+            object::STRING_OBJ,
+            operator,
+            object::STRING_OBJ
+        )),
+    }
+}
+
+fn evalIndexExpression(left: object::Object, index: object::Object) -> object::Object {
+    match (&left, &index) {
+        (object::Object::Array { Elements }, object::Object::Integer { Value }) => {
+            evalArrayIndexExpression(Elements, *Value)
+        }
+        (object::Object::Hash { Pairs }, _) => evalHashIndexExpression(Pairs, index),
+        _ => newError(format!(
+            "index operator not supported: {}[{}]",
+            left.Type(),
+            index.Type()
+        )),
+    }
+}
+
+fn evalArrayIndexExpression(elements: &[object::Object], index: i64) -> object::Object {
+    if index < 0 {
+        return newError(format!("negative array index: {}", index));
+    }
+    match elements.get(index as usize) {
+        Some(x) => x.clone(),
+        None => NULL,
+    }
+}
+
+fn evalHashIndexExpression(
+    pairs: &BTreeMap<object::HashKey, object::HashPair>,
+    index: object::Object,
+) -> object::Object {
+    let key = match index.HashKey() {
+        Some(x) => x,
+        None => return newError(format!("unusable as hash key: {}", index.Type())),
+    };
+    match pairs.get(&key) {
+        Some(pair) => pair.Value.clone(),
+        None => NULL,
+    }
+}
+
+fn evalHashLiteral(
+    pairs: &[(ast::Expression, ast::Expression)],
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    let mut result = BTreeMap::new();
+    for (keyNode, valueNode) in pairs.iter() {
+        let key = evalExpression(keyNode, env);
+        if isError(&key) {
+            return key;
+        }
+        let hashKey = match key.HashKey() {
+            Some(x) => x,
+            None => return newError(format!("unusable as hash key: {}", key.Type())),
+        };
+        let value = evalExpression(valueNode, env);
+        if isError(&value) {
+            return value;
+        }
+        result.insert(
+            hashKey,
+            object::HashPair {
+                Key: key,
+                Value: value,
+            },
+        );
+    }
+    object::Object::Hash { Pairs: result }
+}
+
+fn nativeBoolToBooleanObject(input: bool) -> object::Object {
+    if input {
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+fn evalIfExpression(
+    ie: &ast::Expression,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
     if let ast::Expression::IfExpression {
-        Token,
         Condition,
         Consequence,
         Alternative,
+        ..
     } = ie
     {
         let condition = evalExpression(Condition, env);
@@ -253,9 +411,9 @@ fn evalIfExpression(ie: &ast::Expression, env: &mut object::Environment) -> obje
             return evalStatement(Consequence, env);
         }
         if let ast::Statement::Nil = Alternative.as_ref() {
-            return object::Object::Null;
+            NULL
         } else {
-            return evalStatement(Alternative, env);
+            evalStatement(Alternative, env)
         }
     } else {
         panic!("ie is not ast::Expression::IfExpression. got={}", ie);
@@ -265,21 +423,103 @@ fn evalIfExpression(ie: &ast::Expression, env: &mut object::Environment) -> obje
 fn isTruthy(obj: &object::Object) -> bool {
     match *obj {
         object::Object::Null => false,
-        TRUE => true,
-        FALSE => false,
+        object::Object::Boolean { Value } => Value,
         _ => true,
     }
 }
 
-fn evalBlockStatement(block: &ast::Statement, env: &mut object::Environment) -> object::Object {
-    if let ast::Statement::BlockStatement { Token, Statements } = block {
+// evalWhileStatement runs body for as long as condition holds, each pass in a scope of its
+// own like the passes of a for loop. a loop is always null; only a return or an error inside
+// it ends it with a value.
+fn evalWhileStatement(
+    condition: &ast::Expression,
+    body: &ast::Statement,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    loop {
+        let cond = evalExpression(condition, env);
+        if isError(&cond) {
+            return cond;
+        }
+        if !isTruthy(&cond) {
+            return NULL;
+        }
+        let passEnv = object::NewEnclosedEnvironment(Rc::clone(env));
+        match evalBlockStatement(body, &passEnv) {
+            object::Object::Break => return NULL,
+            result @ (object::Object::ReturnValue { .. } | object::Object::Error { .. }) => {
+                return result
+            }
+            _ => {}
+        }
+    }
+}
+
+// evalForStatement binds the variables to each item of iterable in turn and runs body. every
+// iteration gets a scope of its own, enclosing env like the scope of a function call.
+fn evalForStatement(
+    variables: &[ast::Identifier],
+    iterable: &ast::Expression,
+    body: &ast::Statement,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    let obj = evalExpression(iterable, env);
+    if isError(&obj) {
+        return obj;
+    }
+    let mut it = match iterator::Iter(&obj, variables.len() == 2) {
+        Ok(it) => it,
+        Err(msg) => return withSpan(newError(msg), iterable.Span()),
+    };
+    loop {
+        let frame = object::Frame {
+            Function: callName(iterable),
+            Span: iterable.Span(),
+            Args: 0,
+        };
+        let step = applyFunction(it, vec![], frame);
+        if isError(&step) {
+            return withSpan(step, iterable.Span());
+        }
+        let values = match iterator::Step(step, variables.len()) {
+            Ok(Some((next, values))) => {
+                it = next;
+                values
+            }
+            Ok(None) => return NULL,
+            Err(msg) => return withSpan(newError(msg), iterable.Span()),
+        };
+
+        let loopEnv = object::NewEnclosedEnvironment(Rc::clone(env));
+        for (name, value) in variables.iter().zip(values) {
+            loopEnv.borrow_mut().Set(&name.Value, value);
+        }
+        match evalBlockStatement(body, &loopEnv) {
+            object::Object::Break => return NULL,
+            result @ (object::Object::ReturnValue { .. } | object::Object::Error { .. }) => {
+                return result
+            }
+            _ => {}
+        }
+    }
+}
+
+fn evalBlockStatement(
+    block: &ast::Statement,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    if let ast::Statement::BlockStatement { Statements, .. } = block {
         let mut result: object::Object = object::Object::Null;
         for statement in Statements.iter() {
             result = evalStatement(statement, env);
 
             if result != object::Object::Null {
                 let rt = result.Type();
-                if rt == object::RETURN_VALUE_OBJ || rt == object::ERROR_OBJ {
+                if rt == object::RETURN_VALUE_OBJ
+                    || rt == object::ERROR_OBJ
+                    || rt == object::BREAK_OBJ
+                    || rt == object::CONTINUE_OBJ
+                {
                     return result;
                 }
             }
@@ -291,24 +531,74 @@ fn evalBlockStatement(block: &ast::Statement, env: &mut object::Environment) ->
 }
 
 fn newError(format: String) -> object::Object {
-    object::Object::Error { Message: format }
+    object::Object::Error {
+        Message: format,
+        Span: token::Span::default(),
+        Trace: vec![],
+    }
 }
 
 fn isError(obj: &object::Object) -> bool {
     obj.Type() == object::ERROR_OBJ
 }
 
-fn evalIdentifier(node: &ast::Identifier, env: &mut object::Environment) -> object::Object {
-    let val = env.Get(&node.Value);
-    match val {
-        Some(s) => s.clone(),
+// evalAssignStatement runs `name = value` or a compound form like `name += value`. name must
+// already be bound, and not as a const. a compound form reads name before value is evaluated.
+fn evalAssignStatement(
+    operator: &str,
+    name: &ast::Identifier,
+    value: &ast::Expression,
+    span: token::Span,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    let current = match env.borrow().Get(&name.Value) {
+        Some(current) => current,
+        None if builtins::Lookup(&name.Value).is_some() => {
+            let err = newError(format!("cannot assign to builtin {}", name.Value));
+            return withSpan(err, name.Token.Span);
+        }
+        None => {
+            let err = newError(format!("identifier not found: {}", name.Value));
+            return withSpan(err, name.Token.Span);
+        }
+    };
+    let mut val = evalExpression(value, env);
+    if isError(&val) || isLoopSignal(&val) {
+        return val;
+    }
+    if operator != "=" {
+        let op = operator.trim_end_matches('=').to_string();
+        val = withSpan(evalInfixExpression(&op, current, val), span);
+        if isError(&val) {
+            return val;
+        }
+    }
+    match env.borrow_mut().Assign(&name.Value, val) {
+        Ok(val) => val,
+        Err(msg) => withSpan(newError(msg), name.Token.Span),
+    }
+}
+
+fn isLoopSignal(obj: &object::Object) -> bool {
+    matches!(obj, object::Object::Break | object::Object::Continue)
+}
+
+fn evalIdentifier(
+    node: &ast::Identifier,
+    env: &Rc<RefCell<object::Environment>>,
+) -> object::Object {
+    if let Some(val) = env.borrow().Get(&node.Value) {
+        return val;
+    }
+    match builtins::Lookup(&node.Value) {
+        Some(builtin) => builtin,
         None => newError(format!("identifier not found: {}", node.Value)),
     }
 }
 
 fn evalExpressions(
-    exps: &Vec<ast::Expression>,
-    env: &mut object::Environment,
+    exps: &[ast::Expression],
+    env: &Rc<RefCell<object::Environment>>,
 ) -> Vec<object::Object> {
     let mut result = vec![];
     for e in exps.iter() {
@@ -322,32 +612,80 @@ fn evalExpressions(
     result
 }
 
-fn applyFunction(f: object::Object, args: Vec<object::Object>) -> object::Object {
-    if let object::Object::Function {
-        Parameters,
-        Body,
-        Env,
-    } = &f
-    {
-        let mut extendedEnv = extendFunctionEnv(f.clone(), args);
-        let evaluated = evalStatement(Body, &mut extendedEnv);
-        unwrapReturnValue(evaluated)
-    } else {
-        return newError(format!("not a function: {}", f.Type()));
+// callName is the name a function is called by, as shown in tracebacks.
+fn callName(function: &ast::Expression) -> String {
+    match function {
+        ast::Expression::Identifier(idt) => idt.Value.clone(),
+        _ => String::from("<anonymous>"),
+    }
+}
+
+// applyFunction calls f. errors raised inside a Monkey function record the call's frame on
+// their way out, so by the time one reaches the top it carries the whole call stack.
+fn applyFunction(
+    f: object::Object,
+    args: Vec<object::Object>,
+    frame: object::Frame,
+) -> object::Object {
+    match &f {
+        object::Object::Function {
+            Parameters, Body, ..
+        } => {
+            if args.len() != Parameters.len() {
+                return newError(format!(
+                    "wrong number of arguments. got={}, want={}",
+                    args.len(),
+                    Parameters.len()
+                ));
+            }
+            let extendedEnv = extendFunctionEnv(&f, args);
+            let evaluated = evalStatement(Body, &extendedEnv);
+            match unwrapReturnValue(evaluated) {
+                object::Object::Error {
+                    Message,
+                    Span,
+                    mut Trace,
+                } => {
+                    Trace.push(frame);
+                    object::Object::Error {
+                        Message,
+                        Span,
+                        Trace,
+                    }
+                }
+                obj => obj,
+            }
+        }
+        object::Object::Builtin { Fn, .. } => Fn(args),
+        object::Object::Host { Arity, Fn, .. } => {
+            if !Arity.Accepts(args.len()) {
+                return newError(format!(
+                    "wrong number of arguments. got={}, want={}",
+                    args.len(),
+                    Arity
+                ));
+            }
+            match Fn(&args) {
+                Ok(obj) => obj,
+                Err(msg) => newError(msg),
+            }
+        }
+        _ => newError(format!("not a function: {}", f.Type())),
     }
 }
 
-fn extendFunctionEnv(f: object::Object, args: Vec<object::Object>) -> object::Environment {
+fn extendFunctionEnv(
+    f: &object::Object,
+    args: Vec<object::Object>,
+) -> Rc<RefCell<object::Environment>> {
     if let object::Object::Function {
-        Parameters,
-        Body,
-        Env,
+        Parameters, Env, ..
     } = f
     {
-        let mut env = object::NewEnclosedEnvironment(Env);
-        for (i, param) in Parameters.iter().enumerate() {
+        let env = object::NewEnclosedEnvironment(Rc::clone(Env));
+        for (param, arg) in Parameters.iter().zip(args) {
             if let ast::Expression::Identifier(x) = param {
-                env.Set(&x.Value, args[i].clone());
+                env.borrow_mut().Set(&x.Value, arg);
             } else {
                 panic!();
             }
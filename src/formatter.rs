@@ -0,0 +1,410 @@
+// source formatter. the program is parsed and printed back from the ast: blocks indented by
+// four spaces, one statement per line, and parentheses only where the parser's precedences
+// need them. comments are kept: each stays on its own line before the statement it preceded,
+// at the end of the line it ended, or inside an expression before the part it preceded. a line
+// comment inside an expression ends the line, and the expression continues on the next one.
+// formatting already formatted source returns it unchanged.
+use crate::ast::{Expression, Program, Statement};
+use crate::token::{Trivia, TriviaKind};
+use crate::{cst, lexer, parser};
+use std::cell::Cell;
+
+const INDENT: &str = "    ";
+
+// precedence of expressions that are never split by an operator around them.
+const ATOM: i32 = parser::INDEX + 1;
+
+// Format parses src and returns it formatted, or the syntax errors that stopped it.
+pub fn Format(src: &str) -> Result<String, Vec<parser::ParseError>> {
+    let tree = cst::Parse(src)?;
+    let f = Formatter {
+        src,
+        comments: tree.Comments(),
+        next: Cell::new(0),
+    };
+    Ok(f.program(&tree.Program))
+}
+
+struct Formatter<'a> {
+    // the source the ast came from, consulted for blank lines between statements.
+    src: &'a str,
+    comments: Vec<&'a Trivia>,
+    // comments before this index have been written.
+    next: Cell<usize>,
+}
+
+impl<'a> Formatter<'a> {
+    fn program(&self, program: &Program) -> String {
+        let mut out = String::new();
+        self.statements(&program.Statements, 0, false, self.src.len(), &mut out);
+        out
+    }
+
+    // statements writes one statement per line, with the comments before end that have not
+    // been written yet. a blank line in the source is kept; runs of blank lines become one. in a
+    // block the value of the last expression statement is the block's value, so it is written
+    // without a semicolon.
+    fn statements(
+        &self,
+        stmts: &[Statement],
+        depth: usize,
+        inBlock: bool,
+        end: usize,
+        out: &mut String,
+    ) {
+        let stmts: Vec<&Statement> = stmts
+            .iter()
+            .filter(|s| !matches!(s, Statement::Nil))
+            .collect();
+        let indent = INDENT.repeat(depth);
+        // the end of the last thing written, to find blank lines after it.
+        let mut prev: Option<usize> = None;
+        for (i, stmt) in stmts.iter().enumerate() {
+            let span = stmt.Span();
+            while let Some(c) = self.takeComment(span.Start) {
+                self.blankLine(prev, c.Span.Start, out);
+                *out += &format!("{}{}\n", indent, c.Text);
+                prev = Some(c.Span.End);
+            }
+            self.blankLine(prev, span.Start, out);
+
+            let last = inBlock && i == stmts.len() - 1;
+            let text = self.statement(stmt, depth, last);
+            // comments inside the statement that no nested block or expression took, like one
+            // between `let` and the name, go above it.
+            while let Some(c) = self.takeComment(span.End) {
+                *out += &format!("{}{}\n", indent, c.Text);
+            }
+            *out += &indent;
+            *out += &text;
+            prev = Some(span.End);
+            let next = stmts.get(i + 1).map_or(end, |s| s.Span().Start);
+            if let Some(c) = self.trailingComment(span.End, next) {
+                *out += " ";
+                *out += &c.Text;
+                prev = Some(c.Span.End);
+            }
+            *out += "\n";
+        }
+        while let Some(c) = self.takeComment(end) {
+            self.blankLine(prev, c.Span.Start, out);
+            *out += &format!("{}{}\n", indent, c.Text);
+            prev = Some(c.Span.End);
+        }
+    }
+
+    // blankLine writes an empty line if the source has one between prev and next.
+    fn blankLine(&self, prev: Option<usize>, next: usize, out: &mut String) {
+        if let Some(prev) = prev {
+            if next > prev && self.src[prev..next].matches('\n').count() > 1 {
+                *out += "\n";
+            }
+        }
+    }
+
+    // takeComment returns the next unwritten comment if it starts before end.
+    fn takeComment(&self, end: usize) -> Option<&'a Trivia> {
+        let c = self.comments.get(self.next.get())?;
+        if c.Span.Start >= end {
+            return None;
+        }
+        self.next.set(self.next.get() + 1);
+        Some(c)
+    }
+
+    // trailingComment returns the next unwritten comment if it is on the line ending at pos and
+    // starts before end.
+    fn trailingComment(&self, pos: usize, end: usize) -> Option<&'a Trivia> {
+        let c = self.comments.get(self.next.get())?;
+        if self.src[pos..c.Span.Start].contains('\n') {
+            return None;
+        }
+        self.takeComment(end)
+    }
+
+    // hasComment reports whether an unwritten comment starts before end.
+    fn hasComment(&self, end: usize) -> bool {
+        self.comments
+            .get(self.next.get())
+            .is_some_and(|c| c.Span.Start < end)
+    }
+
+    fn statement(&self, stmt: &Statement, depth: usize, last: bool) -> String {
+        match stmt {
+            // comments between the value and the semicolon stay before the semicolon.
+            Statement::LetStatement {
+                Token,
+                Name,
+                Value,
+                Span,
+            } => format!(
+                "{} {} = {}{};",
+                Token.Literal,
+                Name.Value,
+                self.expression(Value, depth),
+                self.closingComments(Span.End, depth)
+            ),
+            Statement::AssignStatement {
+                Token,
+                Name,
+                Value,
+                Span,
+            } => format!(
+                "{} {} {}{};",
+                Name.Value,
+                Token.Literal,
+                self.expression(Value, depth),
+                self.closingComments(Span.End, depth)
+            ),
+            Statement::ReturnStatement {
+                ReturnValue, Span, ..
+            } => format!(
+                "return {}{};",
+                self.expression(ReturnValue, depth),
+                self.closingComments(Span.End, depth)
+            ),
+            Statement::ExpressionStatement {
+                Expression, Span, ..
+            } => {
+                let text =
+                    self.expression(Expression, depth) + &self.closingComments(Span.End, depth);
+                // an if keeps its semicolon too: without it, a next statement like `-1` or
+                // `[1]` would continue the if as an operator or an index.
+                if last {
+                    text
+                } else {
+                    text + ";"
+                }
+            }
+            Statement::BlockStatement { .. } => self.block(stmt, depth),
+            Statement::WhileStatement {
+                Condition, Body, ..
+            } => format!(
+                "while ({}) {}",
+                self.expression(Condition, depth),
+                self.block(Body, depth)
+            ),
+            Statement::ForStatement {
+                Variables,
+                Iterable,
+                Body,
+                ..
+            } => {
+                let names: Vec<&str> = Variables.iter().map(|v| v.Value.as_str()).collect();
+                format!(
+                    "for ({} in {}) {}",
+                    names.join(", "),
+                    self.expression(Iterable, depth),
+                    self.block(Body, depth)
+                )
+            }
+            Statement::BreakStatement { .. } => String::from("break;"),
+            Statement::ContinueStatement { .. } => String::from("continue;"),
+            Statement::Nil => String::new(),
+        }
+    }
+
+    // block writes `{ x }` on one line when it holds a single expression that fits on a line and
+    // spreads it over several lines otherwise.
+    fn block(&self, block: &Statement, depth: usize) -> String {
+        match self.inlineBlock(block, depth) {
+            Some(text) => text,
+            None => self.multilineBlock(block, depth),
+        }
+    }
+
+    fn inlineBlock(&self, block: &Statement, depth: usize) -> Option<String> {
+        match block {
+            Statement::BlockStatement { Span, .. } if self.hasComment(Span.End) => None,
+            Statement::BlockStatement { Statements, .. } => match Statements.as_slice() {
+                [] => Some(String::from("{}")),
+                [Statement::ExpressionStatement { Expression, .. }] => {
+                    let text = self.expression(Expression, depth);
+                    if text.contains('\n') || matches!(Expression, Expression::IfExpression { .. })
+                    {
+                        None
+                    } else {
+                        Some(format!("{{ {} }}", text))
+                    }
+                }
+                _ => None,
+            },
+            _ => Some(String::from("{}")),
+        }
+    }
+
+    fn multilineBlock(&self, block: &Statement, depth: usize) -> String {
+        let (stmts, span) = match block {
+            Statement::BlockStatement {
+                Statements, Span, ..
+            } if !Statements.is_empty() || self.hasComment(Span.End) => (Statements, Span),
+            _ => return String::from("{}"),
+        };
+        let mut out = String::from("{\n");
+        self.statements(stmts, depth + 1, true, span.End, &mut out);
+        out + &INDENT.repeat(depth) + "}"
+    }
+
+    // expression writes exp after the comments that come before it, so they stay where they
+    // were inside the statement.
+    fn expression(&self, exp: &Expression, depth: usize) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.takeComment(exp.Span().Start) {
+            out += &c.Text;
+            if c.Kind == TriviaKind::LineComment {
+                out += "\n";
+                out += &INDENT.repeat(depth + 1);
+            } else {
+                out += " ";
+            }
+        }
+        out + &self.bareExpression(exp, depth)
+    }
+
+    // closingComments writes the comments before end, the closing bracket of an expression,
+    // that none of its parts took.
+    fn closingComments(&self, end: usize, depth: usize) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.takeComment(end) {
+            out += " ";
+            out += &c.Text;
+            if c.Kind == TriviaKind::LineComment {
+                out += "\n";
+                out += &INDENT.repeat(depth);
+            }
+        }
+        out
+    }
+
+    fn bareExpression(&self, exp: &Expression, depth: usize) -> String {
+        match exp {
+            Expression::Nil => String::new(),
+            Expression::Identifier(ident) => ident.Value.clone(),
+            Expression::IntergerLiteral { Token, .. } => Token.Literal.clone(),
+            Expression::Boolean { Value, .. } => Value.to_string(),
+            Expression::StringLiteral { Value, .. } => lexer::Quote(Value),
+            Expression::PrefixExpression {
+                Operator, Right, ..
+            } => Operator.clone() + &self.operand(Right, parser::PREFIX, depth),
+            Expression::InfixExpression {
+                Left,
+                Operator,
+                Right,
+                ..
+            } => {
+                // operators are left-associative, so a right operand of the same precedence
+                // needs parentheses and a left one does not.
+                let p = parser::Precedence(Operator);
+                // ranges read best without spaces: `1..10`.
+                let sep = if Operator == ".." { "" } else { " " };
+                format!(
+                    "{}{}{}{}{}",
+                    self.operand(Left, p, depth),
+                    sep,
+                    Operator,
+                    sep,
+                    self.operand(Right, p + 1, depth)
+                )
+            }
+            Expression::ArrayLiteral { Elements, Span, .. } => format!(
+                "[{}{}]",
+                self.list(Elements, depth),
+                self.closingComments(Span.End, depth)
+            ),
+            Expression::HashLiteral { Pairs, Span, .. } => {
+                let pairs: Vec<String> = Pairs
+                    .iter()
+                    .map(|(k, v)| {
+                        format!(
+                            "{}: {}",
+                            self.expression(k, depth),
+                            self.expression(v, depth)
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{{}{}}}",
+                    pairs.join(", "),
+                    self.closingComments(Span.End, depth)
+                )
+            }
+            // calls and indexes chain in any order, so `f(x)[0]` needs no parentheses.
+            Expression::IndexExpression {
+                Left, Index, Span, ..
+            } => format!(
+                "{}[{}{}]",
+                self.operand(Left, parser::CALL, depth),
+                self.expression(Index, depth),
+                self.closingComments(Span.End, depth)
+            ),
+            Expression::IfExpression {
+                Condition,
+                Consequence,
+                Alternative,
+                ..
+            } => {
+                let cond = self.expression(Condition, depth);
+                if matches!(Alternative.as_ref(), Statement::Nil) {
+                    return format!("if ({}) {}", cond, self.block(Consequence, depth));
+                }
+                // both branches go on one line or neither does.
+                match (
+                    self.inlineBlock(Consequence, depth),
+                    self.inlineBlock(Alternative, depth),
+                ) {
+                    (Some(cons), Some(alt)) => format!("if ({}) {} else {}", cond, cons, alt),
+                    _ => format!(
+                        "if ({}) {} else {}",
+                        cond,
+                        self.multilineBlock(Consequence, depth),
+                        self.multilineBlock(Alternative, depth)
+                    ),
+                }
+            }
+            Expression::FunctionLiteral {
+                Parameters, Body, ..
+            } => format!(
+                "fn({}) {}",
+                self.list(Parameters, depth),
+                self.block(Body, depth)
+            ),
+            Expression::CallExpression {
+                Function,
+                Arguments,
+                Span,
+                ..
+            } => format!(
+                "{}({}{})",
+                self.operand(Function, parser::CALL, depth),
+                self.list(Arguments, depth),
+                self.closingComments(Span.End, depth)
+            ),
+        }
+    }
+
+    // operand formats exp where the surrounding operator binds with precedence min, adding
+    // parentheses if exp binds more loosely.
+    fn operand(&self, exp: &Expression, min: i32, depth: usize) -> String {
+        let text = self.expression(exp, depth);
+        if precedence(exp) < min {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+
+    fn list(&self, exps: &[Expression], depth: usize) -> String {
+        let items: Vec<String> = exps.iter().map(|e| self.expression(e, depth)).collect();
+        items.join(", ")
+    }
+}
+
+fn precedence(exp: &Expression) -> i32 {
+    match exp {
+        Expression::PrefixExpression { .. } => parser::PREFIX,
+        Expression::InfixExpression { Operator, .. } => parser::Precedence(Operator),
+        Expression::CallExpression { .. } => parser::CALL,
+        Expression::IndexExpression { .. } => parser::INDEX,
+        _ => ATOM,
+    }
+}
@@ -0,0 +1,113 @@
+// ansi coloring of repl input and output. source is split into tokens with the lossless
+// lexer, so the colors always agree with how the text will be parsed.
+use crate::object::Object;
+use crate::{cst, token};
+
+const KEYWORD: &str = "\x1b[1;35m";
+const IDENT: &str = "\x1b[36m";
+const NUMBER: &str = "\x1b[33m";
+const STRING: &str = "\x1b[32m";
+const OPERATOR: &str = "\x1b[1m";
+const ILLEGAL: &str = "\x1b[4;31m";
+const FUNCTION: &str = "\x1b[34m";
+const ERROR: &str = "\x1b[1;31m";
+const COMMENT: &str = "\x1b[90m";
+const RESET: &str = "\x1b[0m";
+
+// Source returns src with every token and comment wrapped in the color of its kind.
+// whitespace and delimiters are copied unchanged.
+pub fn Source(src: &str) -> String {
+    let mut out = String::new();
+    for t in cst::Tokenize(src) {
+        t.Leading.iter().for_each(|tr| out += &trivia(tr));
+        out += &paint(tokenColor(t.Token.Type), &t.Text);
+        t.Trailing.iter().for_each(|tr| out += &trivia(tr));
+    }
+    out
+}
+
+fn trivia(t: &token::Trivia) -> String {
+    match t.Kind {
+        token::TriviaKind::Whitespace => t.Text.clone(),
+        _ => paint(COMMENT, &t.Text),
+    }
+}
+
+fn tokenColor(t: token::TokenType) -> &'static str {
+    match t {
+        token::FUNCTION
+        | token::LET
+        | token::CONST
+        | token::TRUE
+        | token::FALSE
+        | token::IF
+        | token::ELSE
+        | token::RETURN
+        | token::WHILE
+        | token::BREAK
+        | token::CONTINUE
+        | token::FOR
+        | token::IN => KEYWORD,
+        token::IDENT => IDENT,
+        token::INT => NUMBER,
+        token::STRING => STRING,
+        token::ILLEGAL => ILLEGAL,
+        token::ASSIGN
+        | token::PLUS_ASSIGN
+        | token::MINUS_ASSIGN
+        | token::ASTERISK_ASSIGN
+        | token::SLASH_ASSIGN
+        | token::PLUS
+        | token::MINUS
+        | token::BANG
+        | token::ASTERISK
+        | token::SLASH
+        | token::PERCENT
+        | token::LT
+        | token::GT
+        | token::LT_EQ
+        | token::GT_EQ
+        | token::EQ
+        | token::NOT_EQ
+        | token::AND
+        | token::OR
+        | token::DOTDOT => OPERATOR,
+        _ => "",
+    }
+}
+
+// Value is obj.Inspect() colored by type. the elements of arrays and hashes are colored
+// individually.
+pub fn Value(obj: &Object) -> String {
+    match obj {
+        Object::Integer { .. } | Object::Range { .. } => paint(NUMBER, &obj.Inspect()),
+        Object::String { .. } => paint(STRING, &obj.Inspect()),
+        Object::Boolean { .. } | Object::Null => paint(KEYWORD, &obj.Inspect()),
+        Object::Error { .. } => paint(ERROR, &obj.Inspect()),
+        Object::Array { Elements } => {
+            let elements: Vec<String> = Elements.iter().map(Value).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Object::Hash { Pairs } => {
+            let pairs: Vec<String> = Pairs
+                .values()
+                .map(|pair| format!("{}: {}", Value(&pair.Key), Value(&pair.Value)))
+                .collect();
+            format!("{{{}}}", pairs.join(", "))
+        }
+        Object::ReturnValue { Value: v } => Value(v),
+        Object::Break | Object::Continue => paint(KEYWORD, &obj.Inspect()),
+        Object::Function { .. } => Source(&obj.Inspect()),
+        Object::Builtin { .. }
+        | Object::CompiledFunction { .. }
+        | Object::Closure { .. }
+        | Object::Host { .. } => paint(FUNCTION, &obj.Inspect()),
+    }
+}
+
+fn paint(color: &str, text: &str) -> String {
+    if color.is_empty() || text.is_empty() {
+        return text.to_string();
+    }
+    format!("{}{}{}", color, text, RESET)
+}
@@ -0,0 +1,90 @@
+use crate::{evaluator, lexer, object, object::Object, parser};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+// Interpreter is the entry point for embedding monkey in a rust program. it owns the global
+// environment, so bindings made by one call to eval are visible to the next.
+pub struct Interpreter {
+    env: Rc<RefCell<object::Environment>>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Error {
+    Parse(Vec<parser::ParseError>),
+    Runtime(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(errors) => {
+                let msgs: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "parser errors: {}", msgs.join("; "))
+            }
+            Error::Runtime(msg) => write!(f, "runtime error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter {
+            env: object::NewEnvironment(),
+        }
+    }
+
+    // binds a rust closure to `name` in the global environment. monkey code calls it like any
+    // other function; a call whose argument count does not satisfy `arity` fails before `f`
+    // runs, and an Err returned by `f` becomes a monkey error object. like `set`, it fails if
+    // `name` is a const.
+    pub fn register_fn<F>(&mut self, name: &str, arity: object::Arity, f: F) -> Result<(), Error>
+    where
+        F: Fn(&[Object]) -> Result<Object, String> + 'static,
+    {
+        let host = Object::Host {
+            Name: name.to_string(),
+            Arity: arity,
+            Fn: Rc::new(f),
+        };
+        self.set(name, host)
+    }
+
+    // binds `name` in the global environment as a let statement would, so a const declared by
+    // an earlier script cannot be replaced.
+    pub fn set(&mut self, name: &str, val: Object) -> Result<(), Error> {
+        match self.env.borrow_mut().Define(name, val, false) {
+            Ok(_) => Ok(()),
+            Err(msg) => Err(Error::Runtime(msg)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        self.env.borrow().Get(name)
+    }
+
+    pub fn eval(&mut self, input: &str) -> Result<Object, Error> {
+        let l = lexer::New(input.to_string());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        if !p.Errors().is_empty() {
+            return Err(Error::Parse(p.Errors().clone()));
+        }
+        match evaluator::Eval(program, &self.env) {
+            Object::Error { Message, .. } => Err(Error::Runtime(Message)),
+            obj => Ok(obj),
+        }
+    }
+
+    pub fn env(&self) -> &Rc<RefCell<object::Environment>> {
+        &self.env
+    }
+}
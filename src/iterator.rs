@@ -0,0 +1,117 @@
+// the iteration protocol for-in loops use, shared by the evaluator and the vm. an iterator is a
+// function of no arguments. each call returns `[item, next]`, where next is the iterator over
+// the remaining items, or null when there are none left. monkey functions that follow this
+// are iterable as they are:
+//
+//     let upto = fn(i, n) { fn() { if (i < n) { [i, upto(i + 1, n)] } } };
+//     for (x in upto(0, 3)) { puts(x) }
+//
+// arrays, strings, hashes and ranges are iterated by native iterators, which are host
+// functions. they copy the collection once, when the loop starts, and every later iterator
+// shares that copy, so changing the collection inside the loop does not change what it visits.
+use crate::object::{self, Object};
+use std::rc::Rc;
+
+// Iter returns an iterator over obj. if pairs is set, the items are `[key, value]` pairs:
+// index and element for arrays, strings and ranges, key and value for hashes. items of a
+// function iterator are used as they are.
+pub fn Iter(obj: &Object, pairs: bool) -> Result<Object, String> {
+    let items: Rc<dyn Fn(usize) -> Option<Object>> = match obj {
+        Object::Array { Elements } => {
+            let elements = Rc::new(Elements.clone());
+            Rc::new(move |i| elements.get(i).cloned())
+        }
+        Object::String { Value } => {
+            let chars: Rc<Vec<char>> = Rc::new(Value.chars().collect());
+            Rc::new(move |i| {
+                chars.get(i).map(|c| Object::String {
+                    Value: c.to_string(),
+                })
+            })
+        }
+        Object::Hash { Pairs } => {
+            let entries: Rc<Vec<object::HashPair>> = Rc::new(Pairs.values().cloned().collect());
+            Rc::new(move |i| {
+                entries.get(i).map(|pair| {
+                    if pairs {
+                        pair2(pair.Key.clone(), pair.Value.clone())
+                    } else {
+                        pair.Key.clone()
+                    }
+                })
+            })
+        }
+        Object::Range { Start, End } => {
+            let (start, end) = (*Start, *End);
+            Rc::new(move |i| {
+                let n = start.checked_add(i as i64)?;
+                (n < end).then_some(Object::Integer { Value: n })
+            })
+        }
+        Object::Function { .. }
+        | Object::Closure { .. }
+        | Object::Builtin { .. }
+        | Object::Host { .. } => return Ok(obj.clone()),
+        _ => return Err(format!("cannot iterate over {}", obj.Type())),
+    };
+    // hashes already paired their items with keys.
+    if pairs && !matches!(obj, Object::Hash { .. }) {
+        let inner = items;
+        return Ok(native(
+            Rc::new(move |i| inner(i).map(|item| pair2(Object::Integer { Value: i as i64 }, item))),
+            0,
+        ));
+    }
+    Ok(native(items, 0))
+}
+
+// Step unpacks what a call to an iterator returned: None at the end, otherwise the iterator
+// for the rest and the values for the loop's vars variables.
+pub fn Step(step: Object, vars: usize) -> Result<Option<(Object, Vec<Object>)>, String> {
+    let (item, next) = match step {
+        Object::Null => return Ok(None),
+        Object::Array { mut Elements } if Elements.len() == 2 => {
+            let next = Elements.pop().unwrap();
+            (Elements.pop().unwrap(), next)
+        }
+        _ => {
+            return Err(format!(
+                "iterator returned {}, want [item, next] or null",
+                step.Type()
+            ))
+        }
+    };
+    if vars == 1 {
+        return Ok(Some((next, vec![item])));
+    }
+    match item {
+        Object::Array { Elements } if Elements.len() == vars => Ok(Some((next, Elements))),
+        _ => Err(format!(
+            "cannot unpack {} into {} variables",
+            item.Type(),
+            vars
+        )),
+    }
+}
+
+// native is the iterator starting at item i of items.
+fn native(items: Rc<dyn Fn(usize) -> Option<Object>>, i: usize) -> Object {
+    Object::Host {
+        Name: String::from("iterator"),
+        Arity: object::Arity::Exact(0),
+        Fn: Rc::new(move |_| {
+            Ok(match items(i) {
+                Some(item) => Object::Array {
+                    Elements: vec![item, native(Rc::clone(&items), i + 1)],
+                },
+                None => Object::Null,
+            })
+        }),
+    }
+}
+
+fn pair2(key: Object, value: Object) -> Object {
+    Object::Array {
+        Elements: vec![key, value],
+    }
+}
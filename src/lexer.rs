@@ -2,18 +2,52 @@ use crate::token;
 use token::Token;
 
 pub struct Lexer {
-    input: String,
+    input: Vec<char>,
+    // (byte offset, line, column) of each char in input, plus one entry for the end.
+    positions: Vec<(usize, usize, usize)>,
     position: i32,
     readPosition: i32,
     ch: Option<String>,
+    // set by NewLossless: skipped whitespace and comments are kept in trivia.
+    keepTrivia: bool,
+    trivia: Vec<token::Trivia>,
 }
 
 pub fn New(input: String) -> Lexer {
+    NewAt(input, 0, 1)
+}
+
+// NewLossless is New, but the whitespace and comments skipped before each token are kept and
+// can be collected with TakeTrivia.
+pub fn NewLossless(input: String) -> Lexer {
+    let mut l = New(input);
+    l.keepTrivia = true;
+    l
+}
+
+// NewAt lexes input as if it started at the given byte offset and line of a larger source,
+// e.g. one line of a REPL session.
+pub fn NewAt(input: String, offset: usize, firstLine: usize) -> Lexer {
+    let mut positions = vec![];
+    let (mut line, mut column) = (firstLine, 1);
+    for (i, c) in input.char_indices() {
+        positions.push((offset + i, line, column));
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    positions.push((offset + input.len(), line, column));
     let mut l = Lexer {
-        input,
+        input: input.chars().collect(),
+        positions,
         position: 0,
         readPosition: 0,
         ch: Some(String::from("")),
+        keepTrivia: false,
+        trivia: vec![],
     };
     l.readChar();
     l
@@ -24,23 +58,41 @@ impl Lexer {
         if self.readPosition >= self.input.len() as i32 {
             self.ch = None;
         } else {
-            //self.ch = String::from(&self.input[self.readPosition..2]);
-            self.ch = Some(
-                self.input
-                    .chars()
-                    .nth(self.readPosition as usize)
-                    .unwrap()
-                    .to_string(),
-            );
+            self.ch = Some(self.input[self.readPosition as usize].to_string());
         }
         self.position = self.readPosition;
         self.readPosition += 1;
     }
 
     pub fn NextToken(&mut self) -> Token {
+        self.skipTrivia();
+        let (start, line, column) = self.currentPosition();
+        let mut tok = self.readToken();
+        tok.Span = token::Span {
+            Start: start,
+            End: self.currentPosition().0,
+            Line: line,
+            Column: column,
+        };
+        tok
+    }
+
+    // TakeTrivia returns the trivia skipped since the last call. it is always empty unless the
+    // lexer was made with NewLossless.
+    pub fn TakeTrivia(&mut self) -> Vec<token::Trivia> {
+        std::mem::take(&mut self.trivia)
+    }
+
+    fn currentPosition(&self) -> (usize, usize, usize) {
+        let i = (self.position as usize).min(self.input.len());
+        self.positions[i]
+    }
+
+    fn readToken(&mut self) -> Token {
         let tok: Token;
         let assign_str = &String::from("=");
         let semicolon_str = &String::from(";");
+        let colon_str = &String::from(":");
         let lparen_str = &String::from("(");
         let rparen_str = &String::from(")");
         let comma_str = &String::from(",");
@@ -53,8 +105,14 @@ impl Lexer {
         let asterisk_str = &String::from("*");
         let lt_str = &String::from("<");
         let gt_str = &String::from(">");
-
-        self.skipWhitespace();
+        let percent_str = &String::from("%");
+        let ampersand_str = &String::from("&");
+        let pipe_str = &String::from("|");
+        let dot_str = &String::from(".");
+        let quote_str = &String::from("\"");
+        let lbracket_str = &String::from("[");
+        let rbracket_str = &String::from("]");
+        let next = self.peekChar();
 
         match &self.ch {
             Some(s) if s == assign_str => {
@@ -68,6 +126,9 @@ impl Lexer {
             Some(s) if s == semicolon_str => {
                 tok = newToken(token::SEMICOLON, &self.ch);
             }
+            Some(s) if s == colon_str => {
+                tok = newToken(token::COLON, &self.ch);
+            }
             Some(s) if s == lparen_str => {
                 tok = newToken(token::LPAREN, &self.ch);
             }
@@ -77,6 +138,10 @@ impl Lexer {
             Some(s) if s == comma_str => {
                 tok = newToken(token::COMMA, &self.ch);
             }
+            Some(s) if s == plus_str && next.as_deref() == Some("=") => {
+                self.readChar();
+                tok = newToken(token::PLUS_ASSIGN, &Some(String::from("+=")));
+            }
             Some(s) if s == plus_str => {
                 tok = newToken(token::PLUS, &self.ch);
             }
@@ -86,6 +151,16 @@ impl Lexer {
             Some(s) if s == rbrace_str => {
                 tok = newToken(token::RBRACE, &self.ch);
             }
+            Some(s) if s == lbracket_str => {
+                tok = newToken(token::LBRACKET, &self.ch);
+            }
+            Some(s) if s == rbracket_str => {
+                tok = newToken(token::RBRACKET, &self.ch);
+            }
+            Some(s) if s == minus_str && next.as_deref() == Some("=") => {
+                self.readChar();
+                tok = newToken(token::MINUS_ASSIGN, &Some(String::from("-=")));
+            }
             Some(s) if s == minus_str => {
                 tok = newToken(token::MINUS, &self.ch);
             }
@@ -97,18 +172,73 @@ impl Lexer {
                     tok = newToken(token::BANG, &self.ch);
                 }
             }
+            Some(s) if s == slash_str && next.as_deref() == Some("*") => {
+                // skipTrivia stops at a block comment only if it is never closed.
+                let mut literal = String::new();
+                while let Some(c) = self.ch.take() {
+                    literal += &c;
+                    self.readChar();
+                }
+                return newToken(token::ILLEGAL, &Some(literal));
+            }
+            Some(s) if s == slash_str && next.as_deref() == Some("=") => {
+                self.readChar();
+                tok = newToken(token::SLASH_ASSIGN, &Some(String::from("/=")));
+            }
             Some(s) if s == slash_str => {
                 tok = newToken(token::SLASH, &self.ch);
             }
+            Some(s) if s == asterisk_str && next.as_deref() == Some("=") => {
+                self.readChar();
+                tok = newToken(token::ASTERISK_ASSIGN, &Some(String::from("*=")));
+            }
             Some(s) if s == asterisk_str => {
                 tok = newToken(token::ASTERISK, &self.ch);
             }
+            Some(s) if s == percent_str => {
+                tok = newToken(token::PERCENT, &self.ch);
+            }
             Some(s) if s == lt_str => {
-                tok = newToken(token::LT, &self.ch);
+                if self.peekChar() == Some(String::from("=")) {
+                    self.readChar();
+                    tok = newToken(token::LT_EQ, &Some(String::from("<=")));
+                } else {
+                    tok = newToken(token::LT, &self.ch);
+                }
             }
             Some(s) if s == gt_str => {
-                tok = newToken(token::GT, &self.ch);
+                if self.peekChar() == Some(String::from("=")) {
+                    self.readChar();
+                    tok = newToken(token::GT_EQ, &Some(String::from(">=")));
+                } else {
+                    tok = newToken(token::GT, &self.ch);
+                }
             }
+            // a single & or | is not an operator.
+            Some(s) if s == ampersand_str => {
+                if self.peekChar() == Some(String::from("&")) {
+                    self.readChar();
+                    tok = newToken(token::AND, &Some(String::from("&&")));
+                } else {
+                    tok = newToken(token::ILLEGAL, &self.ch);
+                }
+            }
+            Some(s) if s == pipe_str => {
+                if self.peekChar() == Some(String::from("|")) {
+                    self.readChar();
+                    tok = newToken(token::OR, &Some(String::from("||")));
+                } else {
+                    tok = newToken(token::ILLEGAL, &self.ch);
+                }
+            }
+            Some(s) if s == dot_str && next.as_deref() == Some(".") => {
+                self.readChar();
+                tok = newToken(token::DOTDOT, &Some(String::from("..")));
+            }
+            Some(s) if s == quote_str => match self.readString() {
+                Ok(s) => tok = newToken(token::STRING, &Some(s)),
+                Err(s) => tok = newToken(token::ILLEGAL, &Some(s)),
+            },
             None => tok = newToken(token::EOF, &Some(String::from(""))),
             _ => {
                 if isLetter(&self.ch) {
@@ -147,14 +277,120 @@ impl Lexer {
         res
     }
 
-    fn skipWhitespace(&mut self) {
-        while self.ch.as_ref() != None
-            && (*self.ch.as_ref().unwrap() == String::from(" ")
-                || *self.ch.as_ref().unwrap() == String::from("\t")
-                || *self.ch.as_ref().unwrap() == String::from("\n")
-                || *self.ch.as_ref().unwrap() == String::from("\r"))
-        {
+    // reads the body of a string literal, resolving escape sequences. on a malformed
+    // literal the raw source text is returned as the error so it can become an ILLEGAL token.
+    fn readString(&mut self) -> Result<String, String> {
+        let mut res = String::from("");
+        let mut raw = String::from("\"");
+        let mut valid = true;
+        loop {
+            self.readChar();
+            let ch = match self.ch.clone() {
+                Some(s) => s,
+                None => return Err(raw),
+            };
+            raw += &ch;
+            match ch.as_str() {
+                "\"" => break,
+                "\\" => {
+                    self.readChar();
+                    let escaped = match self.ch.clone() {
+                        Some(s) => s,
+                        None => return Err(raw),
+                    };
+                    raw += &escaped;
+                    match escaped.as_str() {
+                        "n" => res.push('\n'),
+                        "t" => res.push('\t'),
+                        "\"" => res.push('"'),
+                        "\\" => res.push('\\'),
+                        "u" => match self.readUnicodeEscape(&mut raw) {
+                            Some(c) => res.push(c),
+                            None => valid = false,
+                        },
+                        _ => valid = false,
+                    }
+                }
+                _ => res += &ch,
+            }
+        }
+        if valid {
+            Ok(res)
+        } else {
+            Err(raw)
+        }
+    }
+
+    // reads the `{XXXX}` part of a `\u{XXXX}` escape.
+    fn readUnicodeEscape(&mut self, raw: &mut String) -> Option<char> {
+        if self.peekChar() != Some(String::from("{")) {
+            return None;
+        }
+        self.readChar();
+        raw.push('{');
+        let mut hex = String::from("");
+        while let Some(s) = self.peekChar() {
+            if s == "\"" {
+                break;
+            }
             self.readChar();
+            raw.push_str(&s);
+            if s == "}" {
+                return u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+            }
+            hex += &s;
+        }
+        None
+    }
+
+    // skipTrivia skips whitespace and comments, recording them if trivia is kept. an unclosed
+    // block comment is left for readToken to report.
+    fn skipTrivia(&mut self) {
+        loop {
+            let start = self.position as usize;
+            let (ch, next) = (self.ch.clone(), self.peekChar());
+            let kind = match ch.as_deref() {
+                Some(" ") | Some("\t") | Some("\n") | Some("\r") => {
+                    while let Some(" ") | Some("\t") | Some("\n") | Some("\r") = self.ch.as_deref()
+                    {
+                        self.readChar();
+                    }
+                    token::TriviaKind::Whitespace
+                }
+                Some("/") if next.as_deref() == Some("/") => {
+                    while self.ch.is_some() && self.ch.as_deref() != Some("\n") {
+                        self.readChar();
+                    }
+                    token::TriviaKind::LineComment
+                }
+                Some("/") if next.as_deref() == Some("*") => {
+                    let end = (start + 3..self.input.len())
+                        .find(|&i| self.input[i - 1] == '*' && self.input[i] == '/');
+                    match end {
+                        Some(end) => {
+                            while (self.position as usize) <= end {
+                                self.readChar();
+                            }
+                        }
+                        None => return,
+                    }
+                    token::TriviaKind::BlockComment
+                }
+                _ => return,
+            };
+            if self.keepTrivia {
+                let (offset, line, column) = self.positions[start];
+                self.trivia.push(token::Trivia {
+                    Kind: kind,
+                    Text: self.input[start..self.position as usize].iter().collect(),
+                    Span: token::Span {
+                        Start: offset,
+                        End: self.currentPosition().0,
+                        Line: line,
+                        Column: column,
+                    },
+                });
+            }
         }
     }
 
@@ -162,21 +398,46 @@ impl Lexer {
         if self.readPosition >= self.input.len() as i32 {
             None
         } else {
-            Some(
-                self.input
-                    .chars()
-                    .nth(self.readPosition as usize)
-                    .unwrap()
-                    .to_string(),
-            )
+            Some(self.input[self.readPosition as usize].to_string())
+        }
+    }
+}
+
+// Dump lexes input and returns one token per line as `line:col<TAB>TYPE<TAB>"literal"`, ending
+// with the EOF token.
+pub fn Dump(input: &str) -> String {
+    let mut l = New(input.to_string());
+    let mut out = String::new();
+    loop {
+        let tok = l.NextToken();
+        out += &format!("{}\t{}\t{:?}\n", tok.Span, tok.Type, tok.Literal);
+        if tok.Type == token::EOF {
+            return out;
+        }
+    }
+}
+
+// Quote writes s as a string literal, escaping what the lexer would not read back as is.
+pub fn Quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\t' => out += "\\t",
+            c if c.is_control() => out += &format!("\\u{{{:04x}}}", c as u32),
+            c => out.push(c),
         }
     }
+    out + "\""
 }
 
 pub fn newToken(tokenType: token::TokenType, ch: &Option<String>) -> Token {
     Token {
         Type: tokenType,
         Literal: ch.clone().unwrap(),
+        Span: token::Span::default(),
     }
 }
 
@@ -184,7 +445,7 @@ fn isLetter(s: &Option<String>) -> bool {
     if let Some(ss) = s {
         let ch = ss.clone().into_bytes()[0];
         // alphabet(a-z, A-Z) or _
-        (97 <= ch && ch <= 122) || (65 <= ch && ch <= 90) || ch == 95
+        ch.is_ascii_lowercase() || ch.is_ascii_uppercase() || ch == 95
     } else {
         false
     }
@@ -194,7 +455,7 @@ fn isDigit(s: &Option<String>) -> bool {
     if let Some(ss) = s {
         let ch = ss.clone().into_bytes()[0];
         // 0-9
-        48 <= ch && ch <= 57
+        ch.is_ascii_digit()
     } else {
         false
     }
@@ -1,7 +1,25 @@
+#![allow(non_snake_case, non_upper_case_globals)]
+
+pub mod artifact;
 pub mod ast;
+pub mod builtins;
+pub mod cli;
+pub mod code;
+pub mod compiler;
+pub mod cst;
+pub mod diagnostics;
+pub mod editor;
 pub mod evaluator;
+pub mod formatter;
+pub mod highlight;
+pub mod interpreter;
+pub mod iterator;
 pub mod lexer;
 pub mod object;
 pub mod parser;
 pub mod repl;
+pub mod symbol_table;
 pub mod token;
+pub mod vm;
+
+pub use interpreter::Interpreter;
@@ -1,8 +1,9 @@
 extern crate monkey_interpreter;
-use monkey_interpreter::repl;
+use monkey_interpreter::cli;
+use std::env;
+use std::process;
 
 fn main() {
-    println!("Hello! This is the Monkey programming language!");
-    println!("Feel free to type in commands");
-    repl::Start();
+    let args: Vec<String> = env::args().skip(1).collect();
+    process::exit(cli::Main(&args));
 }
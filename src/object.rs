@@ -1,17 +1,91 @@
-use crate::ast;
-use std::collections::HashMap;
+use crate::{ast, code, lexer, token};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::{Rc, Weak};
 
 pub type ObjectType = &'static str;
 
 pub const BOOLEAN_OBJ: ObjectType = "BOOLEAN";
 pub const INTEGER_OBJ: ObjectType = "INTEGER";
+pub const STRING_OBJ: ObjectType = "STRING";
+pub const ARRAY_OBJ: ObjectType = "ARRAY";
+pub const HASH_OBJ: ObjectType = "HASH";
+pub const RANGE_OBJ: ObjectType = "RANGE";
+pub const BUILTIN_OBJ: ObjectType = "BUILTIN";
+
+pub type BuiltinFunction = fn(Vec<Object>) -> Object;
+pub type HostFunction = Rc<dyn Fn(&[Object]) -> Result<Object, String>>;
+
+// number of arguments a host function accepts.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+    Any,
+}
+
+impl Arity {
+    pub fn Accepts(&self, n: usize) -> bool {
+        match *self {
+            Arity::Exact(want) => n == want,
+            Arity::AtLeast(want) => n >= want,
+            Arity::Any => true,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::AtLeast(n) => write!(f, "at least {}", n),
+            Arity::Any => write!(f, "any"),
+        }
+    }
+}
+
+// Frame is one call on the evaluator's stack: the name the function was called by (or
+// `<anonymous>`), where the call happened and how many arguments were passed.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Frame {
+    pub Function: String,
+    pub Span: token::Span,
+    pub Args: usize,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = if self.Args == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{}, {} arg{}, called at {}",
+            self.Function, self.Args, plural, self.Span
+        )
+    }
+}
+
 pub const NULL_OBJ: ObjectType = "NULL";
 pub const RETURN_VALUE_OBJ: ObjectType = "RETURN_VALUE";
+pub const BREAK_OBJ: ObjectType = "BREAK";
+pub const CONTINUE_OBJ: ObjectType = "CONTINUE";
 pub const ERROR_OBJ: ObjectType = "ERROR";
 pub const FUNCTION_OBJ: ObjectType = "FUNCTION";
+pub const COMPILED_FUNCTION_OBJ: ObjectType = "COMPILED_FUNCTION";
 
-#[derive(PartialEq, Eq, Clone)]
+// CompiledFunction is a function body lowered to bytecode by the compiler.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct CompiledFunction {
+    pub Instructions: code::Instructions,
+    pub NumLocals: usize,
+    pub NumParameters: usize,
+    // the name the function was bound to with `let`, empty for anonymous functions.
+    pub Name: String,
+}
+
+#[derive(Clone)]
 pub enum Object {
     Integer {
         Value: i64,
@@ -19,28 +93,126 @@ pub enum Object {
     Boolean {
         Value: bool,
     },
+    String {
+        Value: String,
+    },
+    Array {
+        Elements: Vec<Object>,
+    },
+    Hash {
+        Pairs: BTreeMap<HashKey, HashPair>,
+    },
+    // the integers from Start up to but not including End, produced by `Start..End`.
+    Range {
+        Start: i64,
+        End: i64,
+    },
     ReturnValue {
         Value: Box<Object>,
     },
+    // signals that unwind the body of the innermost loop, like ReturnValue does a function.
+    Break,
+    Continue,
     Error {
         Message: String,
+        // where the error was raised. the evaluator fills this in from the innermost node
+        // that produced the error.
+        Span: token::Span,
+        // calls the error unwound through, innermost first.
+        Trace: Vec<Frame>,
     },
     Function {
         Parameters: Vec<ast::Expression>,
         Body: Box<ast::Statement>,
-        Env: Environment,
+        Env: Rc<RefCell<Environment>>,
+    },
+    Builtin {
+        Name: &'static str,
+        Fn: BuiltinFunction,
+    },
+    CompiledFunction {
+        Fn: Rc<CompiledFunction>,
+    },
+    // a compiled function together with the free variables it captured when it was created.
+    // each variable is a cell shared with the scope that declared it and any other closure
+    // that captured it, so an assignment through one is seen by all of them.
+    Closure {
+        Fn: Rc<CompiledFunction>,
+        Free: Rc<Vec<Rc<RefCell<Object>>>>,
+    },
+    Host {
+        Name: String,
+        Arity: Arity,
+        Fn: HostFunction,
     },
     Null,
 }
 
+impl PartialEq for Object {
+    fn eq(&self, other: &Object) -> bool {
+        match (self, other) {
+            (Object::Integer { Value: l }, Object::Integer { Value: r }) => l == r,
+            (Object::Boolean { Value: l }, Object::Boolean { Value: r }) => l == r,
+            (Object::String { Value: l }, Object::String { Value: r }) => l == r,
+            (Object::Array { Elements: l }, Object::Array { Elements: r }) => l == r,
+            (Object::Hash { Pairs: l }, Object::Hash { Pairs: r }) => l == r,
+            (Object::Range { Start: ls, End: le }, Object::Range { Start: rs, End: re }) => {
+                ls == rs && le == re
+            }
+            (Object::ReturnValue { Value: l }, Object::ReturnValue { Value: r }) => l == r,
+            (Object::Break, Object::Break) => true,
+            (Object::Continue, Object::Continue) => true,
+            (Object::Error { Message: l, .. }, Object::Error { Message: r, .. }) => l == r,
+            // functions capture their environment, which may contain the function itself,
+            // so two functions are only equal when they share the same scope.
+            (
+                Object::Function {
+                    Parameters: lp,
+                    Body: lb,
+                    Env: le,
+                },
+                Object::Function {
+                    Parameters: rp,
+                    Body: rb,
+                    Env: re,
+                },
+            ) => lp == rp && lb == rb && Rc::ptr_eq(le, re),
+            (Object::Builtin { Name: l, .. }, Object::Builtin { Name: r, .. }) => l == r,
+            (Object::CompiledFunction { Fn: l }, Object::CompiledFunction { Fn: r }) => l == r,
+            // a cell may hold the closure itself, so closures are equal when they share the
+            // same variables rather than equal values.
+            (Object::Closure { Fn: lf, Free: lv }, Object::Closure { Fn: rf, Free: rv }) => {
+                Rc::ptr_eq(lf, rf)
+                    && lv.len() == rv.len()
+                    && lv.iter().zip(rv.iter()).all(|(l, r)| Rc::ptr_eq(l, r))
+            }
+            (Object::Host { Fn: l, .. }, Object::Host { Fn: r, .. }) => Rc::ptr_eq(l, r),
+            (Object::Null, Object::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Object {}
+
 impl Object {
     pub fn Type(&self) -> ObjectType {
         match self {
             Object::Integer { .. } => INTEGER_OBJ,
             Object::Boolean { .. } => BOOLEAN_OBJ,
+            Object::String { .. } => STRING_OBJ,
+            Object::Array { .. } => ARRAY_OBJ,
+            Object::Hash { .. } => HASH_OBJ,
+            Object::Range { .. } => RANGE_OBJ,
             Object::ReturnValue { .. } => RETURN_VALUE_OBJ,
+            Object::Break => BREAK_OBJ,
+            Object::Continue => CONTINUE_OBJ,
             Object::Error { .. } => ERROR_OBJ,
             Object::Function { .. } => FUNCTION_OBJ,
+            Object::Builtin { .. } => BUILTIN_OBJ,
+            Object::CompiledFunction { .. } => COMPILED_FUNCTION_OBJ,
+            Object::Closure { .. } => FUNCTION_OBJ,
+            Object::Host { .. } => BUILTIN_OBJ,
             Object::Null => NULL_OBJ,
         }
     }
@@ -49,12 +221,28 @@ impl Object {
         match self {
             Object::Integer { Value } => format!("{}", Value),
             Object::Boolean { Value } => format!("{}", Value),
+            Object::String { Value } => lexer::Quote(Value),
+            Object::Array { Elements } => {
+                let mut elements = vec![];
+                for e in Elements.iter() {
+                    elements.push(e.Inspect());
+                }
+                format!("[{}]", elements.join(", "))
+            }
+            Object::Hash { Pairs } => {
+                let mut pairs = vec![];
+                for pair in Pairs.values() {
+                    pairs.push(format!("{}: {}", pair.Key.Inspect(), pair.Value.Inspect()));
+                }
+                format!("{{{}}}", pairs.join(", "))
+            }
+            Object::Range { Start, End } => format!("{}..{}", Start, End),
             Object::ReturnValue { Value } => Value.Inspect(),
-            Object::Error { Message } => format! {"ERROR: {}", Message},
+            Object::Break => String::from("break"),
+            Object::Continue => String::from("continue"),
+            Object::Error { Message, .. } => format! {"ERROR: {}", Message},
             Object::Function {
-                Parameters,
-                Body,
-                Env,
+                Parameters, Body, ..
             } => {
                 let mut params = vec![];
                 for p in Parameters.iter() {
@@ -64,11 +252,53 @@ impl Object {
                     {}
                 }}", params.join(" "), Body.into_string()}
             }
+            Object::Builtin { .. } => String::from("builtin function"),
+            Object::CompiledFunction { Fn } => format!("compiled function {}", Fn.Name),
+            Object::Closure { Fn, .. } if Fn.Name.is_empty() => String::from("closure"),
+            Object::Closure { Fn, .. } => format!("closure {}", Fn.Name),
+            Object::Host { Name, .. } => format!("host function {}", Name),
             Object::Null => String::from("null"),
         }
     }
 }
 
+impl Object {
+    // integers, booleans and strings can be used as hash keys. everything else is rejected.
+    pub fn HashKey(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer { Value } => Some(HashKey {
+                Type: self.Type(),
+                Value: *Value as u64,
+            }),
+            Object::Boolean { Value } => Some(HashKey {
+                Type: self.Type(),
+                Value: *Value as u64,
+            }),
+            Object::String { Value } => {
+                let mut h = DefaultHasher::new();
+                Value.hash(&mut h);
+                Some(HashKey {
+                    Type: self.Type(),
+                    Value: h.finish(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct HashKey {
+    pub Type: ObjectType,
+    pub Value: u64,
+}
+
+#[derive(PartialEq, Eq, Clone)]
+pub struct HashPair {
+    pub Key: Object,
+    pub Value: Object,
+}
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -78,24 +308,65 @@ impl fmt::Display for Object {
                 "Object::Boolean{{Value: {}}}",
                 Value
             },
+            Object::String { Value } => write! {
+                f,
+                "Object::String{{Value: {}}}",
+                Value
+            },
+            Object::Array { Elements } => write! {
+                f,
+                "Object::Array{{Elements: {}}}",
+                Elements.len()
+            },
+            Object::Hash { Pairs } => write! {
+                f,
+                "Object::Hash{{Pairs: {}}}",
+                Pairs.len()
+            },
+            Object::Range { Start, End } => write! {
+                f,
+                "Object::Range{{Start: {}, End: {}}}",
+                Start,
+                End
+            },
             Object::ReturnValue { Value } => write! {
                 f,
                 "Object::ReturnValue{{Value: {}}}",
                 Value
             },
-            Object::Error { Message } => write! {
+            Object::Break => write!(f, "Object::Break"),
+            Object::Continue => write!(f, "Object::Continue"),
+            Object::Error { Message, .. } => write! {
                 f,
                 "Object::Error{{Message: {}}}",
                 Message
             },
-            Object::Function {
-                Parameters,
-                Body,
-                Env,
-            } => write! {
+            Object::Function { .. } => write! {
                 f,
                 "Object::Function"
             },
+            Object::Builtin { Name, .. } => write! {
+                f,
+                "Object::Builtin{{Name: {}}}",
+                Name
+            },
+            Object::CompiledFunction { Fn } => write! {
+                f,
+                "Object::CompiledFunction{{Name: {}}}",
+                Fn.Name
+            },
+            Object::Closure { Fn, Free } => write! {
+                f,
+                "Object::Closure{{Name: {}, Free: {}}}",
+                Fn.Name,
+                Free.len()
+            },
+            Object::Host { Name, Arity, .. } => write! {
+                f,
+                "Object::Host{{Name: {}, Arity: {}}}",
+                Name,
+                Arity
+            },
             Object::Null => write! {
                 f,
                 "Object::Null"
@@ -104,41 +375,155 @@ impl fmt::Display for Object {
     }
 }
 
-#[derive(PartialEq, Eq, Clone)]
+impl fmt::Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
 pub struct Environment {
-    store: HashMap<String, Object>,
-    outer: Option<Box<Environment>>,
+    store: HashMap<String, Binding>,
+    outer: Option<Rc<RefCell<Environment>>>,
+    // the scope itself, for handing back to the functions it stores.
+    this: Weak<RefCell<Environment>>,
 }
 
-pub fn NewEnvironment() -> Environment {
-    let s = HashMap::new();
-    Environment {
-        store: s,
-        outer: None,
-    }
+struct Binding {
+    Value: Stored,
+    Const: bool,
 }
 
-pub fn NewEnclosedEnvironment(outer: Environment) -> Environment {
-    Environment {
-        store: HashMap::new(),
-        outer: Some(Box::new(outer)),
-    }
+// a function bound in the scope it closes over would keep that scope alive through its own
+// Env, so neither would ever be freed. it is stored without its Env and given the scope back
+// when it is read. a function that reaches its scope some other way still keeps it alive, as
+// when it is put in an array bound there, or returned from a call and bound in the caller.
+enum Stored {
+    Value(Object),
+    Function {
+        Parameters: Vec<ast::Expression>,
+        Body: Box<ast::Statement>,
+    },
+}
+
+pub fn NewEnvironment() -> Rc<RefCell<Environment>> {
+    newEnvironment(None)
+}
+
+pub fn NewEnclosedEnvironment(outer: Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+    newEnvironment(Some(outer))
+}
+
+fn newEnvironment(outer: Option<Rc<RefCell<Environment>>>) -> Rc<RefCell<Environment>> {
+    Rc::new_cyclic(|this| {
+        RefCell::new(Environment {
+            store: HashMap::new(),
+            outer,
+            this: this.clone(),
+        })
+    })
 }
 
 impl Environment {
-    pub fn Get(&self, name: &String) -> Option<&Object> {
-        let obj = self.store.get(name);
-        match obj {
-            Some(x) => Some(x),
+    pub fn Get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(x) => Some(self.unpack(&x.Value)),
             None => match &self.outer {
-                Some(x) => x.Get(name),
+                Some(x) => x.borrow().Get(name),
                 None => None,
             },
         }
     }
 
-    pub fn Set(&mut self, name: &String, val: Object) -> Object {
-        self.store.insert(name.clone(), val.clone());
+    // IsConst reports whether the innermost binding of name is a const.
+    pub fn IsConst(&self, name: &str) -> bool {
+        match self.store.get(name) {
+            Some(x) => x.Const,
+            None => self
+                .outer
+                .as_ref()
+                .is_some_and(|x| x.borrow().IsConst(name)),
+        }
+    }
+
+    // Names returns every name visible from this scope, innermost scope first.
+    pub fn Names(&self) -> Vec<String> {
+        let mut res: Vec<String> = self.store.keys().cloned().collect();
+        res.sort();
+        if let Some(outer) = &self.outer {
+            for name in outer.borrow().Names() {
+                if !res.contains(&name) {
+                    res.push(name);
+                }
+            }
+        }
+        res
+    }
+
+    // Set binds name in this scope, replacing whatever was bound to it here. it is for scopes
+    // that hold no consts, like those of parameters and loop variables; anything else goes
+    // through Define.
+    pub fn Set(&mut self, name: &str, val: Object) -> Object {
+        self.bind(name, val, false)
+    }
+
+    // Define is Set for let and const statements: a const of this scope cannot be declared
+    // again.
+    pub fn Define(&mut self, name: &str, val: Object, constant: bool) -> Result<Object, String> {
+        if self.store.get(name).is_some_and(|x| x.Const) {
+            return Err(format!("cannot redeclare const {}", name));
+        }
+        Ok(self.bind(name, val, constant))
+    }
+
+    fn bind(&mut self, name: &str, val: Object, constant: bool) -> Object {
+        let binding = Binding {
+            Value: self.pack(&val),
+            Const: constant,
+        };
+        self.store.insert(name.to_string(), binding);
         val
     }
+
+    fn pack(&self, val: &Object) -> Stored {
+        match val {
+            Object::Function {
+                Parameters,
+                Body,
+                Env,
+            } if Rc::as_ptr(Env) == self.this.as_ptr() => Stored::Function {
+                Parameters: Parameters.clone(),
+                Body: Body.clone(),
+            },
+            _ => Stored::Value(val.clone()),
+        }
+    }
+
+    fn unpack(&self, stored: &Stored) -> Object {
+        match stored {
+            Stored::Value(x) => x.clone(),
+            // the scope is being read, so something still holds it.
+            Stored::Function { Parameters, Body } => Object::Function {
+                Parameters: Parameters.clone(),
+                Body: Body.clone(),
+                Env: self.this.upgrade().unwrap(),
+            },
+        }
+    }
+
+    // Assign updates name in the innermost scope that binds it. nothing changes if no scope
+    // does or the binding is a const.
+    pub fn Assign(&mut self, name: &str, val: Object) -> Result<Object, String> {
+        match self.store.get_mut(name) {
+            Some(x) if x.Const => Err(format!("cannot assign to const {}", name)),
+            Some(_) => {
+                let stored = self.pack(&val);
+                self.store.get_mut(name).unwrap().Value = stored;
+                Ok(val)
+            }
+            None => match &self.outer {
+                Some(x) => x.borrow_mut().Assign(name, val),
+                None => Err(format!("identifier not found: {}", name)),
+            },
+        }
+    }
 }
@@ -1,32 +1,172 @@
-use crate::{ast, ast::Program, lexer, lexer::Lexer, token, token::Token};
+use crate::{ast, ast::Program, lexer, lexer::Lexer, token};
 use phf::phf_map;
 use std::collections::HashMap;
-
-const LOWEST: i32 = 1;
-const EQUALS: i32 = 2;
-const LESSGRATER: i32 = 3;
-const SUM: i32 = 4;
-const PRODUCT: i32 = 5;
-const PREFIX: i32 = 6;
-const CALL: i32 = 7;
+use std::fmt;
+
+pub const LOWEST: i32 = 1;
+pub const LOGICALOR: i32 = 2;
+pub const LOGICALAND: i32 = 3;
+pub const EQUALS: i32 = 4;
+pub const LESSGRATER: i32 = 5;
+pub const RANGE: i32 = 6;
+pub const SUM: i32 = 7;
+pub const PRODUCT: i32 = 8;
+pub const PREFIX: i32 = 9;
+pub const CALL: i32 = 10;
+pub const INDEX: i32 = 11;
 
 static precedences: phf::Map<&'static str, i32> = phf_map! {
+    "||" => LOGICALOR,
+    "&&" => LOGICALAND,
     "==" => EQUALS,
     "!=" => EQUALS,
     "<" => LESSGRATER,
     ">" => LESSGRATER,
+    "<=" => LESSGRATER,
+    ">=" => LESSGRATER,
+    ".." => RANGE,
     "+" => SUM,
     "-" => SUM,
     "/" => PRODUCT,
     "*" => PRODUCT,
+    "%" => PRODUCT,
     "(" => CALL,
+    "[" => INDEX,
 };
 
+// Precedence is how tightly the infix operator op binds, LOWEST if op is not one.
+pub fn Precedence(op: &str) -> i32 {
+    match precedences.get(op) {
+        Some(x) => *x,
+        None => LOWEST,
+    }
+}
+
+fn isAssignment(t: token::TokenType) -> bool {
+    matches!(
+        t,
+        token::ASSIGN
+            | token::PLUS_ASSIGN
+            | token::MINUS_ASSIGN
+            | token::ASTERISK_ASSIGN
+            | token::SLASH_ASSIGN
+    )
+}
+
+// ParseError is a syntax error together with the source range it was found at.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    UnexpectedToken {
+        Expected: token::TokenType,
+        Got: token::TokenType,
+        Span: token::Span,
+    },
+    NoPrefixParseFn {
+        Got: token::TokenType,
+        Span: token::Span,
+    },
+    InvalidIntegerLiteral {
+        Literal: String,
+        Span: token::Span,
+    },
+    IllegalToken {
+        Literal: String,
+        Span: token::Span,
+    },
+    // break or continue that is not inside a loop of the current function.
+    OutsideLoop {
+        Keyword: String,
+        Span: token::Span,
+    },
+    // break or continue in a block of an expression, like `1 + if (a) { break; }`, whose
+    // loop is outside the expression.
+    InsideExpression {
+        Keyword: String,
+        Span: token::Span,
+    },
+    // an assignment to something that is not a name, like `a[0] = 1`.
+    InvalidAssignment {
+        Span: token::Span,
+    },
+    ConstAssigned {
+        Name: String,
+        Span: token::Span,
+    },
+    // a let or const for a name that is already a const of the same scope.
+    ConstRedeclared {
+        Name: String,
+        Span: token::Span,
+    },
+}
+
+impl ParseError {
+    pub fn Span(&self) -> token::Span {
+        match self {
+            ParseError::UnexpectedToken { Span, .. } => *Span,
+            ParseError::NoPrefixParseFn { Span, .. } => *Span,
+            ParseError::InvalidIntegerLiteral { Span, .. } => *Span,
+            ParseError::IllegalToken { Span, .. } => *Span,
+            ParseError::OutsideLoop { Span, .. } => *Span,
+            ParseError::InsideExpression { Span, .. } => *Span,
+            ParseError::InvalidAssignment { Span, .. } => *Span,
+            ParseError::ConstAssigned { Span, .. } => *Span,
+            ParseError::ConstRedeclared { Span, .. } => *Span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken { Expected, Got, .. } => write!(
+                f,
+                "expected next token to be {}, got {}, instead",
+                Expected, Got
+            ),
+            ParseError::NoPrefixParseFn { Got, .. } => {
+                write!(f, "no prefix parse function for {} found", Got)
+            }
+            ParseError::InvalidIntegerLiteral { Literal, .. } => {
+                write!(f, "could not parse {} as integer", Literal)
+            }
+            ParseError::IllegalToken { Literal, .. } => write!(f, "illegal token {}", Literal),
+            ParseError::OutsideLoop { Keyword, .. } => write!(f, "{} outside of a loop", Keyword),
+            ParseError::InsideExpression { Keyword, .. } => {
+                write!(f, "{} inside an expression", Keyword)
+            }
+            ParseError::InvalidAssignment { .. } => write!(f, "can only assign to a name"),
+            ParseError::ConstAssigned { Name, .. } => write!(f, "cannot assign to const {}", Name),
+            ParseError::ConstRedeclared { Name, .. } => {
+                write!(f, "cannot redeclare const {}", Name)
+            }
+        }
+    }
+}
+
 pub struct Parser {
     l: lexer::Lexer,
     curToken: token::Token,
     peekToken: token::Token,
-    errors: Vec<String>,
+    errors: Vec<ParseError>,
+    // number of loops around the statement being parsed, within the current function.
+    loopDepth: usize,
+    // the loops around the statement are outside the expression it is part of, so break and
+    // continue cannot reach them.
+    inExpression: bool,
+    // the if about to be parsed starts an expression statement. its blocks may use break and
+    // continue, as no operand is waiting for its value.
+    statementIf: bool,
+    // the scopes the program will run in, innermost last.
+    scopes: Vec<Scope>,
+}
+
+// Scope is what the parser knows of a scope at run time: the program, a function body or the
+// body of a loop. blocks of ifs are part of the scope around them, though the two branches of
+// an if don't see each other's declarations.
+#[derive(Default)]
+struct Scope {
+    // the names declared so far, and whether each is a const.
+    names: HashMap<String, bool>,
 }
 
 impl Lexer {
@@ -36,6 +176,10 @@ impl Lexer {
             curToken: lexer::newToken(token::ILLEGAL, &Some(String::from(""))),
             peekToken: lexer::newToken(token::ILLEGAL, &Some(String::from(""))),
             errors: vec![],
+            loopDepth: 0,
+            inExpression: false,
+            statementIf: false,
+            scopes: vec![Scope::default()],
         };
         p.nextToken();
         p.nextToken();
@@ -45,8 +189,13 @@ impl Lexer {
 
 impl Parser {
     fn nextToken(&mut self) {
-        self.curToken = lexer::newToken(self.peekToken.Type, &Some(self.peekToken.Literal.clone()));
-        self.peekToken = self.l.NextToken();
+        self.curToken = std::mem::replace(&mut self.peekToken, self.l.NextToken());
+    }
+
+    // spanFrom returns the span from start up to the end of the current token, which is the
+    // last token of the node being parsed.
+    fn spanFrom(&self, start: token::Span) -> token::Span {
+        start.To(self.curToken.Span)
     }
 
     pub fn ParseProgram(&mut self) -> Program {
@@ -63,10 +212,14 @@ impl Parser {
 
     fn parseStatement(&mut self) -> Option<ast::Statement> {
         match self.curToken.Type {
-            token::LET => return self.parseLetStatement(),
-            token::RETURN => return self.parseReturnStatement(),
-            _ => return self.parseExpressionStatement(),
-        };
+            token::LET | token::CONST => self.parseLetStatement(),
+            token::IDENT if isAssignment(self.peekToken.Type) => self.parseAssignStatement(),
+            token::RETURN => self.parseReturnStatement(),
+            token::WHILE => self.parseWhileStatement(),
+            token::FOR => self.parseForStatement(),
+            token::BREAK | token::CONTINUE => self.parseLoopControlStatement(),
+            _ => self.parseExpressionStatement(),
+        }
     }
 
     fn parseLetStatement(&mut self) -> Option<ast::Statement> {
@@ -79,6 +232,7 @@ impl Parser {
             Token: self.curToken.clone(),
             Value: self.curToken.Literal.clone(),
         };
+        self.declare(&temp_name, temp_token.Type == token::CONST);
 
         if !self.expectPeek(token::ASSIGN) {
             return None;
@@ -86,17 +240,81 @@ impl Parser {
 
         self.nextToken();
 
-        let stmt = ast::Statement::LetStatement {
+        let temp_value = self.parseExpression(LOWEST);
+
+        if self.peekTokenIs(token::SEMICOLON) {
+            self.nextToken();
+        }
+
+        Some(ast::Statement::LetStatement {
+            Span: self.spanFrom(temp_token.Span),
             Token: temp_token,
             Name: temp_name,
-            Value: self.parseExpression(LOWEST),
+            Value: temp_value,
+        })
+    }
+
+    fn parseAssignStatement(&mut self) -> Option<ast::Statement> {
+        let temp_name = ast::Identifier {
+            Token: self.curToken.clone(),
+            Value: self.curToken.Literal.clone(),
         };
+        if self.isConst(&temp_name.Value) {
+            self.errors.push(ParseError::ConstAssigned {
+                Name: temp_name.Value.clone(),
+                Span: temp_name.Token.Span,
+            });
+        }
+
+        self.nextToken();
+        let temp_token = self.curToken.clone();
+        self.nextToken();
+
+        let temp_value = self.parseExpression(LOWEST);
 
         if self.peekTokenIs(token::SEMICOLON) {
             self.nextToken();
         }
 
-        Some(stmt)
+        Some(ast::Statement::AssignStatement {
+            Span: self.spanFrom(temp_name.Token.Span),
+            Token: temp_token,
+            Name: temp_name,
+            Value: temp_value,
+        })
+    }
+
+    // declare records name in the current scope, reporting a const it would redeclare.
+    fn declare(&mut self, name: &ast::Identifier, constant: bool) {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.names.get(&name.Value) == Some(&true) {
+            self.errors.push(ParseError::ConstRedeclared {
+                Name: name.Value.clone(),
+                Span: name.Token.Span,
+            });
+        } else {
+            scope.names.insert(name.Value.clone(), constant);
+        }
+    }
+
+    // isConst reports whether the innermost declaration of name the parser has seen is a const.
+    // names declared elsewhere, e.g. on an earlier line of a repl session, are checked when the
+    // program runs.
+    fn isConst(&self, name: &str) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.names.get(name))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    fn enterScope(&mut self, names: &[&ast::Identifier]) {
+        let mut scope = Scope::default();
+        for name in names.iter() {
+            scope.names.insert(name.Value.clone(), false);
+        }
+        self.scopes.push(scope);
     }
 
     fn curTokenIs(&self, t: token::TokenType) -> bool {
@@ -110,28 +328,43 @@ impl Parser {
     fn expectPeek(&mut self, t: token::TokenType) -> bool {
         if self.peekTokenIs(t) {
             self.nextToken();
-            return true;
+            true
         } else {
             self.peekError(t);
-            return false;
+            false
         }
     }
 
-    pub fn Errors(&self) -> &Vec<String> {
+    pub fn Errors(&self) -> &Vec<ParseError> {
         &self.errors
     }
 
     fn peekError(&mut self, t: token::TokenType) {
-        let msg = format!(
-            "expected next token to be {}, got {}, instead",
-            t, self.peekToken.Type
-        );
-        self.errors.push(msg);
+        self.errors.push(ParseError::UnexpectedToken {
+            Expected: t,
+            Got: self.peekToken.Type,
+            Span: self.peekToken.Span,
+        });
+    }
+
+    fn noPrefixParseFnError(&mut self) {
+        let err = if self.curTokenIs(token::ILLEGAL) {
+            ParseError::IllegalToken {
+                Literal: self.curToken.Literal.clone(),
+                Span: self.curToken.Span,
+            }
+        } else {
+            ParseError::NoPrefixParseFn {
+                Got: self.curToken.Type,
+                Span: self.curToken.Span,
+            }
+        };
+        self.errors.push(err);
     }
 
     pub fn checkParserErrors(&self) {
         let errors = self.Errors();
-        if errors.len() == 0 {
+        if errors.is_empty() {
             return;
         }
         println!("parser has {} errors", errors.len());
@@ -144,34 +377,173 @@ impl Parser {
     fn parseReturnStatement(&mut self) -> Option<ast::Statement> {
         let token_temp = self.curToken.clone();
         self.nextToken();
-        let stmt = ast::Statement::ReturnStatement {
+        let temp_value = self.parseExpression(LOWEST);
+
+        if self.peekTokenIs(token::SEMICOLON) {
+            self.nextToken();
+        }
+
+        Some(ast::Statement::ReturnStatement {
+            Span: self.spanFrom(token_temp.Span),
             Token: token_temp,
-            ReturnValue: self.parseExpression(LOWEST),
-        };
+            ReturnValue: temp_value,
+        })
+    }
+
+    fn parseWhileStatement(&mut self) -> Option<ast::Statement> {
+        let temp_token = self.curToken.clone();
+
+        if !self.expectPeek(token::LPAREN) {
+            return None;
+        }
+
+        self.nextToken();
+        let temp_condition = self.parseExpression(LOWEST);
+
+        if !self.expectPeek(token::RPAREN) {
+            return None;
+        }
+
+        if !self.expectPeek(token::LBRACE) {
+            return None;
+        }
+
+        // each pass runs the body in a scope of its own.
+        self.loopDepth += 1;
+        self.enterScope(&[]);
+        let temp_body = self.parseBlockStatement();
+        self.scopes.pop();
+        self.loopDepth -= 1;
 
         if self.peekTokenIs(token::SEMICOLON) {
             self.nextToken();
         }
 
-        Some(stmt)
+        Some(ast::Statement::WhileStatement {
+            Span: self.spanFrom(temp_token.Span),
+            Token: temp_token,
+            Condition: temp_condition,
+            Body: Box::new(temp_body),
+        })
+    }
+
+    fn parseForStatement(&mut self) -> Option<ast::Statement> {
+        let temp_token = self.curToken.clone();
+
+        if !self.expectPeek(token::LPAREN) {
+            return None;
+        }
+
+        let mut temp_variables = vec![];
+        loop {
+            if !self.expectPeek(token::IDENT) {
+                return None;
+            }
+            temp_variables.push(ast::Identifier {
+                Token: self.curToken.clone(),
+                Value: self.curToken.Literal.clone(),
+            });
+            if temp_variables.len() == 2 || !self.peekTokenIs(token::COMMA) {
+                break;
+            }
+            self.nextToken();
+        }
+
+        if !self.expectPeek(token::IN) {
+            return None;
+        }
+
+        self.nextToken();
+        let temp_iterable = self.parseExpression(LOWEST);
+
+        if !self.expectPeek(token::RPAREN) {
+            return None;
+        }
+
+        if !self.expectPeek(token::LBRACE) {
+            return None;
+        }
+
+        // each pass runs the body in a scope of its own.
+        self.loopDepth += 1;
+        self.enterScope(&temp_variables.iter().collect::<Vec<_>>());
+        let temp_body = self.parseBlockStatement();
+        self.scopes.pop();
+        self.loopDepth -= 1;
+
+        if self.peekTokenIs(token::SEMICOLON) {
+            self.nextToken();
+        }
+
+        Some(ast::Statement::ForStatement {
+            Span: self.spanFrom(temp_token.Span),
+            Token: temp_token,
+            Variables: temp_variables,
+            Iterable: temp_iterable,
+            Body: Box::new(temp_body),
+        })
+    }
+
+    fn parseLoopControlStatement(&mut self) -> Option<ast::Statement> {
+        let temp_token = self.curToken.clone();
+        if self.loopDepth == 0 && self.inExpression {
+            self.errors.push(ParseError::InsideExpression {
+                Keyword: temp_token.Literal.clone(),
+                Span: temp_token.Span,
+            });
+        } else if self.loopDepth == 0 {
+            self.errors.push(ParseError::OutsideLoop {
+                Keyword: temp_token.Literal.clone(),
+                Span: temp_token.Span,
+            });
+        }
+
+        if self.peekTokenIs(token::SEMICOLON) {
+            self.nextToken();
+        }
+
+        let span = self.spanFrom(temp_token.Span);
+        if temp_token.Type == token::BREAK {
+            Some(ast::Statement::BreakStatement {
+                Token: temp_token,
+                Span: span,
+            })
+        } else {
+            Some(ast::Statement::ContinueStatement {
+                Token: temp_token,
+                Span: span,
+            })
+        }
     }
 
     fn parseExpressionStatement(&mut self) -> Option<ast::Statement> {
+        self.statementIf = self.curTokenIs(token::IF);
         let res = self.parseExpression(LOWEST);
         if let ast::Expression::Nil = res {
             return None;
         }
 
-        let stmt = ast::Statement::ExpressionStatement {
-            Token: self.curToken.clone(),
-            Expression: res,
-        };
+        let temp_token = self.curToken.clone();
+
+        // only names can be assigned to. the value is still parsed so that it does not cause
+        // errors of its own.
+        if isAssignment(self.peekToken.Type) {
+            self.errors
+                .push(ParseError::InvalidAssignment { Span: res.Span() });
+            self.nextToken();
+            self.nextToken();
+            self.parseExpression(LOWEST);
+        }
 
         if self.peekTokenIs(token::SEMICOLON) {
             self.nextToken();
         }
 
-        Some(stmt)
+        Some(ast::Statement::ExpressionStatement {
+            Span: self.spanFrom(res.Span()),
+            Token: temp_token,
+            Expression: res,
+        })
     }
 
     fn parseExpression(&mut self, precedence: i32) -> ast::Expression {
@@ -181,16 +553,18 @@ impl Parser {
                 Value: self.curToken.Literal.clone(),
             }),
             token::INT => self.parseIntergerLiteral(),
+            token::STRING => self.parseStringLiteral(),
             token::BANG => self.parsePrefixExpression(),
             token::MINUS => self.parsePrefixExpression(),
             token::TRUE => self.parseBoolean(),
             token::FALSE => self.parseBoolean(),
             token::LPAREN => self.parseGroupedExpression(),
+            token::LBRACKET => self.parseArrayLiteral(),
+            token::LBRACE => self.parseHashLiteral(),
             token::IF => self.parseIfExpression(),
             token::FUNCTION => self.parseFunctionLiteral(),
             _ => {
-                let msg = format!("no prefix parse function for {} found", self.curToken.Type);
-                self.errors.push(msg);
+                self.noPrefixParseFnError();
                 ast::Expression::Nil
             }
         };
@@ -229,10 +603,38 @@ impl Parser {
                     self.nextToken();
                     left = self.parseInfixExpression(left);
                 }
+                token::PERCENT => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
+                token::LT_EQ => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
+                token::GT_EQ => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
+                token::AND => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
+                token::OR => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
+                token::DOTDOT => {
+                    self.nextToken();
+                    left = self.parseInfixExpression(left);
+                }
                 token::LPAREN => {
                     self.nextToken();
                     left = self.parseCallExpression(left);
                 }
+                token::LBRACKET => {
+                    self.nextToken();
+                    left = self.parseIndexExpression(left);
+                }
                 _ => {}
             }
         }
@@ -245,8 +647,10 @@ impl Parser {
 
         match value {
             Err(_) => {
-                let msg = format!("could not parse {} as integer", self.curToken.Literal);
-                self.errors.push(msg);
+                self.errors.push(ParseError::InvalidIntegerLiteral {
+                    Literal: self.curToken.Literal.clone(),
+                    Span: self.curToken.Span,
+                });
                 ast::Expression::Nil
             }
             Ok(v) => ast::Expression::IntergerLiteral {
@@ -256,31 +660,32 @@ impl Parser {
         }
     }
 
+    fn parseStringLiteral(&self) -> ast::Expression {
+        ast::Expression::StringLiteral {
+            Token: self.curToken.clone(),
+            Value: self.curToken.Literal.clone(),
+        }
+    }
+
     fn parsePrefixExpression(&mut self) -> ast::Expression {
         let token_temp = self.curToken.clone();
         let ope_temp = self.curToken.Literal.clone();
         self.nextToken();
+        let right = self.parseExpression(PREFIX);
         ast::Expression::PrefixExpression {
+            Span: self.spanFrom(token_temp.Span),
             Token: token_temp,
             Operator: ope_temp,
-            Right: Box::new(self.parseExpression(PREFIX)),
+            Right: Box::new(right),
         }
     }
 
     fn peekPrecedence(&self) -> i32 {
-        let p = precedences.get(self.peekToken.Type);
-        match p {
-            Some(x) => *x,
-            None => LOWEST,
-        }
+        Precedence(self.peekToken.Type)
     }
 
     fn curPrecedence(&self) -> i32 {
-        let p = precedences.get(self.curToken.Type);
-        match p {
-            Some(x) => *x,
-            None => LOWEST,
-        }
+        Precedence(self.curToken.Type)
     }
 
     fn parseInfixExpression(&mut self, left: ast::Expression) -> ast::Expression {
@@ -288,11 +693,13 @@ impl Parser {
         let ope_temp = self.curToken.Literal.clone();
         let precedence = self.curPrecedence();
         self.nextToken();
+        let right = self.parseExpression(precedence);
         ast::Expression::InfixExpression {
+            Span: self.spanFrom(left.Span()),
             Token: token_temp,
             Operator: ope_temp,
             Left: Box::new(left),
-            Right: Box::new(self.parseExpression(precedence)),
+            Right: Box::new(right),
         }
     }
 
@@ -316,6 +723,7 @@ impl Parser {
 
     fn parseIfExpression(&mut self) -> ast::Expression {
         let temp_token = self.curToken.clone();
+        let statement = std::mem::take(&mut self.statementIf);
 
         if !self.expectPeek(token::LPAREN) {
             return ast::Expression::Nil;
@@ -332,8 +740,19 @@ impl Parser {
             return ast::Expression::Nil;
         }
 
+        // the blocks of an if inside an expression can only leave loops of their own.
+        let (loopDepth, inExpression) = (self.loopDepth, self.inExpression);
+        if !statement {
+            self.inExpression = inExpression || loopDepth > 0;
+            self.loopDepth = 0;
+        }
+
+        // only one branch runs, so each starts from the names declared before the if.
+        let before = self.scopes.last().unwrap().names.clone();
         let temp_consequence = self.parseBlockStatement();
         let mut temp_alternative = ast::Statement::Nil;
+        let consequenceNames =
+            std::mem::replace(&mut self.scopes.last_mut().unwrap().names, before);
 
         if self.peekTokenIs(token::ELSE) {
             self.nextToken();
@@ -342,8 +761,17 @@ impl Parser {
             }
             temp_alternative = self.parseBlockStatement();
         }
+        self.loopDepth = loopDepth;
+        self.inExpression = inExpression;
+
+        // afterwards a name is a const if either branch may have made it one.
+        let names = &mut self.scopes.last_mut().unwrap().names;
+        for (name, constant) in consequenceNames {
+            *names.entry(name).or_insert(constant) |= constant;
+        }
 
         ast::Expression::IfExpression {
+            Span: self.spanFrom(temp_token.Span),
             Token: temp_token,
             Condition: Box::new(temp_condition),
             Consequence: Box::new(temp_consequence),
@@ -366,6 +794,7 @@ impl Parser {
         }
 
         ast::Statement::BlockStatement {
+            Span: self.spanFrom(temp_token.Span),
             Token: temp_token,
             Statements: temp_statements,
         }
@@ -387,10 +816,27 @@ impl Parser {
             return ast::Expression::Nil;
         }
 
+        // a function body starts outside of any loop, even if the function is defined in one.
+        let loopDepth = std::mem::take(&mut self.loopDepth);
+        let inExpression = std::mem::take(&mut self.inExpression);
+        let params: Vec<&ast::Identifier> = temp_parameters
+            .iter()
+            .filter_map(|p| match p {
+                ast::Expression::Identifier(ident) => Some(ident),
+                _ => None,
+            })
+            .collect();
+        self.enterScope(&params);
+        let temp_body = self.parseBlockStatement();
+        self.scopes.pop();
+        self.loopDepth = loopDepth;
+        self.inExpression = inExpression;
+
         ast::Expression::FunctionLiteral {
+            Span: self.spanFrom(temp_token.Span),
             Token: temp_token,
             Parameters: temp_parameters,
-            Body: Box::new(self.parseBlockStatement()),
+            Body: Box::new(temp_body),
         }
     }
 
@@ -428,8 +874,9 @@ impl Parser {
     }
 
     fn parseCallExpression(&mut self, function: ast::Expression) -> ast::Expression {
-        match self.parseCallArguments() {
+        match self.parseExpressionList(token::RPAREN) {
             Some(x) => ast::Expression::CallExpression {
+                Span: self.spanFrom(function.Span()),
                 Token: self.curToken.clone(),
                 Function: Box::new(function),
                 Arguments: x,
@@ -438,9 +885,72 @@ impl Parser {
         }
     }
 
-    fn parseCallArguments(&mut self) -> Option<Vec<ast::Expression>> {
+    fn parseArrayLiteral(&mut self) -> ast::Expression {
+        let temp_token = self.curToken.clone();
+        match self.parseExpressionList(token::RBRACKET) {
+            Some(x) => ast::Expression::ArrayLiteral {
+                Span: self.spanFrom(temp_token.Span),
+                Token: temp_token,
+                Elements: x,
+            },
+            None => ast::Expression::Nil,
+        }
+    }
+
+    // `{` only starts a block where the grammar expects one (after `if (...)`, `else` and
+    // `fn(...)`), so any `{` reaching expression position is a hash literal.
+    fn parseHashLiteral(&mut self) -> ast::Expression {
+        let temp_token = self.curToken.clone();
+        let mut pairs = vec![];
+
+        while !self.peekTokenIs(token::RBRACE) {
+            self.nextToken();
+            let key = self.parseExpression(LOWEST);
+
+            if !self.expectPeek(token::COLON) {
+                return ast::Expression::Nil;
+            }
+
+            self.nextToken();
+            let value = self.parseExpression(LOWEST);
+            pairs.push((key, value));
+
+            if !self.peekTokenIs(token::RBRACE) && !self.expectPeek(token::COMMA) {
+                return ast::Expression::Nil;
+            }
+        }
+
+        if !self.expectPeek(token::RBRACE) {
+            return ast::Expression::Nil;
+        }
+
+        ast::Expression::HashLiteral {
+            Span: self.spanFrom(temp_token.Span),
+            Token: temp_token,
+            Pairs: pairs,
+        }
+    }
+
+    fn parseIndexExpression(&mut self, left: ast::Expression) -> ast::Expression {
+        let temp_token = self.curToken.clone();
+        self.nextToken();
+        let index = self.parseExpression(LOWEST);
+
+        if !self.expectPeek(token::RBRACKET) {
+            return ast::Expression::Nil;
+        }
+
+        ast::Expression::IndexExpression {
+            Span: self.spanFrom(left.Span()),
+            Token: temp_token,
+            Left: Box::new(left),
+            Index: Box::new(index),
+        }
+    }
+
+    fn parseExpressionList(&mut self, end: token::TokenType) -> Option<Vec<ast::Expression>> {
         let mut args = vec![];
-        if self.peekTokenIs(token::RPAREN) {
+        if self.peekTokenIs(end) {
             self.nextToken();
             return Some(args);
         }
@@ -452,7 +962,7 @@ impl Parser {
             args.push(self.parseExpression(LOWEST));
         }
 
-        if !self.expectPeek(token::RPAREN) {
+        if !self.expectPeek(end) {
             return None;
         }
         Some(args)
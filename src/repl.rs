@@ -1,34 +1,334 @@
-use crate::{evaluator, lexer, object, repl, token};
+use crate::{
+    ast, compiler, diagnostics, editor, evaluator, highlight, lexer, object, parser, token,
+};
+use std::cell::RefCell;
+use std::fs;
 use std::io;
+use std::io::IsTerminal;
+use std::rc::Rc;
 
 const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+const FILENAME: &str = "<repl>";
 
-pub fn Start() {
-    println!(">> ");
-    let mut env = object::NewEnvironment();
+const HELP: &str = ":env              list the bindings in the environment
+:tokens <src>     print the tokens of src
+:ast <src>        print the syntax tree of src
+:type <expr>      evaluate expr and print the type of its value
+:bytecode <src>   print the compiled bytecode of src
+:reset            clear the environment and the history
+:load <file>      run a script in the current environment
+:save <file>      write every input evaluated so far to file
+:help             print this help";
+
+struct Session {
+    env: Rc<RefCell<object::Environment>>,
+    color: bool,
+    mode: diagnostics::Mode,
+    // every line entered so far. functions keep the spans of the line they were defined on, so
+    // errors are rendered against the whole session rather than just the current line.
+    source: String,
+    lineNo: usize,
+    // inputs that were evaluated without error, in order, for :save.
+    history: Vec<String>,
+}
+
+// Start runs the repl on stdin. color enables highlighting of input, results and errors; it is
+// ignored when stdout is not a terminal.
+pub fn Start(color: bool) {
+    let color = color && io::stdout().is_terminal();
+    let mut s = Session {
+        env: object::NewEnvironment(),
+        color,
+        mode: if color {
+            diagnostics::Mode::Color
+        } else {
+            diagnostics::Mode::Plain
+        },
+        source: String::new(),
+        lineNo: 1,
+        history: vec![],
+    };
+    let mut reader = editor::New(s.env.clone(), color);
+    // lines of a statement that is still being typed.
+    let mut input = String::new();
     loop {
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).expect("Failing in input");
-        let l = lexer::New(input);
+        let prompt = if input.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        let line = match reader.ReadLine(prompt) {
+            editor::Line::Text(line) => line,
+            editor::Line::Interrupted => {
+                input.clear();
+                continue;
+            }
+            editor::Line::Eof => {
+                // ctrl-d. whatever was left unfinished is evaluated so its error is shown.
+                println!();
+                if !input.trim().is_empty() {
+                    s.eval(&std::mem::take(&mut input), true);
+                }
+                reader.SaveHistory();
+                return;
+            }
+        };
+        if input.is_empty() {
+            if line.trim_start().starts_with(':') {
+                s.skip(&line);
+                s.command(line.trim());
+                reader.SetEnv(s.env.clone());
+                continue;
+            }
+            if line.trim().is_empty() {
+                s.skip(&line);
+                continue;
+            }
+        }
+        input += &line;
+        if IsComplete(&input) {
+            s.eval(&std::mem::take(&mut input), true);
+        }
+    }
+}
+
+impl Session {
+    // skip adds text to the session source without evaluating it, keeping line numbers in
+    // step with what was typed.
+    fn skip(&mut self, text: &str) {
+        self.source += text;
+        self.lineNo += text.matches('\n').count();
+    }
+
+    // parse lexes input as the next lines of the session.
+    fn parse(&mut self, input: &str) -> Option<ast::Program> {
+        let l = lexer::NewAt(input.to_string(), self.source.len(), self.lineNo);
+        self.skip(input);
         let mut p = l.New();
         let program = p.ParseProgram();
-        if p.Errors().len() != 0 {
-            printParserErrors(p.Errors());
-            continue;
+        if !p.Errors().is_empty() {
+            printParserErrors(&self.source, p.Errors(), self.mode);
+            return None;
         }
-        let evaluated = evaluator::Eval(program, &mut env);
+        Some(program)
+    }
+
+    // eval runs input in the session environment. the value is printed when echo is set;
+    // errors are always printed.
+    fn eval(&mut self, input: &str, echo: bool) {
+        let program = match self.parse(input) {
+            Some(program) => program,
+            None => return,
+        };
+        let evaluated = evaluator::Eval(program, &self.env);
+        if let Some(d) = diagnostics::FromRuntimeError(&evaluated, &self.env.borrow()) {
+            print!("{}", d.Render(&self.source, FILENAME, self.mode));
+            return;
+        }
+        self.history.push(input.trim_end().to_string());
+        if !echo {
+            return;
+        }
+        // statements like loops and calls to puts have no value to show.
         if let object::Object::Null = evaluated {
-            println!("semantics error");
+            return;
+        }
+        if self.color {
+            println!("{}", highlight::Value(&evaluated));
         } else {
             println!("{}", evaluated.Inspect());
         }
     }
+
+    fn command(&mut self, line: &str) {
+        let (name, arg) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+        match (name, arg) {
+            (":env", "") => self.printEnv(),
+            (":tokens", src) => print!("{}", lexer::Dump(src)),
+            (":ast", src) => {
+                let mut p = lexer::New(src.to_string()).New();
+                let program = p.ParseProgram();
+                if p.Errors().is_empty() {
+                    print!("{}", program.Dump());
+                } else {
+                    printParserErrors(src, p.Errors(), self.mode);
+                }
+            }
+            (":type", src) if !src.is_empty() => self.printType(src),
+            (":bytecode", src) => printBytecode(src, &self.env.borrow(), self.mode),
+            (":reset", "") => {
+                self.env = object::NewEnvironment();
+                self.history.clear();
+                println!("environment cleared");
+            }
+            (":load", path) if !path.is_empty() => match fs::read_to_string(path) {
+                Ok(src) => {
+                    let src = if src.ends_with('\n') { src } else { src + "\n" };
+                    self.eval(&src, false);
+                }
+                Err(e) => println!("error: {}: {}", path, e),
+            },
+            (":save", path) if !path.is_empty() => {
+                let mut text = self.history.join("\n");
+                text.push('\n');
+                match fs::write(path, text) {
+                    Ok(()) => println!("saved {} inputs to {}", self.history.len(), path),
+                    Err(e) => println!("error: {}: {}", path, e),
+                }
+            }
+            (":help", "") => println!("{}", HELP),
+            (":type", _) | (":load", _) | (":save", _) => {
+                println!("error: {} needs an argument. try :help", name)
+            }
+            _ => println!("error: unknown command {}. try :help", line),
+        }
+    }
+
+    fn printEnv(&self) {
+        let env = self.env.borrow();
+        for name in env.Names() {
+            let val = env.Get(&name).unwrap();
+            println!("{}: {} = {}", name, val.Type(), summary(&val));
+        }
+    }
+
+    // printType evaluates src in a scope enclosed by the session, so a let in src does not
+    // leak into the environment.
+    fn printType(&self, src: &str) {
+        let mut p = lexer::New(src.to_string()).New();
+        let program = p.ParseProgram();
+        if !p.Errors().is_empty() {
+            printParserErrors(src, p.Errors(), self.mode);
+            return;
+        }
+        let env = object::NewEnclosedEnvironment(self.env.clone());
+        let evaluated = evaluator::Eval(program, &env);
+        let err = diagnostics::FromRuntimeError(&evaluated, &env.borrow());
+        match err {
+            Some(d) => print!("{}", d.Render(src, FILENAME, self.mode)),
+            None => println!("{}", evaluated.Type()),
+        }
+    }
+}
+
+// summary is Inspect on one line: a function shows its parameters instead of its body.
+fn summary(obj: &object::Object) -> String {
+    match obj {
+        object::Object::Function { Parameters, .. } => {
+            let params: Vec<String> = Parameters.iter().map(|p| p.into_string()).collect();
+            format!("fn({})", params.join(", "))
+        }
+        _ => obj.Inspect(),
+    }
+}
+
+// IsComplete reports whether src can be parsed as it is, or whether the repl should keep
+// reading lines: a bracket is still open, a string or block comment is unterminated or the
+// last token is an operator or keyword that needs something after it.
+pub fn IsComplete(src: &str) -> bool {
+    let mut l = lexer::New(src.to_string());
+    let mut depth = 0;
+    let mut last = token::EOF;
+    loop {
+        let tok = l.NextToken();
+        match tok.Type {
+            token::EOF => break,
+            token::LPAREN | token::LBRACE | token::LBRACKET => depth += 1,
+            token::RPAREN | token::RBRACE | token::RBRACKET => depth -= 1,
+            token::ILLEGAL if tok.Literal.starts_with('"') && !isClosedString(&tok.Literal) => {
+                return false
+            }
+            // only an unclosed block comment is ILLEGAL.
+            token::ILLEGAL if tok.Literal.starts_with("/*") => return false,
+            _ => {}
+        }
+        last = tok.Type;
+    }
+    // more closing than opening brackets can never be fixed by reading more.
+    if depth != 0 {
+        return depth < 0;
+    }
+    !matches!(
+        last,
+        token::ASSIGN
+            | token::PLUS_ASSIGN
+            | token::MINUS_ASSIGN
+            | token::ASTERISK_ASSIGN
+            | token::SLASH_ASSIGN
+            | token::PLUS
+            | token::MINUS
+            | token::BANG
+            | token::ASTERISK
+            | token::SLASH
+            | token::PERCENT
+            | token::COMMA
+            | token::COLON
+            | token::LT
+            | token::GT
+            | token::LT_EQ
+            | token::GT_EQ
+            | token::EQ
+            | token::NOT_EQ
+            | token::AND
+            | token::OR
+            | token::DOTDOT
+            | token::FUNCTION
+            | token::LET
+            | token::CONST
+            | token::IF
+            | token::ELSE
+            | token::RETURN
+            | token::WHILE
+            | token::FOR
+            | token::IN
+    )
+}
+
+// isClosedString reports whether the raw text of a string literal has its closing quote. an
+// ILLEGAL string token is either unterminated or contains an invalid escape.
+fn isClosedString(raw: &str) -> bool {
+    let mut chars = raw.chars().skip(1);
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return true,
+            '\\' => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    false
 }
 
-fn printParserErrors(errors: &Vec<String>) {
-    println!("Error!");
-    println!(" parser errors: ");
-    for msg in errors.iter() {
-        println!("{}", msg);
+fn printParserErrors(source: &str, errors: &[parser::ParseError], mode: diagnostics::Mode) {
+    for err in errors.iter() {
+        let d = diagnostics::FromParseError(err, source);
+        print!("{}", d.Render(source, FILENAME, mode));
+    }
+}
+
+// printBytecode compiles src and prints its disassembly. names bound earlier in the session
+// are treated as globals so expressions using them can be compiled.
+fn printBytecode(src: &str, env: &object::Environment, mode: diagnostics::Mode) {
+    let l = lexer::New(src.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    if !p.Errors().is_empty() {
+        printParserErrors(src, p.Errors(), mode);
+        return;
+    }
+
+    let (mut symbols, constants) = compiler::New().State();
+    for name in env.Names() {
+        symbols.Define(&name);
+    }
+    let mut comp = compiler::NewWithState(symbols, constants);
+    match comp.Compile(&program) {
+        Ok(()) => print!("{}", comp.Bytecode().Disassemble()),
+        Err(err) => println!("compile error: {}", err),
     }
 }
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+pub type SymbolScope = &'static str;
+
+pub const GlobalScope: SymbolScope = "GLOBAL";
+pub const LocalScope: SymbolScope = "LOCAL";
+pub const BuiltinScope: SymbolScope = "BUILTIN";
+pub const FreeScope: SymbolScope = "FREE";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Symbol {
+    pub Name: String,
+    pub Scope: SymbolScope,
+    pub Index: usize,
+}
+
+// SymbolTable maps names to the slots the compiler assigned them. each function body gets its
+// own table enclosing the table of the surrounding scope.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    pub Outer: Option<Box<SymbolTable>>,
+    // variables of enclosing functions referenced from this scope, in the order they are
+    // captured by OpClosure.
+    pub FreeSymbols: Vec<Symbol>,
+    store: HashMap<String, Symbol>,
+    // the names defined in this scope by a const statement.
+    consts: HashSet<String>,
+    numDefinitions: usize,
+    // the locals of the main program, which only blocks at the top level have.
+    numMainLocals: usize,
+    // a block scope has names of its own but keeps its variables in the slots of the
+    // function (or the main program) it is part of.
+    block: bool,
+}
+
+pub fn NewSymbolTable() -> SymbolTable {
+    SymbolTable::default()
+}
+
+pub fn NewEnclosedSymbolTable(outer: SymbolTable) -> SymbolTable {
+    SymbolTable {
+        Outer: Some(Box::new(outer)),
+        ..SymbolTable::default()
+    }
+}
+
+// NewBlockSymbolTable returns a scope for the body of a loop inside outer. names defined in it
+// shadow outer ones until the block ends.
+pub fn NewBlockSymbolTable(outer: SymbolTable) -> SymbolTable {
+    SymbolTable {
+        Outer: Some(Box::new(outer)),
+        block: true,
+        ..SymbolTable::default()
+    }
+}
+
+impl SymbolTable {
+    // Define binds name in this scope. redefining a name reuses its slot, so closures that
+    // look the name up later see the new value.
+    pub fn Define(&mut self, name: &str) -> Symbol {
+        self.consts.remove(name);
+        if let Some(s) = self.store.get(name) {
+            if self.block || s.Scope == self.slotScope() {
+                return s.clone();
+            }
+        }
+        let symbol = self.allocate(name, false);
+        self.store.insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    // DefineConst is Define for a name that may not be assigned to or declared again.
+    pub fn DefineConst(&mut self, name: &str) -> Symbol {
+        let symbol = self.Define(name);
+        self.consts.insert(name.to_string());
+        symbol
+    }
+
+    // DefinesConst reports whether name is a const of this scope itself.
+    pub fn DefinesConst(&self, name: &str) -> bool {
+        self.consts.contains(name)
+    }
+
+    // Consts and SetConsts let the compiler check each branch of an if against the consts
+    // declared before it, as only one of them runs.
+    pub fn Consts(&self) -> HashSet<String> {
+        self.consts.clone()
+    }
+
+    pub fn SetConsts(&mut self, consts: HashSet<String>) {
+        self.consts = consts;
+    }
+
+    // IsConst reports whether the binding name resolves to is a const.
+    pub fn IsConst(&self, name: &str) -> bool {
+        match self.store.get(name) {
+            // free variables stand for a binding further out.
+            Some(s) if s.Scope != FreeScope => self.consts.contains(name),
+            _ => self.Outer.as_ref().is_some_and(|o| o.IsConst(name)),
+        }
+    }
+
+    // slotScope is where the variables defined in this table, other than a block, are stored.
+    fn slotScope(&self) -> SymbolScope {
+        match &self.Outer {
+            None => GlobalScope,
+            Some(_) => LocalScope,
+        }
+    }
+
+    // allocate returns a new slot in the function or main program this table belongs to.
+    // blocks at the top level get locals rather than globals, so that each pass of a loop can
+    // have variables of its own.
+    fn allocate(&mut self, name: &str, inBlock: bool) -> Symbol {
+        if self.block {
+            return self.Outer.as_mut().unwrap().allocate(name, true);
+        }
+        let (scope, counter) = if inBlock && self.Outer.is_none() {
+            (LocalScope, &mut self.numMainLocals)
+        } else {
+            (self.slotScope(), &mut self.numDefinitions)
+        };
+        let symbol = Symbol {
+            Name: name.to_string(),
+            Scope: scope,
+            Index: *counter,
+        };
+        *counter += 1;
+        symbol
+    }
+
+    pub fn DefineBuiltin(&mut self, index: usize, name: &str) -> Symbol {
+        let symbol = Symbol {
+            Name: name.to_string(),
+            Scope: BuiltinScope,
+            Index: index,
+        };
+        self.store.insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    fn defineFree(&mut self, original: Symbol) -> Symbol {
+        let symbol = Symbol {
+            Name: original.Name.clone(),
+            Scope: FreeScope,
+            Index: self.FreeSymbols.len(),
+        };
+        self.FreeSymbols.push(original);
+        self.store.insert(symbol.Name.clone(), symbol.clone());
+        symbol
+    }
+
+    // Resolve looks name up in this scope and then in the enclosing ones. locals of an
+    // enclosing function are turned into free variables of this one.
+    pub fn Resolve(&mut self, name: &str) -> Option<Symbol> {
+        if let Some(s) = self.store.get(name) {
+            return Some(s.clone());
+        }
+        let symbol = self.Outer.as_mut()?.Resolve(name)?;
+        // a block is part of the same function as its outer scope.
+        if self.block || symbol.Scope == GlobalScope || symbol.Scope == BuiltinScope {
+            return Some(symbol);
+        }
+        Some(self.defineFree(symbol))
+    }
+
+    // NextLocal is the slot the next variable defined in a block of this scope will get.
+    pub fn NextLocal(&self) -> usize {
+        match &self.Outer {
+            Some(outer) if self.block => outer.NextLocal(),
+            None => self.numMainLocals,
+            Some(_) => self.numDefinitions,
+        }
+    }
+
+    pub fn NumDefinitions(&self) -> usize {
+        self.numDefinitions
+    }
+
+    // NumMainLocals is how many locals the main program needs for the blocks at its top level.
+    pub fn NumMainLocals(&self) -> usize {
+        self.numMainLocals
+    }
+}
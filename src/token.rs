@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use phf::phf_map;
 pub type TokenType = &'static str;
 use std::fmt;
 
@@ -7,39 +7,103 @@ pub const EOF: TokenType = "EOF";
 
 pub const IDENT: TokenType = "IDENT";
 pub const INT: TokenType = "INT";
+pub const STRING: TokenType = "STRING";
 
 pub const ASSIGN: TokenType = "=";
+pub const PLUS_ASSIGN: TokenType = "+=";
+pub const MINUS_ASSIGN: TokenType = "-=";
+pub const ASTERISK_ASSIGN: TokenType = "*=";
+pub const SLASH_ASSIGN: TokenType = "/=";
 pub const PLUS: TokenType = "+";
 pub const MINUS: TokenType = "-";
 pub const BANG: TokenType = "!";
 pub const ASTERISK: TokenType = "*";
 pub const SLASH: TokenType = "/";
+pub const PERCENT: TokenType = "%";
 pub const COMMA: TokenType = ",";
 pub const SEMICOLON: TokenType = ";";
+pub const COLON: TokenType = ":";
+pub const DOTDOT: TokenType = "..";
 
 pub const LPAREN: TokenType = "(";
 pub const RPAREN: TokenType = ")";
 pub const LBRACE: TokenType = "{";
 pub const RBRACE: TokenType = "}";
+pub const LBRACKET: TokenType = "[";
+pub const RBRACKET: TokenType = "]";
 
 pub const LT: TokenType = "<";
 pub const GT: TokenType = ">";
+pub const LT_EQ: TokenType = "<=";
+pub const GT_EQ: TokenType = ">=";
 
 pub const EQ: TokenType = "==";
 pub const NOT_EQ: TokenType = "!=";
 
+pub const AND: TokenType = "&&";
+pub const OR: TokenType = "||";
+
 pub const FUNCTION: TokenType = "FUNCTION";
 pub const LET: TokenType = "LET";
+pub const CONST: TokenType = "CONST";
 pub const TRUE: TokenType = "TRUE";
 pub const FALSE: TokenType = "FALSE";
 pub const IF: TokenType = "IF";
 pub const ELSE: TokenType = "ELSE";
 pub const RETURN: TokenType = "RETURN";
+pub const WHILE: TokenType = "WHILE";
+pub const BREAK: TokenType = "BREAK";
+pub const CONTINUE: TokenType = "CONTINUE";
+pub const FOR: TokenType = "FOR";
+pub const IN: TokenType = "IN";
+
+// Span is a half-open range of byte offsets into the source text. Line and Column
+// (both 1-based, column counted in chars) locate Start.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    pub Start: usize,
+    pub End: usize,
+    pub Line: usize,
+    pub Column: usize,
+}
+
+impl Span {
+    // To returns a span running from the start of self to the end of other.
+    pub fn To(&self, other: Span) -> Span {
+        Span {
+            End: other.End.max(self.Start),
+            ..*self
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.Line, self.Column)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+}
+
+// Trivia is source text between tokens that does not change the program: whitespace and
+// comments. only a lossless lexer keeps it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Trivia {
+    pub Kind: TriviaKind,
+    pub Text: String,
+    pub Span: Span,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Token {
     pub Type: TokenType,
     pub Literal: String,
+    pub Span: Span,
 }
 
 impl fmt::Display for Token {
@@ -48,22 +112,32 @@ impl fmt::Display for Token {
     }
 }
 
-pub fn LookupIdent(ident: &String) -> TokenType {
-    let mut keywords = HashMap::new();
-    keywords.insert("fn", FUNCTION);
-    keywords.insert("let", LET);
-    keywords.insert("true", TRUE);
-    keywords.insert("false", FALSE);
-    keywords.insert("if", IF);
-    keywords.insert("else", ELSE);
-    keywords.insert("return", RETURN);
-
-    let tok: TokenType;
+static keywords: phf::Map<&'static str, TokenType> = phf_map! {
+    "fn" => FUNCTION,
+    "let" => LET,
+    "const" => CONST,
+    "true" => TRUE,
+    "false" => FALSE,
+    "if" => IF,
+    "else" => ELSE,
+    "return" => RETURN,
+    "while" => WHILE,
+    "break" => BREAK,
+    "continue" => CONTINUE,
+    "for" => FOR,
+    "in" => IN,
+};
 
-    if keywords.contains_key(ident.as_str()) {
-        let tok = keywords.get(ident.as_str()).unwrap();
+pub fn LookupIdent(ident: &str) -> TokenType {
+    if let Some(tok) = keywords.get(ident) {
         return tok;
     }
 
     IDENT
 }
+
+pub fn Keywords() -> Vec<&'static str> {
+    let mut res: Vec<&'static str> = keywords.keys().copied().collect();
+    res.sort_unstable();
+    res
+}
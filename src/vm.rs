@@ -0,0 +1,565 @@
+use crate::{builtins, code, compiler, iterator, object};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+pub const StackSize: usize = 2048;
+pub const MaxFrames: usize = 1024;
+
+const TRUE: object::Object = object::Object::Boolean { Value: true };
+const FALSE: object::Object = object::Object::Boolean { Value: false };
+const NULL: object::Object = object::Object::Null;
+
+// Frame is the activation of one closure: where it is in its instructions and where its
+// locals start on the stack.
+struct Frame {
+    Fn: Rc<object::CompiledFunction>,
+    Free: Rc<Vec<Rc<RefCell<object::Object>>>>,
+    ip: usize,
+    basePointer: usize,
+}
+
+pub struct VM {
+    constants: Vec<object::Object>,
+    builtins: Vec<object::Object>,
+    globals: Vec<object::Object>,
+
+    stack: Vec<object::Object>,
+    // always points to the next free slot. the top of the stack is stack[sp-1].
+    sp: usize,
+    // the cell a local slot has been moved into once a closure captured it.
+    cells: Vec<Option<Rc<RefCell<object::Object>>>>,
+    // cells collected by OpCaptureLocal and OpCaptureFree for the next OpClosure.
+    captured: Vec<Rc<RefCell<object::Object>>>,
+
+    frames: Vec<Frame>,
+}
+
+pub fn New(bytecode: compiler::Bytecode) -> VM {
+    NewWithGlobalsStore(bytecode, vec![])
+}
+
+// NewWithGlobalsStore runs bytecode against the globals of an earlier run, e.g. the previous
+// line of a REPL session.
+pub fn NewWithGlobalsStore(bytecode: compiler::Bytecode, globals: Vec<object::Object>) -> VM {
+    let mainFn = object::CompiledFunction {
+        Instructions: bytecode.Instructions,
+        NumLocals: bytecode.NumLocals,
+        ..Default::default()
+    };
+    let mainFrame = Frame {
+        Fn: Rc::new(mainFn),
+        Free: Rc::default(),
+        ip: 0,
+        basePointer: 0,
+    };
+    VM {
+        constants: bytecode.Constants,
+        builtins: builtins::Names()
+            .iter()
+            .filter_map(|name| builtins::Lookup(name))
+            .collect(),
+        globals,
+        stack: vec![NULL; StackSize],
+        sp: bytecode.NumLocals,
+        cells: vec![None; StackSize],
+        captured: vec![],
+        frames: vec![mainFrame],
+    }
+}
+
+impl VM {
+    // LastPoppedStackElem is the value of the last expression statement that was executed.
+    pub fn LastPoppedStackElem(&self) -> object::Object {
+        self.stack[self.sp].clone()
+    }
+
+    pub fn Globals(self) -> Vec<object::Object> {
+        self.globals
+    }
+
+    // Run executes the program until it finishes or fails. errors carry the same messages the
+    // evaluator produces for them.
+    pub fn Run(&mut self) -> Result<(), String> {
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            if frame.ip >= frame.Fn.Instructions.len() {
+                return Ok(());
+            }
+            let op = frame.Fn.Instructions[frame.ip];
+            frame.ip += 1;
+
+            match op {
+                code::OpConstant => {
+                    let idx = self.readUint16();
+                    self.push(self.constants[idx].clone())?;
+                }
+                code::OpPop => {
+                    self.pop();
+                }
+                code::OpAdd
+                | code::OpSub
+                | code::OpMul
+                | code::OpDiv
+                | code::OpMod
+                | code::OpEqual
+                | code::OpNotEqual
+                | code::OpGreaterThan
+                | code::OpLessThan
+                | code::OpGreaterThanOrEqual
+                | code::OpLessThanOrEqual
+                | code::OpRange => self.executeBinaryOperation(op)?,
+                code::OpTrue => self.push(TRUE)?,
+                code::OpFalse => self.push(FALSE)?,
+                code::OpNull => self.push(NULL)?,
+                code::OpBang => {
+                    let operand = self.pop();
+                    let result = match operand {
+                        object::Object::Boolean { Value } => nativeBoolToBooleanObject(!Value),
+                        // like the evaluator, prefix operators on null yield null.
+                        object::Object::Null => NULL,
+                        _ => FALSE,
+                    };
+                    self.push(result)?;
+                }
+                code::OpMinus => {
+                    let operand = self.pop();
+                    let result = match operand {
+                        object::Object::Integer { Value } => match Value.checked_neg() {
+                            Some(Value) => object::Object::Integer { Value },
+                            None => return Err(String::from("integer overflow")),
+                        },
+                        object::Object::Null => NULL,
+                        _ => return Err(format!("unknown operator: -{}", operand.Type())),
+                    };
+                    self.push(result)?;
+                }
+                code::OpJump => {
+                    let pos = self.readUint16();
+                    self.currentFrame().ip = pos;
+                }
+                code::OpJumpNotTruthy => {
+                    let pos = self.readUint16();
+                    let condition = self.pop();
+                    if !isTruthy(&condition) {
+                        self.currentFrame().ip = pos;
+                    }
+                }
+                code::OpSetGlobal => {
+                    let idx = self.readUint16();
+                    let val = self.pop();
+                    if idx >= self.globals.len() {
+                        self.globals.resize(idx + 1, NULL);
+                    }
+                    self.globals[idx] = val;
+                }
+                code::OpGetGlobal => {
+                    let idx = self.readUint16();
+                    let val = self.globals.get(idx).cloned().unwrap_or(NULL);
+                    self.push(val)?;
+                }
+                code::OpSetLocal => {
+                    let idx = self.readUint8();
+                    let slot = self.currentFrame().basePointer + idx;
+                    let val = self.pop();
+                    match &self.cells[slot] {
+                        Some(cell) => *cell.borrow_mut() = val,
+                        None => self.stack[slot] = val,
+                    }
+                }
+                code::OpGetLocal => {
+                    let idx = self.readUint8();
+                    let slot = self.currentFrame().basePointer + idx;
+                    let val = match &self.cells[slot] {
+                        Some(cell) => cell.borrow().clone(),
+                        None => self.stack[slot].clone(),
+                    };
+                    self.push(val)?;
+                }
+                code::OpGetBuiltin => {
+                    let idx = self.readUint8();
+                    self.push(self.builtins[idx].clone())?;
+                }
+                code::OpGetFree => {
+                    let idx = self.readUint8();
+                    let val = self.currentFrame().Free[idx].borrow().clone();
+                    self.push(val)?;
+                }
+                code::OpSetFree => {
+                    let idx = self.readUint8();
+                    let val = self.pop();
+                    *self.currentFrame().Free[idx].borrow_mut() = val;
+                }
+                code::OpCaptureLocal => {
+                    let idx = self.readUint8();
+                    let slot = self.currentFrame().basePointer + idx;
+                    let stack = &self.stack;
+                    let cell = self.cells[slot]
+                        .get_or_insert_with(|| Rc::new(RefCell::new(stack[slot].clone())));
+                    self.captured.push(Rc::clone(cell));
+                }
+                code::OpCaptureFree => {
+                    let idx = self.readUint8();
+                    let cell = Rc::clone(&self.currentFrame().Free[idx]);
+                    self.captured.push(cell);
+                }
+                code::OpFreshLocals => {
+                    let first = self.readUint8();
+                    let count = self.readUint8();
+                    let start = self.currentFrame().basePointer + first;
+                    for slot in start..start + count {
+                        self.cells[slot] = None;
+                        self.stack[slot] = NULL;
+                    }
+                }
+                code::OpArray => {
+                    let n = self.readUint16();
+                    let elements = self.stack[self.sp - n..self.sp].to_vec();
+                    self.sp -= n;
+                    self.push(object::Object::Array { Elements: elements })?;
+                }
+                code::OpHash => {
+                    let n = self.readUint16();
+                    let hash = self.buildHash(self.sp - n, self.sp)?;
+                    self.sp -= n;
+                    self.push(hash)?;
+                }
+                code::OpIndex => {
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.executeIndexExpression(left, index)?;
+                }
+                code::OpCall => {
+                    let numArgs = self.readUint8();
+                    self.executeCall(numArgs)?;
+                }
+                code::OpReturnValue => {
+                    let returnValue = self.pop();
+                    if self.frames.len() == 1 {
+                        // a return at the top level ends the program with its value.
+                        self.push(returnValue)?;
+                        self.pop();
+                        return Ok(());
+                    }
+                    let frame = self.frames.pop().unwrap();
+                    self.sp = frame.basePointer - 1;
+                    self.push(returnValue)?;
+                }
+                code::OpReturn => {
+                    let frame = self.frames.pop().unwrap();
+                    self.sp = frame.basePointer - 1;
+                    self.push(NULL)?;
+                }
+                code::OpClosure => {
+                    let idx = self.readUint16();
+                    let numFree = self.readUint8();
+                    self.pushClosure(idx, numFree)?;
+                }
+                code::OpIter => {
+                    let vars = self.readUint8();
+                    let obj = self.pop();
+                    self.push(iterator::Iter(&obj, vars == 2)?)?;
+                }
+                code::OpIterNext => {
+                    let end = self.readUint16();
+                    let vars = self.readUint8();
+                    let step = self.pop();
+                    match iterator::Step(step, vars)? {
+                        None => self.currentFrame().ip = end,
+                        // the first variable is assigned first, so it goes on top.
+                        Some((next, values)) => {
+                            self.push(next)?;
+                            for v in values.into_iter().rev() {
+                                self.push(v)?;
+                            }
+                        }
+                    }
+                }
+                _ => return Err(format!("unknown opcode: {}", op)),
+            }
+        }
+    }
+
+    fn currentFrame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn readUint16(&mut self) -> usize {
+        let frame = self.currentFrame();
+        let val = code::ReadUint16(&frame.Fn.Instructions[frame.ip..]) as usize;
+        frame.ip += 2;
+        val
+    }
+
+    fn readUint8(&mut self) -> usize {
+        let frame = self.currentFrame();
+        let val = code::ReadUint8(&frame.Fn.Instructions[frame.ip..]) as usize;
+        frame.ip += 1;
+        val
+    }
+
+    fn push(&mut self, obj: object::Object) -> Result<(), String> {
+        if self.sp >= StackSize {
+            return Err(String::from("stack overflow"));
+        }
+        self.stack[self.sp] = obj;
+        self.sp += 1;
+        Ok(())
+    }
+
+    // pop leaves the value in its slot so LastPoppedStackElem can still see it.
+    fn pop(&mut self) -> object::Object {
+        self.sp -= 1;
+        self.stack[self.sp].clone()
+    }
+
+    fn executeBinaryOperation(&mut self, op: code::Opcode) -> Result<(), String> {
+        let right = self.pop();
+        let left = self.pop();
+
+        let result = match (&left, &right) {
+            (object::Object::Integer { Value: l }, object::Object::Integer { Value: r }) => {
+                executeIntegerOperation(op, *l, *r)?
+            }
+            (object::Object::String { Value: l }, object::Object::String { Value: r }) => {
+                match op {
+                    code::OpAdd => object::Object::String {
+                        Value: l.clone() + r,
+                    },
+                    code::OpEqual => nativeBoolToBooleanObject(l == r),
+                    code::OpNotEqual => nativeBoolToBooleanObject(l != r),
+                    _ => return Err(unknownOperator(op, &left, &right)),
+                }
+            }
+            (object::Object::Array { Elements: l }, object::Object::Array { Elements: r })
+                if op == code::OpAdd =>
+            {
+                object::Object::Array {
+                    Elements: [l.as_slice(), r.as_slice()].concat(),
+                }
+            }
+            _ if op == code::OpEqual => nativeBoolToBooleanObject(left == right),
+            _ if op == code::OpNotEqual => nativeBoolToBooleanObject(left != right),
+            _ if left.Type() != right.Type() => {
+                return Err(format!(
+                    "type mismatch: {} {} {}",
+                    left.Type(),
+                    operatorString(op),
+                    right.Type()
+                ))
+            }
+            _ => return Err(unknownOperator(op, &left, &right)),
+        };
+        self.push(result)
+    }
+
+    fn executeIndexExpression(
+        &mut self,
+        left: object::Object,
+        index: object::Object,
+    ) -> Result<(), String> {
+        let result = match (&left, &index) {
+            (object::Object::Array { Elements }, object::Object::Integer { Value }) => {
+                if *Value < 0 {
+                    return Err(format!("negative array index: {}", Value));
+                }
+                Elements.get(*Value as usize).cloned().unwrap_or(NULL)
+            }
+            (object::Object::Hash { Pairs }, _) => {
+                let key = match index.HashKey() {
+                    Some(x) => x,
+                    None => return Err(format!("unusable as hash key: {}", index.Type())),
+                };
+                match Pairs.get(&key) {
+                    Some(pair) => pair.Value.clone(),
+                    None => NULL,
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "index operator not supported: {}[{}]",
+                    left.Type(),
+                    index.Type()
+                ))
+            }
+        };
+        self.push(result)
+    }
+
+    fn buildHash(&self, start: usize, end: usize) -> Result<object::Object, String> {
+        let mut pairs = BTreeMap::new();
+        for i in (start..end).step_by(2) {
+            let key = self.stack[i].clone();
+            let value = self.stack[i + 1].clone();
+            let hashKey = match key.HashKey() {
+                Some(x) => x,
+                None => return Err(format!("unusable as hash key: {}", key.Type())),
+            };
+            pairs.insert(
+                hashKey,
+                object::HashPair {
+                    Key: key,
+                    Value: value,
+                },
+            );
+        }
+        Ok(object::Object::Hash { Pairs: pairs })
+    }
+
+    fn executeCall(&mut self, numArgs: usize) -> Result<(), String> {
+        let callee = self.stack[self.sp - 1 - numArgs].clone();
+        match callee {
+            object::Object::Closure { Fn, Free } => self.callClosure(Fn, Free, numArgs),
+            object::Object::Builtin { Fn, .. } => {
+                let args = self.stack[self.sp - numArgs..self.sp].to_vec();
+                let result = Fn(args);
+                self.sp -= numArgs + 1;
+                if let object::Object::Error { Message, .. } = result {
+                    return Err(Message);
+                }
+                self.push(result)
+            }
+            object::Object::Host { Arity, Fn, .. } => {
+                if !Arity.Accepts(numArgs) {
+                    return Err(format!(
+                        "wrong number of arguments. got={}, want={}",
+                        numArgs, Arity
+                    ));
+                }
+                let args = self.stack[self.sp - numArgs..self.sp].to_vec();
+                let result = Fn(&args)?;
+                self.sp -= numArgs + 1;
+                self.push(result)
+            }
+            _ => Err(format!("not a function: {}", callee.Type())),
+        }
+    }
+
+    fn callClosure(
+        &mut self,
+        f: Rc<object::CompiledFunction>,
+        free: Rc<Vec<Rc<RefCell<object::Object>>>>,
+        numArgs: usize,
+    ) -> Result<(), String> {
+        if numArgs != f.NumParameters {
+            return Err(format!(
+                "wrong number of arguments. got={}, want={}",
+                numArgs, f.NumParameters
+            ));
+        }
+        if self.frames.len() >= MaxFrames {
+            return Err(String::from("stack overflow"));
+        }
+
+        let basePointer = self.sp - numArgs;
+        if basePointer + f.NumLocals >= StackSize {
+            return Err(String::from("stack overflow"));
+        }
+        // locals other than the arguments start out as null, not as whatever an earlier
+        // call left in their slots, and none of them is shared with a closure yet.
+        for slot in self.stack[basePointer + numArgs..basePointer + f.NumLocals].iter_mut() {
+            *slot = NULL;
+        }
+        for cell in self.cells[basePointer..basePointer + f.NumLocals].iter_mut() {
+            *cell = None;
+        }
+        self.sp = basePointer + f.NumLocals;
+        self.frames.push(Frame {
+            Fn: f,
+            Free: free,
+            ip: 0,
+            basePointer,
+        });
+        Ok(())
+    }
+
+    fn pushClosure(&mut self, constIndex: usize, numFree: usize) -> Result<(), String> {
+        let f = match &self.constants[constIndex] {
+            object::Object::CompiledFunction { Fn } => Rc::clone(Fn),
+            other => return Err(format!("not a function: {}", other.Type())),
+        };
+        if numFree > self.captured.len() {
+            return Err(format!("closure expects {} captured variables", numFree));
+        }
+        let free = self.captured.split_off(self.captured.len() - numFree);
+        self.push(object::Object::Closure {
+            Fn: f,
+            Free: Rc::new(free),
+        })
+    }
+}
+
+fn executeIntegerOperation(
+    op: code::Opcode,
+    left: i64,
+    right: i64,
+) -> Result<object::Object, String> {
+    // results that don't fit in an i64, like i64::MIN / -1, are an error rather than wrapping.
+    let checked = |value: Option<i64>| match value {
+        Some(Value) => Ok(object::Object::Integer { Value }),
+        None => Err(String::from("integer overflow")),
+    };
+    let result = match op {
+        code::OpAdd => checked(left.checked_add(right))?,
+        code::OpSub => checked(left.checked_sub(right))?,
+        code::OpMul => checked(left.checked_mul(right))?,
+        code::OpDiv | code::OpMod if right == 0 => return Err(String::from("division by zero")),
+        code::OpDiv => checked(left.checked_div(right))?,
+        code::OpMod => checked(left.checked_rem(right))?,
+        code::OpGreaterThan => nativeBoolToBooleanObject(left > right),
+        code::OpLessThan => nativeBoolToBooleanObject(left < right),
+        code::OpRange => object::Object::Range {
+            Start: left,
+            End: right,
+        },
+        code::OpGreaterThanOrEqual => nativeBoolToBooleanObject(left >= right),
+        code::OpLessThanOrEqual => nativeBoolToBooleanObject(left <= right),
+        code::OpEqual => nativeBoolToBooleanObject(left == right),
+        code::OpNotEqual => nativeBoolToBooleanObject(left != right),
+        _ => return Err(format!("unknown integer operator: {}", op)),
+    };
+    Ok(result)
+}
+
+fn operatorString(op: code::Opcode) -> &'static str {
+    match op {
+        code::OpAdd => "+",
+        code::OpSub => "-",
+        code::OpMul => "*",
+        code::OpDiv => "/",
+        code::OpEqual => "==",
+        code::OpNotEqual => "!=",
+        code::OpGreaterThan => ">",
+        code::OpLessThan => "<",
+        code::OpMod => "%",
+        code::OpGreaterThanOrEqual => ">=",
+        code::OpLessThanOrEqual => "<=",
+        code::OpRange => "..",
+        _ => "?",
+    }
+}
+
+fn unknownOperator(op: code::Opcode, left: &object::Object, right: &object::Object) -> String {
+    format!(
+        "unknown operator: {} {} {}",
+        left.Type(),
+        operatorString(op),
+        right.Type()
+    )
+}
+
+fn nativeBoolToBooleanObject(input: bool) -> object::Object {
+    if input {
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+fn isTruthy(obj: &object::Object) -> bool {
+    match *obj {
+        object::Object::Null => false,
+        object::Object::Boolean { Value } => Value,
+        _ => true,
+    }
+}
@@ -0,0 +1,241 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{artifact, code, compiler, lexer, object, vm};
+
+fn compile(input: &str) -> compiler::Bytecode {
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let mut comp = compiler::New();
+    comp.Compile(&program).unwrap();
+    comp.Bytecode()
+}
+
+const PROGRAM: &str = r#"
+let greeting = "hello";
+let adder = fn(a) { fn(b) { a + b } };
+let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+[greeting, adder(1)(2), fib(10)]
+"#;
+
+#[test]
+fn TestRoundTrip() {
+    let bytecode = compile(PROGRAM);
+    let data = artifact::Encode(&bytecode).unwrap();
+    assert_eq!(&data[..4], artifact::Magic);
+
+    let decoded = artifact::Decode(&data).unwrap();
+    assert_eq!(decoded, bytecode);
+
+    let mut machine = vm::New(decoded);
+    machine.Run().unwrap();
+    assert_eq!(
+        machine.LastPoppedStackElem().Inspect(),
+        r#"["hello", 3, 55]"#
+    );
+}
+
+#[test]
+fn TestSaveLoad() {
+    let path = std::env::temp_dir().join(format!("monkey-artifact-{}.mkc", std::process::id()));
+    let bytecode = compile(PROGRAM);
+    artifact::Save(&bytecode, &path).unwrap();
+    let loaded = artifact::Load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), bytecode);
+}
+
+#[test]
+fn TestVersionMismatch() {
+    let mut data = artifact::Encode(&compile("1 + 2")).unwrap();
+    let version = artifact::FormatVersion + 1;
+    data[4..6].copy_from_slice(&version.to_be_bytes());
+
+    let err = artifact::Decode(&data).unwrap_err();
+    assert!(matches!(
+        err,
+        artifact::Error::VersionMismatch { Found, Expected }
+            if Found == version && Expected == artifact::FormatVersion
+    ));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "compiled program has format version {}, expected version {}. recompile it",
+            version,
+            artifact::FormatVersion
+        )
+    );
+}
+
+#[test]
+fn TestRejectMalformed() {
+    let data = artifact::Encode(&compile(PROGRAM)).unwrap();
+
+    assert!(matches!(
+        artifact::Decode(b"MNK"),
+        Err(artifact::Error::BadMagic)
+    ));
+    assert!(matches!(
+        artifact::Decode(b"ELF\x7f\x00\x01"),
+        Err(artifact::Error::BadMagic)
+    ));
+    assert!(matches!(
+        artifact::Decode(&data[..data.len() - 3]),
+        Err(artifact::Error::Truncated)
+    ));
+
+    // a main program that loads a constant the pool does not have.
+    let bytecode = compiler::Bytecode {
+        Instructions: code::Make(code::OpConstant, &[7]),
+        Constants: vec![object::Object::Integer { Value: 1 }],
+        NumLocals: 0,
+        NumGlobals: 0,
+    };
+    let data = artifact::Encode(&bytecode).unwrap();
+    assert!(matches!(
+        artifact::Decode(&data),
+        Err(artifact::Error::InvalidInstructions(_))
+    ));
+}
+
+#[test]
+fn TestRejectCorrupted() {
+    let data = artifact::Encode(&compile(PROGRAM)).unwrap();
+    // every byte after the magic and the version is covered by the checksum.
+    for i in 6..data.len() {
+        for flip in [0x01, 0x80, 0xff] {
+            let mut corrupted = data.clone();
+            corrupted[i] ^= flip;
+            assert!(
+                artifact::Decode(&corrupted).is_err(),
+                "byte {} ^ {}",
+                i,
+                flip
+            );
+        }
+    }
+
+    let mut corrupted = data.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0x01;
+    let err = artifact::Decode(&corrupted).unwrap_err();
+    assert!(matches!(err, artifact::Error::ChecksumMismatch));
+    assert_eq!(
+        err.to_string(),
+        "compiled program is corrupted (checksum mismatch)"
+    );
+}
+
+#[test]
+fn TestRejectInvalidPrograms() {
+    struct tests_struct {
+        instructions: Vec<Vec<u8>>,
+        function: Vec<Vec<u8>>,
+    }
+
+    let tests = vec![
+        // a global the program does not have.
+        tests_struct {
+            instructions: vec![code::Make(code::OpGetGlobal, &[0])],
+            function: vec![],
+        },
+        // a jump into the operands of an instruction.
+        tests_struct {
+            instructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpJump, &[1]),
+            ],
+            function: vec![],
+        },
+        tests_struct {
+            instructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpIter, &[1]),
+                code::Make(code::OpIterNext, &[99, 1]),
+            ],
+            function: vec![],
+        },
+        // popping more values than were pushed.
+        tests_struct {
+            instructions: vec![code::Make(code::OpPop, &[])],
+            function: vec![],
+        },
+        tests_struct {
+            instructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpArray, &[2]),
+            ],
+            function: vec![],
+        },
+        tests_struct {
+            instructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpJumpNotTruthy, &[9]),
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpAdd, &[]),
+            ],
+            function: vec![],
+        },
+        tests_struct {
+            instructions: vec![code::Make(code::OpReturn, &[])],
+            function: vec![],
+        },
+        // free variables the closure was not given.
+        tests_struct {
+            instructions: vec![code::Make(code::OpClosure, &[1, 0])],
+            function: vec![
+                code::Make(code::OpGetFree, &[0]),
+                code::Make(code::OpReturnValue, &[]),
+            ],
+        },
+        tests_struct {
+            instructions: vec![
+                code::Make(code::OpClosure, &[1, 0]),
+                code::Make(code::OpCaptureLocal, &[0]),
+                code::Make(code::OpClosure, &[1, 1]),
+            ],
+            function: vec![code::Make(code::OpReturn, &[])],
+        },
+    ];
+
+    for tt in tests {
+        let bytecode = compiler::Bytecode {
+            Instructions: tt.instructions.concat(),
+            Constants: vec![
+                object::Object::Integer { Value: 1 },
+                object::Object::CompiledFunction {
+                    Fn: std::rc::Rc::new(object::CompiledFunction {
+                        Instructions: tt.function.concat(),
+                        NumLocals: 1,
+                        ..Default::default()
+                    }),
+                },
+            ],
+            NumLocals: 1,
+            NumGlobals: 0,
+        };
+        let data = artifact::Encode(&bytecode).unwrap();
+        let result = artifact::Decode(&data);
+        assert!(
+            matches!(result, Err(artifact::Error::InvalidInstructions(_))),
+            "{}: {:?}",
+            bytecode.Disassemble(),
+            result
+        );
+    }
+}
+
+#[test]
+fn TestUnsupportedConstant() {
+    let bytecode = compiler::Bytecode {
+        Instructions: vec![],
+        Constants: vec![object::Object::Boolean { Value: true }],
+        NumLocals: 0,
+        NumGlobals: 0,
+    };
+    assert!(matches!(
+        artifact::Encode(&bytecode),
+        Err(artifact::Error::UnsupportedConstant("BOOLEAN"))
+    ));
+}
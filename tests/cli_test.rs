@@ -0,0 +1,386 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::cli;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+struct output {
+    status: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn run(args: &[&str], stdin: &str) -> output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_monkey"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    output {
+        status: out.status.code().unwrap(),
+        stdout: String::from_utf8(out.stdout).unwrap(),
+        stderr: String::from_utf8(out.stderr).unwrap(),
+    }
+}
+
+#[test]
+fn TestRunSources() {
+    let path = std::env::temp_dir().join(format!("monkey-cli-{}.mk", std::process::id()));
+    std::fs::write(&path, "let x = 2;\nputs(x * 3);\nx").unwrap();
+    let script = path.to_str().unwrap().to_string();
+
+    struct tests_struct<'a> {
+        args: Vec<&'a str>,
+        stdin: &'a str,
+        expected: &'a str,
+    }
+
+    let tests = vec![
+        tests_struct {
+            args: vec![&script],
+            stdin: "",
+            expected: "6\n",
+        },
+        tests_struct {
+            args: vec!["-e", "1 + 2"],
+            stdin: "",
+            expected: "3\n",
+        },
+        tests_struct {
+            args: vec!["-e", r#""a" + "b""#],
+            stdin: "",
+            expected: "ab\n",
+        },
+        tests_struct {
+            args: vec!["-e", "puts(1)"],
+            stdin: "",
+            expected: "1\n",
+        },
+        tests_struct {
+            args: vec!["--vm", "-e", "[1, len(\"ab\")]"],
+            stdin: "",
+            expected: "[1, 2]\n",
+        },
+        tests_struct {
+            args: vec![],
+            stdin: "puts(\"piped\")",
+            expected: "piped\n",
+        },
+        tests_struct {
+            args: vec!["--no-color", "-e", "[1, \"a\"]"],
+            stdin: "",
+            expected: "[1, \"a\"]\n",
+        },
+        tests_struct {
+            args: vec!["-"],
+            stdin: "puts(10 / 2)",
+            expected: "5\n",
+        },
+    ];
+
+    for tt in tests.iter() {
+        let out = run(&tt.args, tt.stdin);
+        assert_eq!(out.status, cli::ExitOk, "{:?}: {}", tt.args, out.stderr);
+        assert_eq!(out.stdout, tt.expected, "{:?}", tt.args);
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn TestExitCodes() {
+    struct tests_struct<'a> {
+        args: Vec<&'a str>,
+        status: i32,
+        stderr: &'a str,
+    }
+
+    let tests = vec![
+        tests_struct {
+            args: vec!["-e", "let = 1"],
+            status: cli::ExitParseError,
+            stderr: " --> <expr>:1:5",
+        },
+        tests_struct {
+            args: vec!["-e", "x + 1"],
+            status: cli::ExitRuntimeError,
+            stderr: "error: identifier not found: x",
+        },
+        tests_struct {
+            args: vec!["--vm", "-e", "1 / 0"],
+            status: cli::ExitRuntimeError,
+            stderr: "error: division by zero",
+        },
+        tests_struct {
+            args: vec!["--check", "-e", "x + 1"],
+            status: cli::ExitOk,
+            stderr: "",
+        },
+        tests_struct {
+            args: vec!["--check", "-e", "fn(x {"],
+            status: cli::ExitParseError,
+            stderr: "error: ",
+        },
+        tests_struct {
+            args: vec!["does-not-exist.mk"],
+            status: cli::ExitNoInput,
+            stderr: "monkey: does-not-exist.mk: ",
+        },
+        tests_struct {
+            args: vec!["--bogus"],
+            status: cli::ExitUsage,
+            stderr: "monkey: unknown option --bogus",
+        },
+        tests_struct {
+            args: vec!["-e"],
+            status: cli::ExitUsage,
+            stderr: "monkey: -e requires an expression",
+        },
+    ];
+
+    for tt in tests.iter() {
+        let out = run(&tt.args, "");
+        assert_eq!(out.status, tt.status, "{:?}: {}", tt.args, out.stderr);
+        assert!(
+            out.stderr.contains(tt.stderr),
+            "{:?}: stderr {:?} does not contain {:?}",
+            tt.args,
+            out.stderr,
+            tt.stderr
+        );
+        assert_eq!(out.stdout, "", "{:?}", tt.args);
+    }
+}
+
+#[test]
+fn TestDumpTokens() {
+    let out = run(&["--dump-tokens", "-e", "let x = 5;"], "");
+    assert_eq!(out.status, cli::ExitOk);
+    assert_eq!(
+        out.stdout,
+        "1:1\tLET\t\"let\"
+1:5\tIDENT\t\"x\"
+1:7\t=\t\"=\"
+1:9\tINT\t\"5\"
+1:10\t;\t\";\"
+1:11\tEOF\t\"\"
+"
+    );
+}
+
+#[test]
+fn TestDumpAst() {
+    let out = run(&["--dump-ast", "-e", r#"let x = 1 + f(2, "a");"#], "");
+    assert_eq!(out.status, cli::ExitOk);
+    assert_eq!(
+        out.stdout,
+        r#"Program
+  LetStatement 1:1 x
+    InfixExpression 1:9 +
+      IntegerLiteral 1:9 1
+      CallExpression 1:13
+        Identifier 1:13 f
+        IntegerLiteral 1:15 2
+        StringLiteral 1:18 "a"
+"#
+    );
+}
+
+#[test]
+fn TestCompileAndRun() {
+    let dir = std::env::temp_dir();
+    let src = dir.join(format!("monkey-cli-compile-{}.mk", std::process::id()));
+    let out = src.with_extension("mkc");
+    std::fs::write(&src, "puts(fn(a) { a * 7 }(6))").unwrap();
+
+    let compiled = run(&["compile", src.to_str().unwrap()], "");
+    assert_eq!(compiled.status, cli::ExitOk, "{}", compiled.stderr);
+    let ran = run(&["run", out.to_str().unwrap()], "");
+    // compiled programs are also recognised when passed as a script.
+    let direct = run(&[out.to_str().unwrap()], "");
+
+    std::fs::remove_file(&src).unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert_eq!(ran.stdout, "42\n");
+    assert_eq!(direct.stdout, "42\n");
+}
+
+#[test]
+fn TestReplContinuation() {
+    let out = run(
+        &["--repl"],
+        "let add = fn(a, b) {\n  a + b\n};\nadd(1,\n 2)\n\"two\nlines\"\n[1,\n",
+    );
+    assert_eq!(out.status, cli::ExitOk);
+    // the unfinished array at end of input is still reported, then the repl exits.
+    assert!(out.stdout.contains(">> .. .. fn(a b) {"), "{}", out.stdout);
+    assert!(out.stdout.contains(">> .. 3\n"), "{}", out.stdout);
+    assert!(
+        out.stdout.contains(">> .. \"two\\nlines\"\n"),
+        "{}",
+        out.stdout
+    );
+    assert!(out.stdout.contains(">> .. \nerror: "), "{}", out.stdout);
+    assert!(
+        out.stdout.ends_with(" --> <repl>:9:1\n  |\n9 | \n  | ^\n"),
+        "{}",
+        out.stdout
+    );
+}
+
+#[test]
+fn TestReplNullResults() {
+    let out = run(
+        &["--repl"],
+        "puts(1)\nlet i = 0;\nwhile (i < 1) { i += 1; }\ni\n",
+    );
+    assert_eq!(out.status, cli::ExitOk);
+    assert!(
+        out.stdout.ends_with(">> 1\n>> 0\n>> >> 1\n>> \n"),
+        "{}",
+        out.stdout
+    );
+    assert!(!out.stdout.contains("error"), "{}", out.stdout);
+}
+
+#[test]
+fn TestReplCommands() {
+    let dir = std::env::temp_dir();
+    let script = dir.join(format!("monkey-repl-load-{}.mk", std::process::id()));
+    let saved = dir.join(format!("monkey-repl-save-{}.mk", std::process::id()));
+    std::fs::write(&script, "let y = x * 2;").unwrap();
+
+    let input = format!(
+        "let x = 5;
+let add = fn(a, b) {{ a + b }};
+:env
+:tokens x;
+:ast add(1)
+:type add
+:type let z = 1; z
+z
+:load {}
+y
+:save {}
+:reset
+:env
+:bogus
+:type
+",
+        script.display(),
+        saved.display()
+    );
+    let out = run(&["--repl"], &input);
+    let history = std::fs::read_to_string(&saved);
+    std::fs::remove_file(&script).unwrap();
+    std::fs::remove_file(&saved).unwrap();
+
+    let expected = format!(
+        "Hello! This is the Monkey programming language!
+Feel free to type in commands
+>> 5
+>> fn(a b) {{
+                    (a + b)
+                }}
+>> add: FUNCTION = fn(a, b)
+x: INTEGER = 5
+>> 1:1\tIDENT\t\"x\"
+1:2\t;\t\";\"
+1:3\tEOF\t\"\"
+>> Program
+  ExpressionStatement 1:1
+    CallExpression 1:1
+      Identifier 1:1 add
+      IntegerLiteral 1:5 1
+>> FUNCTION
+>> INTEGER
+>> error: identifier not found: z
+ --> <repl>:8:1
+  |
+8 | z
+  | ^
+>> >> 10
+>> saved 4 inputs to {}
+>> environment cleared
+>> >> error: unknown command :bogus. try :help
+>> error: :type needs an argument. try :help
+>> 
+",
+        saved.display()
+    );
+    assert_eq!(out.stdout, expected);
+    assert_eq!(
+        history.unwrap(),
+        "let x = 5;\nlet add = fn(a, b) { a + b };\nlet y = x * 2;\ny\n"
+    );
+}
+
+#[test]
+fn TestFmt() {
+    let dir = std::env::temp_dir();
+    let messy = dir.join(format!("monkey-fmt-messy-{}.mk", std::process::id()));
+    let tidy = dir.join(format!("monkey-fmt-tidy-{}.mk", std::process::id()));
+    std::fs::write(&messy, "let add=fn(a,b){a+b};\nputs(add(1,2))").unwrap();
+    std::fs::write(&tidy, "puts(1);\n").unwrap();
+    let (messyPath, tidyPath) = (messy.to_str().unwrap(), tidy.to_str().unwrap());
+
+    let out = run(&["fmt"], "let x=(1+2)*3");
+    assert_eq!(out.status, cli::ExitOk);
+    assert_eq!(out.stdout, "let x = (1 + 2) * 3;\n");
+
+    let out = run(&["fmt", "--check"], "let x = 1;\n");
+    assert_eq!((out.status, out.stdout.as_str()), (cli::ExitOk, ""));
+    let out = run(&["fmt", "--check"], "let x=1");
+    assert_eq!(
+        (out.status, out.stdout.as_str()),
+        (cli::ExitUnformatted, "<stdin>\n")
+    );
+    let out = run(&["fmt"], "let = 1");
+    assert_eq!(out.status, cli::ExitParseError);
+
+    let out = run(&["fmt", "--check", messyPath, tidyPath], "");
+    assert_eq!(out.status, cli::ExitUnformatted);
+    assert_eq!(out.stdout, format!("{}\n", messyPath));
+
+    let out = run(&["fmt", messyPath, tidyPath], "");
+    assert_eq!(out.status, cli::ExitOk, "{}", out.stderr);
+    let formatted = std::fs::read_to_string(&messy).unwrap();
+    std::fs::remove_file(&messy).unwrap();
+    std::fs::remove_file(&tidy).unwrap();
+    assert_eq!(
+        formatted,
+        "let add = fn(a, b) { a + b };\nputs(add(1, 2));\n"
+    );
+}
+
+// a reader that stops early, like `head -1`, ends the program quietly.
+#[test]
+fn TestClosedStdout() {
+    for args in [
+        vec!["-e", "for (i in 0..100000) { puts(i) }"],
+        vec!["--vm", "-e", "for (i in 0..100000) { puts(i) }"],
+        vec!["--dump-tokens", "-e", "for (i in 0..100000) { puts(i) }"],
+    ] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_monkey"))
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut first = [0u8; 1];
+        std::io::Read::read_exact(child.stdout.as_mut().unwrap(), &mut first).unwrap();
+        drop(child.stdout.take());
+        let out = child.wait_with_output().unwrap();
+        assert_eq!(out.status.code(), Some(cli::ExitOk), "{:?}", args);
+        assert_eq!(String::from_utf8(out.stderr).unwrap(), "", "{:?}", args);
+    }
+}
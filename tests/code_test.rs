@@ -0,0 +1,118 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{code, object};
+
+#[test]
+fn TestMake() {
+    struct tests_struct {
+        op: code::Opcode,
+        operands: Vec<usize>,
+        expected: Vec<u8>,
+    }
+
+    let tests = vec![
+        tests_struct {
+            op: code::OpConstant,
+            operands: vec![65534],
+            expected: vec![code::OpConstant, 255, 254],
+        },
+        tests_struct {
+            op: code::OpAdd,
+            operands: vec![],
+            expected: vec![code::OpAdd],
+        },
+        tests_struct {
+            op: code::OpGetLocal,
+            operands: vec![255],
+            expected: vec![code::OpGetLocal, 255],
+        },
+        tests_struct {
+            op: code::OpClosure,
+            operands: vec![65534, 255],
+            expected: vec![code::OpClosure, 255, 254, 255],
+        },
+    ];
+
+    for tt in tests.iter() {
+        let instruction = code::Make(tt.op, &tt.operands);
+        assert_eq!(instruction, tt.expected);
+    }
+}
+
+#[test]
+fn TestReadOperands() {
+    struct tests_struct {
+        op: code::Opcode,
+        operands: Vec<usize>,
+        bytesRead: usize,
+    }
+
+    let tests = vec![
+        tests_struct {
+            op: code::OpConstant,
+            operands: vec![65535],
+            bytesRead: 2,
+        },
+        tests_struct {
+            op: code::OpGetLocal,
+            operands: vec![255],
+            bytesRead: 1,
+        },
+        tests_struct {
+            op: code::OpClosure,
+            operands: vec![65535, 255],
+            bytesRead: 3,
+        },
+    ];
+
+    for tt in tests.iter() {
+        let instruction = code::Make(tt.op, &tt.operands);
+        let def = code::Lookup(tt.op).expect("definition not found");
+
+        let (operandsRead, n) = code::ReadOperands(&def, &instruction[1..]);
+        assert_eq!(n, tt.bytesRead);
+        assert_eq!(operandsRead, tt.operands);
+    }
+}
+
+#[test]
+fn TestLookupUnknownOpcode() {
+    assert_eq!(code::Lookup(255), None);
+    assert_eq!(code::Make(255, &[1]), Vec::<u8>::new());
+}
+
+#[test]
+fn TestDisassemble() {
+    let instructions = vec![
+        code::Make(code::OpAdd, &[]),
+        code::Make(code::OpGetLocal, &[1]),
+        code::Make(code::OpConstant, &[0]),
+        code::Make(code::OpJumpNotTruthy, &[12]),
+        code::Make(code::OpGetBuiltin, &[2]),
+        code::Make(code::OpClosure, &[1, 255]),
+        code::Make(code::OpIterNext, &[3, 1]),
+    ]
+    .concat();
+    let constants = vec![object::Object::Integer { Value: 42 }];
+
+    let expected = "0000 OpAdd
+0001 OpGetLocal 1
+0003 OpConstant 0           ; 42
+0006 OpJumpNotTruthy 12     ; -> 0012
+0009 OpGetBuiltin 2         ; len
+0011 OpClosure 1 255        ; <missing constant>
+0015 OpIterNext 3 1         ; -> 0003
+";
+    assert_eq!(code::Disassemble(&instructions, &constants), expected);
+}
+
+#[test]
+fn TestDisassembleMalformed() {
+    let instructions = vec![250, code::OpPop, code::OpConstant, 0];
+    let expected = "0000 ERROR: unknown opcode 250
+0001 OpPop
+0002 ERROR: truncated OpConstant
+";
+    assert_eq!(code::Disassemble(&instructions, &[]), expected);
+}
@@ -0,0 +1,723 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::symbol_table::{self, Symbol};
+use monkey_interpreter::{code, compiler, lexer, object};
+
+// expected constants are either plain objects or the instructions of a compiled function.
+enum constant {
+    Int(i64),
+    Str(&'static str),
+    Fn(Vec<Vec<u8>>),
+}
+
+struct compilerTestCase {
+    input: &'static str,
+    expectedConstants: Vec<constant>,
+    expectedInstructions: Vec<Vec<u8>>,
+}
+
+fn runCompilerTests(tests: Vec<compilerTestCase>) {
+    for tt in tests.iter() {
+        let l = lexer::New(tt.input.to_string());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+
+        let mut comp = compiler::New();
+        if let Err(err) = comp.Compile(&program) {
+            panic!("compiler error for {:?}: {}", tt.input, err);
+        }
+        let bytecode = comp.Bytecode();
+
+        assert_eq!(
+            bytecode.Instructions,
+            tt.expectedInstructions.concat(),
+            "wrong instructions for {:?}",
+            tt.input
+        );
+        assert_eq!(
+            bytecode.Constants.len(),
+            tt.expectedConstants.len(),
+            "wrong number of constants for {:?}",
+            tt.input
+        );
+        for (actual, expected) in bytecode.Constants.iter().zip(tt.expectedConstants.iter()) {
+            match (expected, actual) {
+                (constant::Int(v), object::Object::Integer { Value }) => assert_eq!(v, Value),
+                (constant::Str(v), object::Object::String { Value }) => assert_eq!(v, Value),
+                (constant::Fn(ins), object::Object::CompiledFunction { Fn }) => {
+                    assert_eq!(
+                        Fn.Instructions,
+                        ins.concat(),
+                        "wrong function for {:?}",
+                        tt.input
+                    )
+                }
+                _ => panic!("unexpected constant {} for {:?}", actual, tt.input),
+            }
+        }
+    }
+}
+
+#[test]
+fn TestIntegerArithmetic() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "1 + 2",
+            expectedConstants: vec![constant::Int(1), constant::Int(2)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpAdd, &[]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "1 < 2",
+            expectedConstants: vec![constant::Int(1), constant::Int(2)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpLessThan, &[]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "-1; !true",
+            expectedConstants: vec![constant::Int(1)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpMinus, &[]),
+                code::Make(code::OpPop, &[]),
+                code::Make(code::OpTrue, &[]),
+                code::Make(code::OpBang, &[]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestConditionals() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "if (true) { 10 }; 3333;",
+            expectedConstants: vec![constant::Int(10), constant::Int(3333)],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[10]),
+                // 0004
+                code::Make(code::OpConstant, &[0]),
+                // 0007
+                code::Make(code::OpJump, &[11]),
+                // 0010
+                code::Make(code::OpNull, &[]),
+                // 0011
+                code::Make(code::OpPop, &[]),
+                // 0012
+                code::Make(code::OpConstant, &[1]),
+                // 0015
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "if (true) { 10 } else { 20 }; 3333;",
+            expectedConstants: vec![constant::Int(10), constant::Int(20), constant::Int(3333)],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[10]),
+                // 0004
+                code::Make(code::OpConstant, &[0]),
+                // 0007
+                code::Make(code::OpJump, &[13]),
+                // 0010
+                code::Make(code::OpConstant, &[1]),
+                // 0013
+                code::Make(code::OpPop, &[]),
+                // 0014
+                code::Make(code::OpConstant, &[2]),
+                // 0017
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestLogicalOperators() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "true && false",
+            expectedConstants: vec![],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[12]),
+                // 0004
+                code::Make(code::OpFalse, &[]),
+                // 0005
+                code::Make(code::OpJumpNotTruthy, &[12]),
+                // 0008
+                code::Make(code::OpTrue, &[]),
+                // 0009
+                code::Make(code::OpJump, &[13]),
+                // 0012
+                code::Make(code::OpFalse, &[]),
+                // 0013
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "true || false",
+            expectedConstants: vec![],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[8]),
+                // 0004
+                code::Make(code::OpTrue, &[]),
+                // 0005
+                code::Make(code::OpJump, &[17]),
+                // 0008
+                code::Make(code::OpFalse, &[]),
+                // 0009
+                code::Make(code::OpJumpNotTruthy, &[16]),
+                // 0012
+                code::Make(code::OpTrue, &[]),
+                // 0013
+                code::Make(code::OpJump, &[17]),
+                // 0016
+                code::Make(code::OpFalse, &[]),
+                // 0017
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "1 % 2 >= 3 <= 4",
+            expectedConstants: vec![
+                constant::Int(1),
+                constant::Int(2),
+                constant::Int(3),
+                constant::Int(4),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpMod, &[]),
+                code::Make(code::OpConstant, &[2]),
+                code::Make(code::OpGreaterThanOrEqual, &[]),
+                code::Make(code::OpConstant, &[3]),
+                code::Make(code::OpLessThanOrEqual, &[]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestWhileLoops() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "while (true) { if (false) { break; }; continue; }; 1;",
+            expectedConstants: vec![constant::Int(1)],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[26]),
+                // 0004 the body has no variables to renew
+                code::Make(code::OpFreshLocals, &[0, 0]),
+                // 0007
+                code::Make(code::OpFalse, &[]),
+                // 0008
+                code::Make(code::OpJumpNotTruthy, &[18]),
+                // 0011 break
+                code::Make(code::OpJump, &[26]),
+                // 0014
+                code::Make(code::OpNull, &[]),
+                // 0015
+                code::Make(code::OpJump, &[19]),
+                // 0018
+                code::Make(code::OpNull, &[]),
+                // 0019
+                code::Make(code::OpPop, &[]),
+                // 0020 continue
+                code::Make(code::OpJump, &[0]),
+                // 0023
+                code::Make(code::OpJump, &[0]),
+                // 0026
+                code::Make(code::OpNull, &[]),
+                // 0027
+                code::Make(code::OpPop, &[]),
+                // 0028
+                code::Make(code::OpConstant, &[0]),
+                // 0031
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            // each pass gives y a new binding, in a local of the main program.
+            input: "while (true) { let y = 1; break; }",
+            expectedConstants: vec![constant::Int(1)],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpTrue, &[]),
+                // 0001
+                code::Make(code::OpJumpNotTruthy, &[18]),
+                // 0004
+                code::Make(code::OpFreshLocals, &[0, 1]),
+                // 0007
+                code::Make(code::OpConstant, &[0]),
+                // 0010
+                code::Make(code::OpSetLocal, &[0]),
+                // 0012 break
+                code::Make(code::OpJump, &[18]),
+                // 0015
+                code::Make(code::OpJump, &[0]),
+                // 0018
+                code::Make(code::OpNull, &[]),
+                // 0019
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestForLoops() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "for (x in [1]) { x }",
+            expectedConstants: vec![constant::Int(1)],
+            expectedInstructions: vec![
+                // 0000
+                code::Make(code::OpConstant, &[0]),
+                // 0003
+                code::Make(code::OpArray, &[1]),
+                // 0006
+                code::Make(code::OpIter, &[1]),
+                // 0008 the iterator, in a local of the main program
+                code::Make(code::OpSetLocal, &[0]),
+                // 0010
+                code::Make(code::OpGetLocal, &[0]),
+                // 0012
+                code::Make(code::OpCall, &[0]),
+                // 0014
+                code::Make(code::OpIterNext, &[31, 1]),
+                // 0018
+                code::Make(code::OpFreshLocals, &[1, 1]),
+                // 0021 x
+                code::Make(code::OpSetLocal, &[1]),
+                // 0023 the next iterator
+                code::Make(code::OpSetLocal, &[0]),
+                // 0025
+                code::Make(code::OpGetLocal, &[1]),
+                // 0027
+                code::Make(code::OpPop, &[]),
+                // 0028
+                code::Make(code::OpJump, &[10]),
+                // 0031
+                code::Make(code::OpNull, &[]),
+                // 0032
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            // each pass gives x and y new bindings.
+            input: "fn() { for (x in [1]) { let y = x; } }",
+            expectedConstants: vec![
+                constant::Int(1),
+                constant::Fn(vec![
+                    // 0000
+                    code::Make(code::OpConstant, &[0]),
+                    // 0003
+                    code::Make(code::OpArray, &[1]),
+                    // 0006
+                    code::Make(code::OpIter, &[1]),
+                    // 0008
+                    code::Make(code::OpSetLocal, &[0]),
+                    // 0010
+                    code::Make(code::OpGetLocal, &[0]),
+                    // 0012
+                    code::Make(code::OpCall, &[0]),
+                    // 0014
+                    code::Make(code::OpIterNext, &[32, 1]),
+                    // 0018
+                    code::Make(code::OpFreshLocals, &[1, 2]),
+                    // 0021
+                    code::Make(code::OpSetLocal, &[1]),
+                    // 0023
+                    code::Make(code::OpSetLocal, &[0]),
+                    // 0025
+                    code::Make(code::OpGetLocal, &[1]),
+                    // 0027
+                    code::Make(code::OpSetLocal, &[2]),
+                    // 0029
+                    code::Make(code::OpJump, &[10]),
+                    // 0032
+                    code::Make(code::OpNull, &[]),
+                    // 0033
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[1, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestAssignStatements() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "let x = 1; x = 2; x -= 3;",
+            expectedConstants: vec![constant::Int(1), constant::Int(2), constant::Int(3)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpSetGlobal, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpSetGlobal, &[0]),
+                code::Make(code::OpGetGlobal, &[0]),
+                code::Make(code::OpConstant, &[2]),
+                code::Make(code::OpSub, &[]),
+                code::Make(code::OpSetGlobal, &[0]),
+            ],
+        },
+        compilerTestCase {
+            input: "fn(a) { fn() { a *= 2 } }",
+            expectedConstants: vec![
+                constant::Int(2),
+                constant::Fn(vec![
+                    code::Make(code::OpGetFree, &[0]),
+                    code::Make(code::OpConstant, &[0]),
+                    code::Make(code::OpMul, &[]),
+                    code::Make(code::OpSetFree, &[0]),
+                    code::Make(code::OpReturn, &[]),
+                ]),
+                constant::Fn(vec![
+                    code::Make(code::OpCaptureLocal, &[0]),
+                    code::Make(code::OpClosure, &[1, 1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[2, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestGlobalLetStatements() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "let one = 1; let two = one; two;",
+            expectedConstants: vec![constant::Int(1)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpSetGlobal, &[0]),
+                code::Make(code::OpGetGlobal, &[0]),
+                code::Make(code::OpSetGlobal, &[1]),
+                code::Make(code::OpGetGlobal, &[1]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "let one = 1; let one = 2;",
+            expectedConstants: vec![constant::Int(1), constant::Int(2)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpSetGlobal, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpSetGlobal, &[0]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestCollections() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: r#"["a", 2][0]"#,
+            expectedConstants: vec![constant::Str("a"), constant::Int(2), constant::Int(0)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpArray, &[2]),
+                code::Make(code::OpConstant, &[2]),
+                code::Make(code::OpIndex, &[]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "{1: 2}",
+            expectedConstants: vec![constant::Int(1), constant::Int(2)],
+            expectedInstructions: vec![
+                code::Make(code::OpConstant, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpHash, &[2]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestFunctions() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "fn() { return 5 + 10 }",
+            expectedConstants: vec![
+                constant::Int(5),
+                constant::Int(10),
+                constant::Fn(vec![
+                    code::Make(code::OpConstant, &[0]),
+                    code::Make(code::OpConstant, &[1]),
+                    code::Make(code::OpAdd, &[]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[2, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "fn() { }",
+            expectedConstants: vec![constant::Fn(vec![code::Make(code::OpReturn, &[])])],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[0, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "let f = fn(a) { let b = a; b }; f(1);",
+            expectedConstants: vec![
+                constant::Fn(vec![
+                    code::Make(code::OpGetLocal, &[0]),
+                    code::Make(code::OpSetLocal, &[1]),
+                    code::Make(code::OpGetLocal, &[1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+                constant::Int(1),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[0, 0]),
+                code::Make(code::OpSetGlobal, &[0]),
+                code::Make(code::OpGetGlobal, &[0]),
+                code::Make(code::OpConstant, &[1]),
+                code::Make(code::OpCall, &[1]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "len([])",
+            expectedConstants: vec![],
+            expectedInstructions: vec![
+                code::Make(code::OpGetBuiltin, &[2]),
+                code::Make(code::OpArray, &[0]),
+                code::Make(code::OpCall, &[1]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestClosures() {
+    runCompilerTests(vec![
+        compilerTestCase {
+            input: "fn(a) { fn(b) { a + b } }",
+            expectedConstants: vec![
+                constant::Fn(vec![
+                    code::Make(code::OpGetFree, &[0]),
+                    code::Make(code::OpGetLocal, &[0]),
+                    code::Make(code::OpAdd, &[]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+                constant::Fn(vec![
+                    code::Make(code::OpCaptureLocal, &[0]),
+                    code::Make(code::OpClosure, &[0, 1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[1, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "let countDown = fn(x) { countDown(x - 1); };",
+            expectedConstants: vec![
+                constant::Int(1),
+                constant::Fn(vec![
+                    code::Make(code::OpGetGlobal, &[0]),
+                    code::Make(code::OpGetLocal, &[0]),
+                    code::Make(code::OpConstant, &[0]),
+                    code::Make(code::OpSub, &[]),
+                    code::Make(code::OpCall, &[1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[1, 0]),
+                code::Make(code::OpSetGlobal, &[0]),
+            ],
+        },
+        compilerTestCase {
+            // a local function captures the variable it is assigned to, before it is set.
+            input: "fn() { let f = fn() { f = 1 }; }",
+            expectedConstants: vec![
+                constant::Int(1),
+                constant::Fn(vec![
+                    code::Make(code::OpConstant, &[0]),
+                    code::Make(code::OpSetFree, &[0]),
+                    code::Make(code::OpReturn, &[]),
+                ]),
+                constant::Fn(vec![
+                    code::Make(code::OpCaptureLocal, &[0]),
+                    code::Make(code::OpClosure, &[1, 1]),
+                    code::Make(code::OpSetLocal, &[0]),
+                    code::Make(code::OpReturn, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[2, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+        compilerTestCase {
+            input: "fn(a) { fn() { fn() { a } } }",
+            expectedConstants: vec![
+                constant::Fn(vec![
+                    code::Make(code::OpGetFree, &[0]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+                constant::Fn(vec![
+                    code::Make(code::OpCaptureFree, &[0]),
+                    code::Make(code::OpClosure, &[0, 1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+                constant::Fn(vec![
+                    code::Make(code::OpCaptureLocal, &[0]),
+                    code::Make(code::OpClosure, &[1, 1]),
+                    code::Make(code::OpReturnValue, &[]),
+                ]),
+            ],
+            expectedInstructions: vec![
+                code::Make(code::OpClosure, &[2, 0]),
+                code::Make(code::OpPop, &[]),
+            ],
+        },
+    ]);
+}
+
+#[test]
+fn TestCompilerErrors() {
+    let tests = vec![
+        ("foobar", "identifier not found: foobar"),
+        ("let x = x;", "identifier not found: x"),
+        ("fn() { y }", "identifier not found: y"),
+        ("fn() { y = 1 }", "identifier not found: y"),
+        ("x += 1; let x = 0;", "identifier not found: x"),
+        ("len = 1", "cannot assign to builtin len"),
+        ("const x = 1; x = 2;", "cannot assign to const x"),
+        ("const x = 1; let x = 2;", "cannot redeclare const x"),
+        (
+            "let f = fn() { const y = 1; fn() { y += 1 } };",
+            "cannot assign to const y",
+        ),
+        (
+            "let f = fn() { x = 2 }; const x = 1;",
+            "cannot assign to const x",
+        ),
+    ];
+
+    for (input, expected) in tests.iter() {
+        let l = lexer::New(input.to_string());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        let mut comp = compiler::New();
+        assert_eq!(comp.Compile(&program), Err(expected.to_string()));
+    }
+}
+
+#[test]
+fn TestResolveFree() {
+    let mut global = symbol_table::NewSymbolTable();
+    global.Define("a");
+    let mut firstLocal = symbol_table::NewEnclosedSymbolTable(global);
+    firstLocal.Define("c");
+    let mut secondLocal = symbol_table::NewEnclosedSymbolTable(firstLocal);
+    secondLocal.Define("e");
+
+    let expected = vec![
+        Symbol {
+            Name: String::from("a"),
+            Scope: symbol_table::GlobalScope,
+            Index: 0,
+        },
+        Symbol {
+            Name: String::from("c"),
+            Scope: symbol_table::FreeScope,
+            Index: 0,
+        },
+        Symbol {
+            Name: String::from("e"),
+            Scope: symbol_table::LocalScope,
+            Index: 0,
+        },
+    ];
+    for sym in expected.iter() {
+        assert_eq!(secondLocal.Resolve(&sym.Name).as_ref(), Some(sym));
+    }
+    assert_eq!(
+        secondLocal.FreeSymbols,
+        vec![Symbol {
+            Name: String::from("c"),
+            Scope: symbol_table::LocalScope,
+            Index: 0,
+        }]
+    );
+    assert_eq!(secondLocal.Resolve("unknown"), None);
+}
+
+#[test]
+fn TestBytecodeDisassemble() {
+    let l = lexer::New(String::from("let double = fn(x) { x * 2 }; double(1)"));
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let mut comp = compiler::New();
+    comp.Compile(&program).unwrap();
+
+    let expected = "main:
+0000 OpClosure 1 0          ; fn double
+0004 OpSetGlobal 0
+0007 OpGetGlobal 0
+0010 OpConstant 2           ; 1
+0013 OpCall 1
+0015 OpPop
+
+constants:
+   0 2
+   1 fn double (1 params, 1 locals)
+   2 1
+
+fn double (constant 1):
+0000 OpGetLocal 0
+0002 OpConstant 0           ; 2
+0005 OpMul
+0006 OpReturnValue
+";
+    assert_eq!(comp.Bytecode().Disassemble(), expected);
+}
@@ -0,0 +1,54 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{cst, token};
+
+#[test]
+fn TestText() {
+    let inputs = vec![
+        "",
+        "let x = 1;",
+        "  // only a comment\n",
+        "let s = \"a\\n\\\"b\";  /* c */\n\n\tputs(s) // d\n",
+        "fn(a, /* b */ c) {\r\n  a\r\n}",
+    ];
+
+    for input in inputs.iter() {
+        let tree = cst::Parse(input).unwrap();
+        assert_eq!(tree.Text(), *input);
+    }
+}
+
+#[test]
+fn TestTrivia() {
+    let tree = cst::Parse("let x = 1; // one\n// two\nx").unwrap();
+    let semicolon = &tree.Tokens[4];
+    assert_eq!(semicolon.Token.Type, token::SEMICOLON);
+    let trailing: Vec<&str> = semicolon.Trailing.iter().map(|t| t.Text.as_str()).collect();
+    assert_eq!(trailing, vec![" ", "// one"]);
+
+    let x = &tree.Tokens[5];
+    assert_eq!(x.Token.Type, token::IDENT);
+    let leading: Vec<&str> = x.Leading.iter().map(|t| t.Text.as_str()).collect();
+    assert_eq!(leading, vec!["\n", "// two", "\n"]);
+
+    let comments: Vec<&str> = tree.Comments().iter().map(|t| t.Text.as_str()).collect();
+    assert_eq!(comments, vec!["// one", "// two"]);
+}
+
+#[test]
+fn TestTokenAt() {
+    let tree = cst::Parse("let x = /* c */ 10;").unwrap();
+    let tok = tree.TokenAt(16).unwrap();
+    assert_eq!(tok.Token.Type, token::INT);
+    assert!(tok.Leading.is_empty());
+    // the comment is on the same line, so it trails the `=`.
+    let assign = tree.TokenAt(6).unwrap();
+    assert_eq!(assign.Trailing[1].Kind, token::TriviaKind::BlockComment);
+    assert!(tree.TokenAt(17).is_none());
+}
+
+#[test]
+fn TestParseError() {
+    assert!(cst::Parse("let = 1; // c").is_err());
+}
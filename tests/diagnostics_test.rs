@@ -0,0 +1,151 @@
+#![allow(non_snake_case)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{diagnostics, evaluator, lexer, object};
+
+fn parseDiagnostics(input: &str) -> Vec<diagnostics::Diagnostic> {
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    p.ParseProgram();
+    p.Errors()
+        .iter()
+        .map(|e| diagnostics::FromParseError(e, input))
+        .collect()
+}
+
+#[test]
+fn TestRenderParseError() {
+    let input = "let a = 1;\nlet x 5;";
+    let diags = parseDiagnostics(input);
+    assert!(!diags.is_empty());
+
+    let expected = "error: expected next token to be =, got INT, instead
+ --> test.mk:2:7
+  |
+2 | let x 5;
+  |       ^
+";
+    assert_eq!(
+        diags[0].Render(input, "test.mk", diagnostics::Mode::Plain),
+        expected
+    );
+}
+
+#[test]
+fn TestMisspelledKeywordNote() {
+    let input = "let f = fnn(x) { x };";
+    let diags = parseDiagnostics(input);
+    assert!(!diags.is_empty());
+    assert_eq!(diags[0].Notes, vec![String::from("did you mean `fn`?")]);
+
+    let rendered = diags[0].Render(input, "<repl>", diagnostics::Mode::Plain);
+    assert!(rendered.ends_with("  = note: did you mean `fn`?\n"));
+
+    // `lett x = 5;` parses as `lett; x = 5;`, so the hint comes from the runtime error.
+    let input = "lett x = 5;";
+    assert!(parseDiagnostics(input).is_empty());
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+    let diag = diagnostics::FromRuntimeError(&evaluated, &env.borrow()).unwrap();
+    assert_eq!(diag.Notes, vec![String::from("did you mean `let`?")]);
+}
+
+#[test]
+fn TestRuntimeErrorDiagnostic() {
+    let input = "let count = 1;\ncount + cuont";
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+
+    let diag = diagnostics::FromRuntimeError(&evaluated, &env.borrow()).unwrap();
+    assert_eq!(diag.Message, "identifier not found: cuont");
+    assert_eq!(diag.Notes, vec![String::from("did you mean `count`?")]);
+
+    let expected = "error: identifier not found: cuont
+ --> <repl>:2:9
+  |
+2 | count + cuont
+  |         ^^^^^
+  = note: did you mean `count`?
+";
+    assert_eq!(
+        diag.Render(input, "<repl>", diagnostics::Mode::Plain),
+        expected
+    );
+}
+
+#[test]
+fn TestRenderColor() {
+    let input = "let x 5;";
+    let diags = parseDiagnostics(input);
+    let rendered = diags[0].Render(input, "<repl>", diagnostics::Mode::Color);
+    assert!(rendered.contains("\x1b[1;31merror\x1b[0m"));
+    assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+}
+
+#[test]
+fn TestSuggest() {
+    let candidates = vec![String::from("count"), String::from("len")];
+    assert_eq!(
+        diagnostics::Suggest("coutn", &candidates),
+        Some(String::from("count"))
+    );
+    assert_eq!(
+        diagnostics::Suggest("lne", &candidates),
+        None,
+        "short names allow only one edit"
+    );
+    assert_eq!(
+        diagnostics::Suggest("lem", &candidates),
+        Some(String::from("len"))
+    );
+    assert_eq!(diagnostics::Suggest("x", &candidates), None);
+}
+
+#[test]
+fn TestRenderTraceback() {
+    let input = "let f = fn(n) { if (n == 0) { boom } else { f(n - 1) } };\nf(2)";
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+
+    let diag = diagnostics::FromRuntimeError(&evaluated, &env.borrow()).unwrap();
+    let expected = "error: identifier not found: boom
+ --> t.mk:1:31
+  |
+1 | let f = fn(n) { if (n == 0) { boom } else { f(n - 1) } };
+  |                               ^^^^
+traceback (most recent call first):
+  0: f, 1 arg, called at t.mk:1:45
+  1: f, 1 arg, called at t.mk:1:45
+  2: f, 1 arg, called at t.mk:2:1
+";
+    assert_eq!(
+        diag.Render(input, "t.mk", diagnostics::Mode::Plain),
+        expected
+    );
+}
+
+#[test]
+fn TestRenderLongTraceback() {
+    let input = "let f = fn(n) { if (n == 0) { boom } else { f(n - 1) } };\nf(30)";
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+
+    let diag = diagnostics::FromRuntimeError(&evaluated, &env.borrow()).unwrap();
+    assert_eq!(diag.Trace.len(), 31);
+    let rendered = diag.Render(input, "t.mk", diagnostics::Mode::Plain);
+    assert!(rendered
+        .contains("  9: f, 1 arg, called at t.mk:1:45\n  ... 11 frames omitted ...\n  21: f"));
+    assert!(rendered.ends_with("  30: f, 1 arg, called at t.mk:2:1\n"));
+}
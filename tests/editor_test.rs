@@ -0,0 +1,91 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{editor, object};
+use std::path::PathBuf;
+
+#[test]
+fn TestComplete() {
+    let env = object::NewEnvironment();
+    env.borrow_mut()
+        .Set("lemon", object::Object::Integer { Value: 1 });
+    env.borrow_mut()
+        .Set("result", object::Object::Integer { Value: 2 });
+
+    struct tests_struct {
+        line: &'static str,
+        pos: usize,
+        start: usize,
+        expected: Vec<&'static str>,
+    }
+
+    let tests = vec![
+        tests_struct {
+            line: "le",
+            pos: 2,
+            start: 0,
+            expected: vec!["lemon", "len", "let"],
+        },
+        tests_struct {
+            line: "puts(re",
+            pos: 7,
+            start: 5,
+            expected: vec!["rest", "result", "return"],
+        },
+        tests_struct {
+            // only the word before the cursor counts.
+            line: "f(lemon)",
+            pos: 4,
+            start: 2,
+            expected: vec!["lemon", "len", "let"],
+        },
+        tests_struct {
+            line: "1 + ",
+            pos: 4,
+            start: 4,
+            expected: vec![],
+        },
+        tests_struct {
+            line: "x12",
+            pos: 3,
+            start: 0,
+            expected: vec![],
+        },
+        tests_struct {
+            line: ":t",
+            pos: 2,
+            start: 0,
+            expected: vec![":tokens", ":type"],
+        },
+        tests_struct {
+            line: ":load le",
+            pos: 8,
+            start: 6,
+            expected: vec!["lemon", "len", "let"],
+        },
+    ];
+
+    for tt in tests.iter() {
+        let (start, candidates) = editor::Complete(tt.line, tt.pos, &env.borrow());
+        assert_eq!(start, tt.start, "{:?}", tt.line);
+        assert_eq!(candidates, tt.expected, "{:?}", tt.line);
+    }
+}
+
+#[test]
+fn TestHistoryPath() {
+    std::env::set_var("MONKEY_HISTORY", "/tmp/monkey-test-history");
+    assert_eq!(
+        editor::HistoryPath(),
+        Some(PathBuf::from("/tmp/monkey-test-history"))
+    );
+    std::env::set_var("MONKEY_HISTORY", "");
+    assert_eq!(editor::HistoryPath(), None);
+
+    std::env::remove_var("MONKEY_HISTORY");
+    std::env::set_var("HOME", "/home/monkey");
+    assert_eq!(
+        editor::HistoryPath(),
+        Some(PathBuf::from("/home/monkey/.monkey_history"))
+    );
+}
@@ -1,5 +1,7 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
 extern crate monkey_interpreter;
-use monkey_interpreter::{ast, evaluator, lexer, object, parser};
+use monkey_interpreter::{compiler, evaluator, lexer, object, token, vm};
 
 #[test]
 fn TestEvalIntegerExpression() {
@@ -68,21 +70,63 @@ fn TestEvalIntegerExpression() {
             input: String::from("(5 + 10 * 2 + 15 / 3) * 2 - 10"),
             expected: 50,
         },
+        tests_struct {
+            input: String::from("17 % 5 * 2"),
+            expected: 4,
+        },
+        tests_struct {
+            input: String::from("-7 % 3"),
+            expected: -1,
+        },
     ];
 
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
-        let evaluated = evaluated;
-        assert_eq!(true, testIntegerObject(&evaluated, tt.expected));
+        assert!(testIntegerObject(&evaluated, tt.expected));
     }
 }
 
-fn testEval(input: &String) -> object::Object {
-    let l = lexer::New(input.clone());
+// testEval evaluates input with the tree-walking evaluator and checks that the compiler and
+// vm arrive at the same result.
+fn testEval(input: &str) -> object::Object {
+    let l = lexer::New(input.to_string());
     let mut p = l.New();
     let program = p.ParseProgram();
-    let mut env = object::NewEnvironment();
-    evaluator::Eval(program, &mut env)
+
+    let mut comp = compiler::New();
+    let compiled = comp.Compile(&program).map(|_| comp.Bytecode());
+
+    let env = object::NewEnvironment();
+    let evaluated = evaluator::Eval(program, &env);
+    testVmAgrees(input, compiled, &evaluated);
+    evaluated
+}
+
+fn testVmAgrees(
+    input: &str,
+    compiled: Result<compiler::Bytecode, String>,
+    evaluated: &object::Object,
+) {
+    let result = compiled.and_then(|bytecode| {
+        let mut machine = vm::New(bytecode);
+        machine.Run().map(|_| machine.LastPoppedStackElem())
+    });
+    match (evaluated, result) {
+        (object::Object::Error { Message, .. }, Err(err)) => {
+            assert_eq!(Message, &err, "vm error differs for {:?}", input)
+        }
+        // functions compile to closures, which only share their type with evaluated functions.
+        (object::Object::Function { .. }, Ok(obj)) => {
+            assert_eq!(
+                evaluated.Type(),
+                obj.Type(),
+                "vm result differs for {:?}",
+                input
+            )
+        }
+        (_, Ok(obj)) => assert_eq!(evaluated, &obj, "vm result differs for {:?}", input),
+        (_, Err(err)) => panic!("vm failed for {:?}: {}", input, err),
+    }
 }
 
 fn testIntegerObject(obj: &object::Object, expected: i64) -> bool {
@@ -182,11 +226,51 @@ fn TestEvalBooleanExpression() {
             input: String::from("(1 > 2) == false"),
             expected: true,
         },
+        tests_struct {
+            input: String::from("1 <= 2"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("2 <= 2"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("3 <= 2"),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from("1 >= 2"),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from("2 >= 2"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("true && false"),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from("true && 1"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("false || 1 < 2"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("false || false"),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from("1 < 2 && 2 < 3 || false"),
+            expected: true,
+        },
     ];
 
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
-        assert_eq!(true, testBooleanObject(&evaluated, tt.expected));
+        assert!(testBooleanObject(&evaluated, tt.expected));
     }
 }
 
@@ -210,7 +294,7 @@ fn TestBangOperator() {
         input: String,
         expected: bool,
     }
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("!true"),
             expected: false,
@@ -239,7 +323,7 @@ fn TestBangOperator() {
 
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
-        assert_eq!(true, testBooleanObject(&evaluated, tt.expected));
+        assert!(testBooleanObject(&evaluated, tt.expected));
     }
 }
 
@@ -250,7 +334,7 @@ fn TestIfElseExpressions() {
         expected: object::Object,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("if (true) {10}"),
             expected: object::Object::Integer { Value: 10 },
@@ -284,9 +368,9 @@ fn TestIfElseExpressions() {
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
         if let object::Object::Integer { Value } = tt.expected {
-            assert_eq!(true, testIntegerObject(&evaluated, Value));
+            assert!(testIntegerObject(&evaluated, Value));
         } else {
-            assert_eq!(true, testNullObject(&evaluated));
+            assert!(testNullObject(&evaluated));
         }
     }
 }
@@ -307,7 +391,7 @@ fn TestReturnStatements() {
         expected: i64,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("return 10;"),
             expected: 10,
@@ -340,7 +424,7 @@ fn TestReturnStatements() {
 
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
-        assert_eq!(true, testIntegerObject(&evaluated, tt.expected));
+        assert!(testIntegerObject(&evaluated, tt.expected));
     }
 }
 
@@ -351,7 +435,7 @@ fn TestErrorHandling() {
         expected: String,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("5 + true;"),
             expected: String::from("type mismatch: INTEGER + BOOLEAN"),
@@ -393,11 +477,63 @@ fn TestErrorHandling() {
             input: String::from("foobar"),
             expected: String::from("identifier not found: foobar"),
         },
+        tests_struct {
+            input: String::from("10 / (5 - 5)"),
+            expected: String::from("division by zero"),
+        },
+        tests_struct {
+            input: String::from("10 % 0"),
+            expected: String::from("division by zero"),
+        },
+        tests_struct {
+            input: String::from("let m = -9223372036854775807 - 1; m / -1"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("let m = -9223372036854775807 - 1; m % -1"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("let f = fn(a, b) { a }; f(1)"),
+            expected: String::from("wrong number of arguments. got=1, want=2"),
+        },
+        tests_struct {
+            input: String::from("fn() { 1 }(1)"),
+            expected: String::from("wrong number of arguments. got=1, want=0"),
+        },
+        tests_struct {
+            input: String::from("9223372036854775807 + 1"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("-9223372036854775807 - 2"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("4611686018427387904 * 2"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("-(-9223372036854775807 - 1)"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("let n = 9223372036854775807; n += 1; n"),
+            expected: String::from("integer overflow"),
+        },
+        tests_struct {
+            input: String::from("\"a\" <= \"b\""),
+            expected: String::from("unknown operator: STRING <= STRING"),
+        },
+        tests_struct {
+            input: String::from("true && foobar"),
+            expected: String::from("identifier not found: foobar"),
+        },
     ];
 
     for tt in tests.iter() {
         let evaluated = testEval(&tt.input);
-        if let object::Object::Error { Message } = evaluated {
+        if let object::Object::Error { Message, .. } = evaluated {
             assert_eq!(tt.expected, Message);
         } else {
             println!("no error object returned. got={}", evaluated);
@@ -406,8 +542,41 @@ fn TestErrorHandling() {
     }
 }
 
+// the right operand of && and || must not run when the left one decides the result.
 #[test]
-fn TestLetStatements() {
+fn TestShortCircuit() {
+    struct tests_struct {
+        input: String,
+        expected: bool,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("let x = 0; x != 0 && 10 / x > 1"),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from("let x = 0; x == 0 || 10 / x > 1"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("let x = 5; x != 0 && 10 / x > 1"),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from("false && len(1)"),
+            expected: false,
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(testBooleanObject(&evaluated, tt.expected), "{}", tt.input);
+    }
+}
+
+#[test]
+fn TestWhileLoops() {
     struct tests_struct {
         input: String,
         expected: i64,
@@ -415,65 +584,99 @@ fn TestLetStatements() {
 
     let tests = vec![
         tests_struct {
-            input: String::from("let a = 5; a;"),
+            input: String::from("let i = 0; while (i < 5) { i = i + 1; }; i"),
             expected: 5,
         },
         tests_struct {
-            input: String::from("let a = 5 * 5; a;"),
+            input: String::from("let i = 0; while (true) { if (i == 3) { break; } i = i + 1; }; i"),
+            expected: 3,
+        },
+        tests_struct {
+            input: String::from(
+                "let i = 0; let odd = 0; \
+                 while (i < 10) { i = i + 1; if (i % 2 == 0) { continue; } odd = odd + i; }; \
+                 odd",
+            ),
             expected: 25,
         },
         tests_struct {
-            input: String::from("let a = 5; let b = a; b;"),
-            expected: 5,
+            // break and continue apply to the innermost loop.
+            input: String::from(
+                "let i = 0; let n = 0; \
+                 while (i < 3) { i = i + 1; let j = 0; \
+                 while (true) { j = j + 1; if (j > i) { break; } n = n + 1; } }; \
+                 n",
+            ),
+            expected: 6,
         },
         tests_struct {
-            input: String::from("let a = 5; let b = a; let c = a + b + 5; c;"),
-            expected: 15,
+            input: String::from(
+                "let find = fn(arr, x) { let i = 0; \
+                 while (i < len(arr)) { if (arr[i] == x) { return i; } i = i + 1; } -1 }; \
+                 find([5, 6, 7], 7)",
+            ),
+            expected: 2,
+        },
+        tests_struct {
+            // an if that starts a statement may break before an operator uses its value.
+            input: String::from(
+                "let i = 0; while (true) { if (i == 3) { break; } else { i } + 1; i += 1; }; i",
+            ),
+            expected: 3,
+        },
+        tests_struct {
+            // a block inside an expression can leave a loop of its own.
+            input: String::from(
+                "let n = 0; while (n < 5) { let v = if (true) { while (true) { break; } 2 } else { 0 }; \
+                 n += v; }; n",
+            ),
+            expected: 6,
+        },
+        tests_struct {
+            // like for loops, each pass has variables of its own for closures to keep.
+            input: String::from(
+                "let fs = []; let i = 0; \
+                 while (i < 3) { let j = i; fs = push(fs, fn() { j }); i += 1; }; \
+                 fs[0]() + fs[2]()",
+            ),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { let fs = []; let i = 0; \
+                 while (i < 3) { let j = i * 10; fs = push(fs, fn() { j }); i += 1; } fs }; \
+                 let fs = f(); fs[1]() + fs[2]()",
+            ),
+            expected: 30,
+        },
+        tests_struct {
+            // a loop runs in constant stack space.
+            input: String::from(
+                "let sum = fn(n) { let i = 0; let s = 0; \
+                 while (i < n) { i = i + 1; s = s + i; } s }; \
+                 sum(50000)",
+            ),
+            expected: 1250025000,
         },
     ];
 
     for tt in tests.iter() {
-        assert_eq!(true, testIntegerObject(&testEval(&tt.input), tt.expected));
+        let evaluated = testEval(&tt.input);
+        assert!(testIntegerObject(&evaluated, tt.expected), "{}", tt.input);
     }
-}
 
-#[test]
-fn TestFunctionObject() {
-    let input = String::from("fn(x) {x + 2;}");
-    let evaluated = testEval(&input);
-    if let object::Object::Function {
-        Parameters,
-        Body,
-        Env,
-    } = evaluated
-    {
-        assert_eq!(
-            1,
-            Parameters.len(),
-            "function has wrong parameters. Parameters={:?}",
-            Parameters
-        );
-        assert_eq!(
-            String::from("x"),
-            Parameters[0].into_string(),
-            "parameter is not 'x'. got={}",
-            Parameters[0]
-        );
-        let expectedBody = String::from("(x + 2)");
-        assert_eq!(
-            Body.into_string(),
-            expectedBody,
-            "body is not {}. got={}.",
-            expectedBody,
-            Body.into_string()
-        );
-    } else {
-        panic!("object is not Function. got={}", evaluated);
+    for input in ["while (false) { 1 }", "if (true) { 1; while (false) {} }"] {
+        assert_eq!(testEval(input), object::Object::Null, "{}", input);
     }
+    let evaluated = testEval("let i = 0; while (i < 1) { i = i + 1; i + true; }");
+    assert_eq!(
+        evaluated.Inspect(),
+        "ERROR: type mismatch: INTEGER + BOOLEAN"
+    );
 }
 
 #[test]
-fn TestFunctionApplication() {
+fn TestForLoops() {
     struct tests_struct {
         input: String,
         expected: i64,
@@ -481,45 +684,1004 @@ fn TestFunctionApplication() {
 
     let tests = vec![
         tests_struct {
-            input: String::from("let identity = fn(x) {x;}; identity(5);"),
-            expected: 5,
+            input: String::from(
+                "let f = fn() { for (x in [1, 2, 3]) { if (x > 1) { return x * 10; } } -1 }; f()",
+            ),
+            expected: 20,
         },
         tests_struct {
-            input: String::from("let identity = fn(x) {return x;}; identity(5);"),
+            input: String::from(
+                "let f = fn() { for (i in 3..10) { if (i * i > 20) { return i; } } }; f()",
+            ),
             expected: 5,
         },
         tests_struct {
-            input: String::from("let double = fn(x) {x * 2;}; double(5);"),
-            expected: 10,
+            input: String::from("let f = fn() { for (i in 5..5) { return 1; } 0 }; f()"),
+            expected: 0,
         },
         tests_struct {
-            input: String::from("let add = fn(x, y) {x + y;}; add(5, 5);"),
-            expected: 10,
+            input: String::from(
+                "let f = fn() { for (i, c in \"abc\") { if (c == \"c\") { return i; } } }; f()",
+            ),
+            expected: 2,
         },
         tests_struct {
-            input: String::from("let add = fn(x, y) {x + y;}; add(5 + 5, add(5, 5));"),
-            expected: 20,
+            input: String::from("let f = fn() { for (k in {\"ab\": 7}) { return len(k); } }; f()"),
+            expected: 2,
         },
         tests_struct {
-            input: String::from("fn(x) {x;}(5);"),
-            expected: 5,
+            input: String::from("let f = fn() { for (k, v in {\"ab\": 7}) { return v; } }; f()"),
+            expected: 7,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { for (i, x in [5, 6, 7]) { if (x == 7) { return i; } } }; f()",
+            ),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { for (i in 0..10) { if (i < 3) { continue; } if (i == 3) { return i; } } -1 }; f()",
+            ),
+            expected: 3,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { for (i in 0..10) { break; return 1; } 0 }; f()",
+            ),
+            expected: 0,
+        },
+        tests_struct {
+            // any function following the protocol can be iterated.
+            input: String::from(
+                "let count = fn(n) { let go = fn(i) { fn() { if (i < n) { [i, go(i + 1)] } } }; go(0) }; \
+                 let f = fn() { for (x in count(10)) { if (x * 2 == 8) { return x; } } }; f()",
+            ),
+            expected: 4,
+        },
+        tests_struct {
+            // the loop body is a scope of its own.
+            input: String::from("let x = 1; for (x in [2]) { let y = x; }; x"),
+            expected: 1,
+        },
+        tests_struct {
+            // each pass has its own variables, at the top level as in a function.
+            input: String::from(
+                "let fs = []; for (i in 0..3) { fs = push(fs, fn() { i }); } fs[0]() + fs[1]() + fs[2]()",
+            ),
+            expected: 3,
+        },
+        tests_struct {
+            input: String::from(
+                "let fs = []; for (i in 0..3) { let j = i * 10; fs = push(fs, fn() { j += 1; j }); } \
+                 fs[0](); fs[0]() + fs[2]()",
+            ),
+            expected: 23,
+        },
+        tests_struct {
+            input: String::from(
+                "let fs = []; for (i in 0..2) { for (j in 0..2) { fs = push(fs, fn() { i * 10 + j }); } } \
+                 fs[0]() + fs[1]() + fs[2]() + fs[3]()",
+            ),
+            expected: 22,
         },
     ];
 
     for tt in tests.iter() {
-        assert_eq!(true, testIntegerObject(&testEval(&tt.input), tt.expected));
+        let evaluated = testEval(&tt.input);
+        assert!(testIntegerObject(&evaluated, tt.expected), "{}", tt.input);
+    }
+
+    assert_eq!(testEval("1..4"), object::Object::Range { Start: 1, End: 4 });
+    assert_eq!(testEval("for (x in 1..3) { x }"), object::Object::Null);
+
+    let errors = vec![
+        ("for (x in 5) {}", "cannot iterate over INTEGER"),
+        ("for (x in [1]) {}; x", "identifier not found: x"),
+        (
+            "for (x in fn() { 1 }) {}",
+            "iterator returned INTEGER, want [item, next] or null",
+        ),
+        (
+            "for (a, b in fn() { [1, fn() {}] }) {}",
+            "cannot unpack INTEGER into 2 variables",
+        ),
+    ];
+    for (input, message) in errors {
+        match testEval(input) {
+            object::Object::Error { Message, .. } => assert_eq!(Message, message, "{}", input),
+            obj => panic!("no error for {}. got={}", input, obj.Inspect()),
+        }
     }
 }
 
 #[test]
-fn TestClosures() {
-    let input = String::from(
-        "\
-    let newAdder = fn(x) {
-        fn(y){x+y;};
-    };
-    let addTwo = newAdder(2);
-    addTwo(2);",
-    );
-    assert_eq!(true, testIntegerObject(&testEval(&input), 4));
+fn TestAssignment() {
+    struct tests_struct {
+        input: String,
+        expected: i64,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("let x = 1; x = 2; x"),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x"),
+            expected: 6,
+        },
+        tests_struct {
+            input: String::from("let total = 0; for (x in 1..5) { total += x; }; total"),
+            expected: 10,
+        },
+        tests_struct {
+            input: String::from("let i = 0; while (i < 5) { i += 1; }; i"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { let s = 0; for (x in [1, 2, 3]) { s += x; } s }; f()",
+            ),
+            expected: 6,
+        },
+        tests_struct {
+            input: String::from("let f = fn(n) { n *= 2; n }; f(21)"),
+            expected: 42,
+        },
+        tests_struct {
+            // a function can update a global.
+            input: String::from("let n = 0; let inc = fn() { n += 1; }; inc(); inc(); n"),
+            expected: 2,
+        },
+        tests_struct {
+            // and a closure the variables it captured.
+            input: String::from(
+                "let counter = fn() { let c = 0; fn() { c += 1; c } }; \
+                 let next = counter(); next(); next(); next()",
+            ),
+            expected: 3,
+        },
+        tests_struct {
+            // the innermost binding is the one updated.
+            input: String::from("let x = 1; let f = fn() { let x = 10; x = 20; x }; f() + x"),
+            expected: 21,
+        },
+        tests_struct {
+            // the old value is read before the right-hand side runs.
+            input: String::from("let x = 1; let f = fn() { x = 100; 2 }; x += f(); x"),
+            expected: 3,
+        },
+        tests_struct {
+            // a closure shares the variables it captured with the function that declared them.
+            input: String::from(
+                "let f = fn() { let n = 0; let g = fn() { n += 1 }; g(); g(); n }; f()",
+            ),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from(
+                "let mk = fn() { let n = 0; [fn() { n += 1 }, fn() { n }] }; let p = mk(); p[0](); p[0](); p[1]()",
+            ),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { let n = 1; let g = fn() { fn() { n *= 10 } }; g()(); n = n + 1; g()(); n }; f()",
+            ),
+            expected: 110,
+        },
+        tests_struct {
+            // a function's own name is the binding it was assigned to.
+            input: String::from("let f = fn() { f = 1; 2 }; f() + f"),
+            expected: 3,
+        },
+        tests_struct {
+            input: String::from("let h = fn() { let f = fn() { f = 5; 0 }; f(); f }; h()"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from("let f = fn() { 1 }; let g = f; f = fn() { 2 }; g() * 10 + f()"),
+            expected: 12,
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(testIntegerObject(&evaluated, tt.expected), "{}", tt.input);
+    }
+
+    assert_eq!(
+        testEval("let s = \"a\"; s += \"b\"; s"),
+        object::Object::String {
+            Value: String::from("ab")
+        }
+    );
+
+    let errors = vec![
+        ("x = 1", "identifier not found: x"),
+        ("len = 1", "cannot assign to builtin len"),
+        (
+            "let x = 1; x += true; x",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        ("let x = 1; x /= 0; x", "division by zero"),
+    ];
+    for (input, message) in errors {
+        match testEval(input) {
+            object::Object::Error { Message, .. } => assert_eq!(Message, message, "{}", input),
+            obj => panic!("no error for {}. got={}", input, obj.Inspect()),
+        }
+    }
+}
+
+#[test]
+fn TestConst() {
+    struct tests_struct {
+        input: String,
+        expected: i64,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("const x = 5; x * 2"),
+            expected: 10,
+        },
+        tests_struct {
+            input: String::from("let x = 1; const x = x + 1; x"),
+            expected: 2,
+        },
+        tests_struct {
+            // inner scopes can shadow a const.
+            input: String::from("const x = 1; let f = fn() { let x = 2; x += 1; x }; f() + x"),
+            expected: 4,
+        },
+        tests_struct {
+            input: String::from("const x = 1; let f = fn(x) { x *= 10; x }; f(2) + x"),
+            expected: 21,
+        },
+        tests_struct {
+            // each pass of a for loop has a scope of its own.
+            input: String::from(
+                "let f = fn() { let s = 0; for (i in 1..4) { const sq = i * i; s += sq; } s }; f()",
+            ),
+            expected: 14,
+        },
+        tests_struct {
+            // and so does each pass of a while loop.
+            input: String::from("let i = 0; while (i < 3) { const step = 1; i += step }; i"),
+            expected: 3,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn() { let i = 0; let s = 0; while (i < 4) { const sq = i * i; s += sq; i += 1; } s }; f()",
+            ),
+            expected: 14,
+        },
+        tests_struct {
+            // only one branch of an if runs.
+            input: String::from("let a = true; if (a) { const x = 1; x } else { const x = 2; x }"),
+            expected: 1,
+        },
+        tests_struct {
+            input: String::from("let a = false; if (a) { const x = 1; x } else { const x = 2; x }"),
+            expected: 2,
+        },
+        tests_struct {
+            input: String::from(
+                "let f = fn(a) { if (a) { const x = 1; x } else { const x = 2; x } }; f(true) + f(false)",
+            ),
+            expected: 3,
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(testIntegerObject(&evaluated, tt.expected), "{}", tt.input);
+    }
+
+    // the parser does not see a const declared after the function that assigns to it.
+    let evaluated = testEval("let reset = fn() { limit = 0; }; const limit = 10; reset(); limit");
+    match evaluated {
+        object::Object::Error { Message, .. } => {
+            assert_eq!(Message, "cannot assign to const limit")
+        }
+        obj => panic!("no error. got={}", obj.Inspect()),
+    }
+
+    // later programs run in the same environment, as in the repl.
+    let env = object::NewEnvironment();
+    for (input, message) in [
+        ("let x = 1;", ""),
+        ("const x = 2;", ""),
+        ("let x = 3;", "cannot redeclare const x"),
+        ("const x = 3;", "cannot redeclare const x"),
+        ("x = 3;", "cannot assign to const x"),
+        ("let f = fn() { x -= 1 }; f()", "cannot assign to const x"),
+    ] {
+        let l = lexer::New(input.to_string());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        match evaluator::Eval(program, &env) {
+            object::Object::Error { Message, .. } => assert_eq!(Message, message, "{}", input),
+            obj => assert_eq!(message, "", "{} gave {}", input, obj.Inspect()),
+        }
+    }
+    assert!(testIntegerObject(&env.borrow().Get("x").unwrap(), 2));
+}
+
+#[test]
+fn TestErrorTraceback() {
+    let input = "let inner = fn(x, z) { x + y };
+let outer = fn() { inner(1, 2) };
+outer();";
+
+    let evaluated = testEval(input);
+    if let object::Object::Error {
+        Message,
+        Span,
+        Trace,
+    } = evaluated
+    {
+        assert_eq!(Message, "identifier not found: y");
+        assert_eq!((Span.Line, Span.Column), (1, 28));
+        assert_eq!(
+            Trace,
+            vec![
+                object::Frame {
+                    Function: String::from("inner"),
+                    Span: token::Span {
+                        Start: 51,
+                        End: 62,
+                        Line: 2,
+                        Column: 20,
+                    },
+                    Args: 2,
+                },
+                object::Frame {
+                    Function: String::from("outer"),
+                    Span: token::Span {
+                        Start: 66,
+                        End: 73,
+                        Line: 3,
+                        Column: 1,
+                    },
+                    Args: 0,
+                },
+            ]
+        );
+    } else {
+        panic!("no error object returned. got={}", evaluated);
+    }
+
+    let evaluated = testEval("fn() { 1 + true }()");
+    if let object::Object::Error { Trace, .. } = evaluated {
+        assert_eq!(Trace.len(), 1);
+        assert_eq!(Trace[0].Function, "<anonymous>");
+    } else {
+        panic!("no error object returned. got={}", evaluated);
+    }
+
+    // errors raised outside any function have an empty traceback.
+    let evaluated = testEval("len(1)");
+    if let object::Object::Error { Trace, .. } = evaluated {
+        assert!(Trace.is_empty());
+    } else {
+        panic!("no error object returned. got={}", evaluated);
+    }
+}
+
+#[test]
+fn TestLetStatements() {
+    struct tests_struct {
+        input: String,
+        expected: i64,
+    }
+
+    let tests = [
+        tests_struct {
+            input: String::from("let a = 5; a;"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from("let a = 5 * 5; a;"),
+            expected: 25,
+        },
+        tests_struct {
+            input: String::from("let a = 5; let b = a; b;"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from("let a = 5; let b = a; let c = a + b + 5; c;"),
+            expected: 15,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert!(testIntegerObject(&testEval(&tt.input), tt.expected));
+    }
+}
+
+#[test]
+fn TestFunctionObject() {
+    let input = String::from("fn(x) {x + 2;}");
+    let evaluated = testEval(&input);
+    if let object::Object::Function {
+        Parameters,
+        Body,
+        Env: _,
+    } = evaluated
+    {
+        assert_eq!(
+            1,
+            Parameters.len(),
+            "function has wrong parameters. Parameters={:?}",
+            Parameters
+        );
+        assert_eq!(
+            String::from("x"),
+            Parameters[0].into_string(),
+            "parameter is not 'x'. got={}",
+            Parameters[0]
+        );
+        let expectedBody = String::from("(x + 2)");
+        assert_eq!(
+            Body.into_string(),
+            expectedBody,
+            "body is not {}. got={}.",
+            expectedBody,
+            Body.into_string()
+        );
+    } else {
+        panic!("object is not Function. got={}", evaluated);
+    }
+}
+
+#[test]
+fn TestFunctionApplication() {
+    struct tests_struct {
+        input: String,
+        expected: i64,
+    }
+
+    let tests = [
+        tests_struct {
+            input: String::from("let identity = fn(x) {x;}; identity(5);"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from("let identity = fn(x) {return x;}; identity(5);"),
+            expected: 5,
+        },
+        tests_struct {
+            input: String::from("let double = fn(x) {x * 2;}; double(5);"),
+            expected: 10,
+        },
+        tests_struct {
+            input: String::from("let add = fn(x, y) {x + y;}; add(5, 5);"),
+            expected: 10,
+        },
+        tests_struct {
+            input: String::from("let add = fn(x, y) {x + y;}; add(5 + 5, add(5, 5));"),
+            expected: 20,
+        },
+        tests_struct {
+            input: String::from("fn(x) {x;}(5);"),
+            expected: 5,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert!(testIntegerObject(&testEval(&tt.input), tt.expected));
+    }
+}
+
+#[test]
+fn TestClosures() {
+    let input = String::from(
+        "\
+    let newAdder = fn(x) {
+        fn(y){x+y;};
+    };
+    let addTwo = newAdder(2);
+    addTwo(2);",
+    );
+    assert!(testIntegerObject(&testEval(&input), 4));
+}
+
+#[test]
+fn TestRecursiveFunctions() {
+    struct tests_struct {
+        input: String,
+        expected: i64,
+    }
+
+    let tests = [
+        tests_struct {
+            input: String::from(
+                "\
+    let fib = fn(n) {
+        if (n < 2) { return n; }
+        fib(n - 1) + fib(n - 2);
+    };
+    fib(15);",
+            ),
+            expected: 610,
+        },
+        tests_struct {
+            input: String::from(
+                "\
+    let isEven = fn(n) { if (n == 0) { true } else { isOdd(n - 1) } };
+    let isOdd = fn(n) { if (n == 0) { false } else { isEven(n - 1) } };
+    if (isOdd(7)) { 1 } else { 0 };",
+            ),
+            expected: 1,
+        },
+        tests_struct {
+            input: String::from(
+                "\
+    let getCount = fn() { count; };
+    let count = 42;
+    getCount();",
+            ),
+            expected: 42,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert!(testIntegerObject(&testEval(&tt.input), tt.expected));
+    }
+}
+
+#[test]
+fn TestFunctionScopesAreFreed() {
+    // a function bound in the scope it closes over must not keep that scope alive.
+    let input = "\
+    let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+    let make = fn() {
+        let inner = fn(n) { if (n == 0) { 0 } else { inner(n - 1) } };
+        inner
+    };
+    fib(5) + make()(3);";
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    assert!(testIntegerObject(&evaluator::Eval(program, &env), 5));
+
+    let scope = std::rc::Rc::downgrade(&env);
+    drop(env);
+    assert!(scope.upgrade().is_none());
+
+    // a function returned from a call is bound in an outer scope, so the scope of the call
+    // is kept alive only by the function.
+    let input = "\
+    let make = fn() { let inner = fn() { inner }; inner };
+    let f = make();";
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = object::NewEnvironment();
+    evaluator::Eval(program, &env);
+    let f = env.borrow().Get("f").unwrap();
+    match f {
+        object::Object::Function { Env, .. } => assert_eq!(std::rc::Rc::strong_count(&Env), 2),
+        obj => panic!("object is not Function. got={}", obj.Inspect()),
+    }
+}
+
+#[test]
+fn TestStringLiteral() {
+    let input = String::from(r#""Hello World!""#);
+    let evaluated = testEval(&input);
+    if let object::Object::String { Value } = &evaluated {
+        assert_eq!(*Value, String::from("Hello World!"));
+    } else {
+        panic!("object is not String. got={}", evaluated);
+    }
+    assert_eq!(evaluated.Inspect(), String::from(r#""Hello World!""#));
+
+    let evaluated = testEval(r#""tab\there \"quoted\"""#);
+    assert_eq!(
+        evaluated.Inspect(),
+        String::from(r#""tab\there \"quoted\"""#)
+    );
+
+    // the result reads back as the same string.
+    let evaluated = testEval(r#""a\u{1}b\u{7f}" + "\u{d}\u{0}" + "\u{1F600}""#);
+    assert_eq!(
+        evaluated.Inspect(),
+        "\"a\\u{0001}b\\u{007f}\\u{000d}\\u{0000}\u{1F600}\""
+    );
+    assert_eq!(testEval(&evaluated.Inspect()), evaluated);
+}
+
+#[test]
+fn TestStringConcatenation() {
+    let input = String::from(r#""Hello" + " " + "World!""#);
+    let evaluated = testEval(&input);
+    if let object::Object::String { Value } = evaluated {
+        assert_eq!(Value, String::from("Hello World!"));
+    } else {
+        panic!("object is not String. got={}", evaluated);
+    }
+}
+
+#[test]
+fn TestStringComparison() {
+    struct tests_struct {
+        input: String,
+        expected: bool,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from(r#""a" == "a""#),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from(r#""a" == "b""#),
+            expected: false,
+        },
+        tests_struct {
+            input: String::from(r#""a" != "b""#),
+            expected: true,
+        },
+        tests_struct {
+            input: String::from(r#"let s = "ab"; s == "a" + "b""#),
+            expected: true,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert!(testBooleanObject(&testEval(&tt.input), tt.expected));
+    }
+
+    let evaluated = testEval(r#""Hello" - "World""#);
+    if let object::Object::Error { Message, .. } = evaluated {
+        assert_eq!(Message, String::from("unknown operator: STRING - STRING"));
+    } else {
+        panic!("no error object returned. got={}", evaluated);
+    }
+}
+
+#[test]
+fn TestArrayLiterals() {
+    let input = String::from("[1, 2 * 2, 3 + 3]");
+    let evaluated = testEval(&input);
+    if let object::Object::Array { Elements } = &evaluated {
+        assert_eq!(
+            3,
+            Elements.len(),
+            "array has wrong num of elements. got={}",
+            Elements.len()
+        );
+        assert!(testIntegerObject(&Elements[0], 1));
+        assert!(testIntegerObject(&Elements[1], 4));
+        assert!(testIntegerObject(&Elements[2], 6));
+    } else {
+        panic!("object is not Array. got={}", evaluated);
+    }
+    assert_eq!(evaluated.Inspect(), String::from("[1, 4, 6]"));
+}
+
+#[test]
+fn TestArrayIndexExpressions() {
+    struct tests_struct {
+        input: String,
+        expected: object::Object,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("[1, 2, 3][0]"),
+            expected: object::Object::Integer { Value: 1 },
+        },
+        tests_struct {
+            input: String::from("[1, 2, 3][1]"),
+            expected: object::Object::Integer { Value: 2 },
+        },
+        tests_struct {
+            input: String::from("let i = 0; [1][i];"),
+            expected: object::Object::Integer { Value: 1 },
+        },
+        tests_struct {
+            input: String::from("[1, 2, 3][1 + 1];"),
+            expected: object::Object::Integer { Value: 3 },
+        },
+        tests_struct {
+            input: String::from("let myArray = [1, 2, 3]; myArray[2];"),
+            expected: object::Object::Integer { Value: 3 },
+        },
+        tests_struct {
+            input: String::from("let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];"),
+            expected: object::Object::Integer { Value: 6 },
+        },
+        tests_struct {
+            input: String::from("let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]"),
+            expected: object::Object::Integer { Value: 2 },
+        },
+        tests_struct {
+            input: String::from("[1, 2, 3][3]"),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("([1] + [2, 3])[2]"),
+            expected: object::Object::Integer { Value: 3 },
+        },
+        tests_struct {
+            input: String::from("[1, 2, 3][-1]"),
+            expected: object::Object::Error {
+                Message: String::from("negative array index: -1"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from("1[0]"),
+            expected: object::Object::Error {
+                Message: String::from("index operator not supported: INTEGER[INTEGER]"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(
+            evaluated == tt.expected,
+            "wrong result for {}. got={}, want={}",
+            tt.input,
+            evaluated,
+            tt.expected
+        );
+    }
+}
+
+#[test]
+fn TestHashLiterals() {
+    let input = String::from(
+        r#"
+    let two = "two";
+    {
+        "one": 10 - 9,
+        two: 1 + 1,
+        "thr" + "ee": 6 / 2,
+        4: 4,
+        true: 5,
+        false: 6
+    }"#,
+    );
+    let evaluated = testEval(&input);
+    let pairs = if let object::Object::Hash { Pairs } = &evaluated {
+        Pairs
+    } else {
+        panic!("Eval didn't return Hash. got={}", evaluated);
+    };
+
+    let expected = vec![
+        (
+            object::Object::String {
+                Value: String::from("one"),
+            },
+            1,
+        ),
+        (
+            object::Object::String {
+                Value: String::from("two"),
+            },
+            2,
+        ),
+        (
+            object::Object::String {
+                Value: String::from("three"),
+            },
+            3,
+        ),
+        (object::Object::Integer { Value: 4 }, 4),
+        (object::Object::Boolean { Value: true }, 5),
+        (object::Object::Boolean { Value: false }, 6),
+    ];
+
+    assert_eq!(expected.len(), pairs.len(), "Hash has wrong num of pairs");
+    for (key, value) in expected.iter() {
+        match pairs.get(&key.HashKey().unwrap()) {
+            Some(pair) => assert!(testIntegerObject(&pair.Value, *value)),
+            None => panic!("no pair for given key in Pairs"),
+        }
+    }
+}
+
+#[test]
+fn TestHashIndexExpressions() {
+    struct tests_struct {
+        input: String,
+        expected: object::Object,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from(r#"{"foo": 5}["foo"]"#),
+            expected: object::Object::Integer { Value: 5 },
+        },
+        tests_struct {
+            input: String::from(r#"{"foo": 5}["bar"]"#),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from(r#"let key = "foo"; {"foo": 5}[key]"#),
+            expected: object::Object::Integer { Value: 5 },
+        },
+        tests_struct {
+            input: String::from(r#"{}["foo"]"#),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("{5: 5}[5]"),
+            expected: object::Object::Integer { Value: 5 },
+        },
+        tests_struct {
+            input: String::from("{true: 5}[true]"),
+            expected: object::Object::Integer { Value: 5 },
+        },
+        tests_struct {
+            input: String::from("{false: 5}[false]"),
+            expected: object::Object::Integer { Value: 5 },
+        },
+        tests_struct {
+            input: String::from(r#"{"name": "Monkey"}[fn(x) { x }];"#),
+            expected: object::Object::Error {
+                Message: String::from("unusable as hash key: FUNCTION"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from(r#"{fn(x) { x }: "Monkey"};"#),
+            expected: object::Object::Error {
+                Message: String::from("unusable as hash key: FUNCTION"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(
+            evaluated == tt.expected,
+            "wrong result for {}. got={}, want={}",
+            tt.input,
+            evaluated,
+            tt.expected
+        );
+    }
+}
+
+#[test]
+fn TestBuiltinFunctions() {
+    struct tests_struct {
+        input: String,
+        expected: object::Object,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from(r#"len("")"#),
+            expected: object::Object::Integer { Value: 0 },
+        },
+        tests_struct {
+            input: String::from(r#"len("four")"#),
+            expected: object::Object::Integer { Value: 4 },
+        },
+        tests_struct {
+            input: String::from(r#"len("hello world")"#),
+            expected: object::Object::Integer { Value: 11 },
+        },
+        tests_struct {
+            input: String::from("len([1, 2, 3])"),
+            expected: object::Object::Integer { Value: 3 },
+        },
+        tests_struct {
+            input: String::from("len(1)"),
+            expected: object::Object::Error {
+                Message: String::from("argument to `len` not supported, got INTEGER"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from(r#"len("one", "two")"#),
+            expected: object::Object::Error {
+                Message: String::from("wrong number of arguments. got=2, want=1"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from("first([1, 2, 3])"),
+            expected: object::Object::Integer { Value: 1 },
+        },
+        tests_struct {
+            input: String::from("first([])"),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("first(1)"),
+            expected: object::Object::Error {
+                Message: String::from("argument to `first` must be ARRAY, got INTEGER"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from("last([1, 2, 3])"),
+            expected: object::Object::Integer { Value: 3 },
+        },
+        tests_struct {
+            input: String::from("last([])"),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("last(1)"),
+            expected: object::Object::Error {
+                Message: String::from("argument to `last` must be ARRAY, got INTEGER"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from("rest([1, 2, 3])"),
+            expected: object::Object::Array {
+                Elements: vec![
+                    object::Object::Integer { Value: 2 },
+                    object::Object::Integer { Value: 3 },
+                ],
+            },
+        },
+        tests_struct {
+            input: String::from("rest([])"),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("push([], 1)"),
+            expected: object::Object::Array {
+                Elements: vec![object::Object::Integer { Value: 1 }],
+            },
+        },
+        tests_struct {
+            input: String::from("push(1, 1)"),
+            expected: object::Object::Error {
+                Message: String::from("argument to `push` must be ARRAY, got INTEGER"),
+                Span: token::Span::default(),
+                Trace: vec![],
+            },
+        },
+        tests_struct {
+            input: String::from("let a = [1]; push(a, 2); a"),
+            expected: object::Object::Array {
+                Elements: vec![object::Object::Integer { Value: 1 }],
+            },
+        },
+        tests_struct {
+            input: String::from(r#"puts("hello", 1)"#),
+            expected: object::Object::Null,
+        },
+        tests_struct {
+            input: String::from("let len = fn(x) { 42 }; len([1])"),
+            expected: object::Object::Integer { Value: 42 },
+        },
+    ];
+
+    for tt in tests.iter() {
+        let evaluated = testEval(&tt.input);
+        assert!(
+            evaluated == tt.expected,
+            "wrong result for {}. got={}, want={}",
+            tt.input,
+            evaluated,
+            tt.expected
+        );
+    }
 }
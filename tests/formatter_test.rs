@@ -0,0 +1,187 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{formatter, lexer, Interpreter};
+
+fn parse(input: &str) -> String {
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    assert!(p.Errors().is_empty(), "{:?}", p.Errors());
+    program.into_string()
+}
+
+#[test]
+fn TestFormat() {
+    struct tests_struct {
+        input: &'static str,
+        expected: &'static str,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: "let   x=5",
+            expected: "let x = 5;\n",
+        },
+        tests_struct {
+            input: "((a + b) * c)",
+            expected: "(a + b) * c;\n",
+        },
+        tests_struct {
+            input: "(a * b) + (c * d); a - (b - c); (a - b) - c; a + (b + c)",
+            expected: "a * b + c * d;\na - (b - c);\na - b - c;\na + (b + c);\n",
+        },
+        tests_struct {
+            input: "(-a) * b; -(a * b); !(-a); (a < b) == (b > c); (a + b) < c",
+            expected: "-a * b;\n-(a * b);\n!-a;\na < b == b > c;\na + b < c;\n",
+        },
+        tests_struct {
+            input: "(f(x))[0]; (a + b)(1); (a[1])[2]; -(f(1)); (fn(x) { x })(2)",
+            expected: "f(x)[0];\n(a + b)(1);\na[1][2];\n-f(1);\nfn(x) { x }(2);\n",
+        },
+        tests_struct {
+            input: "[1,2 , 3]; {\"a\":1,true:[]}; {}",
+            expected: "[1, 2, 3];\n{\"a\": 1, true: []};\n{};\n",
+        },
+        tests_struct {
+            input: "\"tab\\there \\\"quoted\\\" \\u{1}\"",
+            expected: "\"tab\\there \\\"quoted\\\" \\u{0001}\";\n",
+        },
+        tests_struct {
+            input: "let add = fn(a,b){a+b};",
+            expected: "let add = fn(a, b) { a + b };\n",
+        },
+        tests_struct {
+            input: "let f = fn(x) { let y = x * 2; return y; };",
+            expected: "let f = fn(x) {\n    let y = x * 2;\n    return y;\n};\n",
+        },
+        tests_struct {
+            input: "if (x) { 1 } else { 2 }\nif (x) { puts(1); 1 }",
+            expected: "if (x) { 1 } else { 2 };\nif (x) {\n    puts(1);\n    1\n};\n",
+        },
+        tests_struct {
+            // both branches are split once one of them has to be.
+            input: "if (x) { 1 } else { puts(2); 2 }",
+            expected: "if (x) {\n    1\n} else {\n    puts(2);\n    2\n};\n",
+        },
+        tests_struct {
+            input: "let f = fn() { if (a) { if (b) { 1 } } };",
+            expected: "let f = fn() {\n    if (a) {\n        if (b) { 1 }\n    }\n};\n",
+        },
+        tests_struct {
+            input: "let a = 1;\n\n\n\nlet b = 2;\nlet c = 3;",
+            expected: "let a = 1;\n\nlet b = 2;\nlet c = 3;\n",
+        },
+        tests_struct {
+            input: "fn() {}",
+            expected: "fn() {};\n",
+        },
+        tests_struct {
+            input: "",
+            expected: "",
+        },
+    ];
+
+    for tt in tests.iter() {
+        let formatted = formatter::Format(tt.input).unwrap();
+        assert_eq!(formatted, tt.expected, "{:?}", tt.input);
+    }
+}
+
+// formatting must not change what a program means, and formatted source must be left alone.
+#[test]
+fn TestFormatIsStable() {
+    let inputs = vec![
+        "let fib=fn(n){if(n<2){n}else{fib(n-1)+fib(n-2)}};puts(fib(10))",
+        "let x = ((1 + 2) * 3) - (4 - (5 - 6)) ; let y = -(-x) + 1 == 7 != !true;",
+        "let h = {\"a\":[1,2,(3)], \"b\": fn(a,b){let c=a*b; c+1}}; h[\"b\"](1, 2)",
+        "let map = fn(arr, f) { let iter = fn(arr, acc) { if (len(arr) == 0) { acc } \
+         else { iter(rest(arr), push(acc, f(first(arr)))) } }; iter(arr, []) };",
+        "if (a) { if (b) { puts(1); 2 } else { 3 } } else { fn(x) { x }(4) }",
+        "let ok = (a || b) && !(c <= d % 2) || e >= (f && g);",
+        "let i=0;while(i<10){let i=i+1;if(i%2==0){continue;};if(i>7){break};puts(i)}",
+        "for(k,v in {\"a\":1}){puts(k,v)};for(i in 0 .. len(xs)-1){if(i>2){break}}",
+        "let n=0;for(x in xs){n+=x*2;n-=1};n=n/2;n*=-1;n/=(1+1)",
+        "const limit=10;const greet=fn(name){\"hi \"+name}",
+    ];
+
+    for input in inputs.iter() {
+        let once = formatter::Format(input).unwrap();
+        assert_eq!(parse(&once), parse(input), "{}", once);
+        let twice = formatter::Format(&once).unwrap();
+        assert_eq!(twice, once);
+    }
+}
+
+// the semicolon after an if statement keeps the next statement from being read as part of it.
+#[test]
+fn TestFormatKeepsStatementsApart() {
+    let inputs = vec![
+        "let x = true; if (x) { 1 };\n[1, 2];",
+        "if (true) { puts(1) };\n-1;",
+        "let f = fn(x) { if (x) { 1 } else { 2 }; (3) }; f(true)",
+    ];
+
+    for input in inputs.iter() {
+        let formatted = formatter::Format(input).unwrap();
+        assert_eq!(parse(&formatted), parse(input), "{}", formatted);
+        assert_eq!(
+            Interpreter::new().eval(&formatted),
+            Interpreter::new().eval(input),
+            "{}",
+            formatted
+        );
+    }
+}
+
+#[test]
+fn TestFormatSyntaxError() {
+    let errors = formatter::Format("let = 1;").unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn TestFormatComments() {
+    struct tests_struct {
+        input: &'static str,
+        expected: &'static str,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: "// header\nlet x=1;// one\n\n\n\n/* two\n   lines */\nx",
+            expected: "// header\nlet x = 1; // one\n\n/* two\n   lines */\nx;\n",
+        },
+        tests_struct {
+            input: "let f = fn(n) { // why\n  // base\n  n }",
+            expected: "let f = fn(n) {\n    // why\n    // base\n    n\n};\n",
+        },
+        tests_struct {
+            input: "puts(f(/* inline */ 10));",
+            expected: "puts(f(/* inline */ 10));\n",
+        },
+        tests_struct {
+            input: "let x = 1 + /* c */ 2; let y = [1, 2 /* end */]; y[0 /* i */] /* s */;",
+            expected: "let x = 1 + /* c */ 2;\nlet y = [1, 2 /* end */];\ny[0 /* i */] /* s */;\n",
+        },
+        tests_struct {
+            // a line comment ends the line, so the rest of the expression goes on the next.
+            input: "let h = {\n  \"a\": 1, // one\n  \"b\": 2 // two\n};",
+            expected: "let h = {\"a\": 1, // one\n    \"b\": 2 // two\n};\n",
+        },
+        tests_struct {
+            input: "let g = fn() { /* todo */ };",
+            expected: "let g = fn() {\n    /* todo */\n};\n",
+        },
+        tests_struct {
+            input: "if (a) { b\n // after b\n}\n// end",
+            expected: "if (a) {\n    b\n    // after b\n};\n// end\n",
+        },
+    ];
+
+    for tt in tests.iter() {
+        let formatted = formatter::Format(tt.input).unwrap();
+        assert_eq!(formatted, tt.expected, "{:?}", tt.input);
+        assert_eq!(formatter::Format(&formatted).unwrap(), formatted);
+    }
+}
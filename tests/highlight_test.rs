@@ -0,0 +1,54 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{highlight, lexer, object::Object};
+
+#[test]
+fn TestSource() {
+    struct tests_struct {
+        input: &'static str,
+        expected: &'static str,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: "let x = 5;",
+            expected: "\x1b[1;35mlet\x1b[0m \x1b[36mx\x1b[0m \x1b[1m=\x1b[0m \x1b[33m5\x1b[0m;",
+        },
+        tests_struct {
+            input: "  puts(\"hi\")  ",
+            expected: "  \x1b[36mputs\x1b[0m(\x1b[32m\"hi\"\x1b[0m)  ",
+        },
+        tests_struct {
+            input: "if (!a != true) { @ }",
+            expected: "\x1b[1;35mif\x1b[0m (\x1b[1m!\x1b[0m\x1b[36ma\x1b[0m \x1b[1m!=\x1b[0m \x1b[1;35mtrue\x1b[0m) { \x1b[4;31m@\x1b[0m }",
+        },
+        tests_struct {
+            input: "\"open",
+            expected: "\x1b[4;31m\"open\x1b[0m",
+        },
+        tests_struct {
+            input: "/* a */ 1 // b",
+            expected: "\x1b[90m/* a */\x1b[0m \x1b[33m1\x1b[0m \x1b[90m// b\x1b[0m",
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert_eq!(highlight::Source(tt.input), tt.expected, "{:?}", tt.input);
+    }
+}
+
+#[test]
+fn TestValue() {
+    let l = lexer::New(String::from(r#"[1, "a", true, {"k": len}]"#));
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    let env = monkey_interpreter::object::NewEnvironment();
+    let value = monkey_interpreter::evaluator::Eval(program, &env);
+
+    assert_eq!(
+        highlight::Value(&value),
+        "[\x1b[33m1\x1b[0m, \x1b[32m\"a\"\x1b[0m, \x1b[1;35mtrue\x1b[0m, {\x1b[32m\"k\"\x1b[0m: \x1b[34mbuiltin function\x1b[0m}]"
+    );
+    assert_eq!(highlight::Value(&Object::Null), "\x1b[1;35mnull\x1b[0m");
+}
@@ -0,0 +1,132 @@
+#![allow(non_snake_case)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{interpreter, object, object::Object, parser, token, Interpreter};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn TestRegisterFn() {
+    let mut interp = Interpreter::new();
+    interp
+        .register_fn("add", object::Arity::Exact(2), |args| match args {
+            [Object::Integer { Value: a }, Object::Integer { Value: b }] => {
+                Ok(Object::Integer { Value: a + b })
+            }
+            _ => Err(String::from("add expects two integers")),
+        })
+        .unwrap();
+
+    assert_eq!(interp.eval("add(1, 2)"), Ok(Object::Integer { Value: 3 }));
+    assert_eq!(
+        interp.eval("let twice = fn(f, x) { f(f(x, x), x) }; twice(add, 5)"),
+        Ok(Object::Integer { Value: 15 })
+    );
+    assert_eq!(
+        interp.eval("add(1)"),
+        Err(interpreter::Error::Runtime(String::from(
+            "wrong number of arguments. got=1, want=2"
+        )))
+    );
+    assert_eq!(
+        interp.eval("add(1, true)"),
+        Err(interpreter::Error::Runtime(String::from(
+            "add expects two integers"
+        )))
+    );
+}
+
+#[test]
+fn TestRegisterFnCapturesHostState() {
+    let calls = Rc::new(RefCell::new(vec![]));
+    let mut interp = Interpreter::new();
+    let log = Rc::clone(&calls);
+    interp
+        .register_fn("log", object::Arity::Any, move |args| {
+            for arg in args {
+                log.borrow_mut().push(arg.Inspect());
+            }
+            Ok(Object::Null)
+        })
+        .unwrap();
+
+    assert_eq!(
+        interp.eval(r#"log("a", 1); log([true]);"#),
+        Ok(Object::Null)
+    );
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            String::from("\"a\""),
+            String::from("1"),
+            String::from("[true]")
+        ]
+    );
+}
+
+#[test]
+fn TestConstsSurviveLaterScripts() {
+    let mut interp = Interpreter::new();
+    assert_eq!(
+        interp.eval("const port = 8080;"),
+        Ok(Object::Integer { Value: 8080 })
+    );
+    for input in [
+        "let port = 1;",
+        "const port = 1;",
+        "port = 1;",
+        "port += 1;",
+    ] {
+        assert!(
+            matches!(interp.eval(input), Err(interpreter::Error::Runtime(_))),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        interp.set("port", Object::Integer { Value: 1 }),
+        Err(interpreter::Error::Runtime(String::from(
+            "cannot redeclare const port"
+        )))
+    );
+    assert!(interp
+        .register_fn("port", object::Arity::Any, |_| Ok(Object::Null))
+        .is_err());
+    assert_eq!(interp.get("port"), Some(Object::Integer { Value: 8080 }));
+}
+
+#[test]
+fn TestInterpreterKeepsBindings() {
+    let mut interp = Interpreter::new();
+    interp.set("limit", Object::Integer { Value: 10 }).unwrap();
+    assert_eq!(
+        interp.eval("let x = limit * 2;"),
+        Ok(Object::Integer { Value: 20 })
+    );
+    assert_eq!(interp.eval("x + 1"), Ok(Object::Integer { Value: 21 }));
+    assert_eq!(interp.get("x"), Some(Object::Integer { Value: 20 }));
+    assert_eq!(
+        interp.eval("let = 5;"),
+        Err(interpreter::Error::Parse(vec![
+            parser::ParseError::UnexpectedToken {
+                Expected: token::IDENT,
+                Got: token::ASSIGN,
+                Span: token::Span {
+                    Start: 4,
+                    End: 5,
+                    Line: 1,
+                    Column: 5,
+                },
+            },
+            parser::ParseError::NoPrefixParseFn {
+                Got: token::ASSIGN,
+                Span: token::Span {
+                    Start: 4,
+                    End: 5,
+                    Line: 1,
+                    Column: 5,
+                },
+            },
+        ]))
+    );
+}
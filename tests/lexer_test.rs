@@ -1,3 +1,5 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
 extern crate monkey_interpreter;
 use monkey_interpreter::lexer;
 use monkey_interpreter::token;
@@ -67,7 +69,7 @@ fn TestNextToken() {
     };
 
     let result = add(five, ten);
-    !-/*5;
+    !-/ *5;
     5 < 10 > 5;
 
     if (5 < 10) {
@@ -388,3 +390,338 @@ fn TestNextToken() {
         assert_eq!(tok.Literal, tt.expectedLiteral);
     }
 }
+
+#[test]
+fn TestStringTokens() {
+    struct TokenTest {
+        expectedType: token::TokenType,
+        expectedLiteral: String,
+    }
+
+    let input =
+        String::from(r#""foobar" "foo bar" "a\nb\t\"c\"\\" "\u{48}\u{1F600}" "bad\q" "open"#);
+
+    let tests = vec![
+        TokenTest {
+            expectedType: token::STRING,
+            expectedLiteral: String::from("foobar"),
+        },
+        TokenTest {
+            expectedType: token::STRING,
+            expectedLiteral: String::from("foo bar"),
+        },
+        TokenTest {
+            expectedType: token::STRING,
+            expectedLiteral: String::from("a\nb\t\"c\"\\"),
+        },
+        TokenTest {
+            expectedType: token::STRING,
+            expectedLiteral: String::from("H\u{1F600}"),
+        },
+        TokenTest {
+            expectedType: token::ILLEGAL,
+            expectedLiteral: String::from(r#""bad\q""#),
+        },
+        TokenTest {
+            expectedType: token::ILLEGAL,
+            expectedLiteral: String::from(r#""open"#),
+        },
+        TokenTest {
+            expectedType: token::EOF,
+            expectedLiteral: String::from(""),
+        },
+    ];
+
+    let mut l = lexer::New(input);
+
+    for tt in tests.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, tt.expectedType);
+        assert_eq!(tok.Literal, tt.expectedLiteral);
+    }
+}
+
+#[test]
+fn TestCollectionTokens() {
+    let input = String::from("[1, 2]; {\"foo\": 1};");
+    let expected = vec![
+        (token::LBRACKET, "["),
+        (token::INT, "1"),
+        (token::COMMA, ","),
+        (token::INT, "2"),
+        (token::RBRACKET, "]"),
+        (token::SEMICOLON, ";"),
+        (token::LBRACE, "{"),
+        (token::STRING, "foo"),
+        (token::COLON, ":"),
+        (token::INT, "1"),
+        (token::RBRACE, "}"),
+        (token::SEMICOLON, ";"),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestOperatorTokens() {
+    let input = String::from("a <= b >= c % d && e || f < g & h | i");
+    let expected = vec![
+        (token::IDENT, "a"),
+        (token::LT_EQ, "<="),
+        (token::IDENT, "b"),
+        (token::GT_EQ, ">="),
+        (token::IDENT, "c"),
+        (token::PERCENT, "%"),
+        (token::IDENT, "d"),
+        (token::AND, "&&"),
+        (token::IDENT, "e"),
+        (token::OR, "||"),
+        (token::IDENT, "f"),
+        (token::LT, "<"),
+        (token::IDENT, "g"),
+        (token::ILLEGAL, "&"),
+        (token::IDENT, "h"),
+        (token::ILLEGAL, "|"),
+        (token::IDENT, "i"),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestConstKeyword() {
+    let input = String::from("const x = 1; constant");
+    let expected = vec![
+        (token::CONST, "const"),
+        (token::IDENT, "x"),
+        (token::ASSIGN, "="),
+        (token::INT, "1"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "constant"),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestLoopKeywords() {
+    let input = String::from("while (x) { break; continue; } whilst");
+    let expected = vec![
+        (token::WHILE, "while"),
+        (token::LPAREN, "("),
+        (token::IDENT, "x"),
+        (token::RPAREN, ")"),
+        (token::LBRACE, "{"),
+        (token::BREAK, "break"),
+        (token::SEMICOLON, ";"),
+        (token::CONTINUE, "continue"),
+        (token::SEMICOLON, ";"),
+        (token::RBRACE, "}"),
+        (token::IDENT, "whilst"),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestAssignmentOperators() {
+    let input = String::from("x = 1; x += 2; x -= 3; x *= 4; x /= 5; x == 6; x + =");
+    let expected = vec![
+        (token::IDENT, "x"),
+        (token::ASSIGN, "="),
+        (token::INT, "1"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::PLUS_ASSIGN, "+="),
+        (token::INT, "2"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::MINUS_ASSIGN, "-="),
+        (token::INT, "3"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::ASTERISK_ASSIGN, "*="),
+        (token::INT, "4"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::SLASH_ASSIGN, "/="),
+        (token::INT, "5"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::EQ, "=="),
+        (token::INT, "6"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::PLUS, "+"),
+        (token::ASSIGN, "="),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestForAndRange() {
+    let input = String::from("for (k, v in 0..10) {} inside .");
+    let expected = vec![
+        (token::FOR, "for"),
+        (token::LPAREN, "("),
+        (token::IDENT, "k"),
+        (token::COMMA, ","),
+        (token::IDENT, "v"),
+        (token::IN, "in"),
+        (token::INT, "0"),
+        (token::DOTDOT, ".."),
+        (token::INT, "10"),
+        (token::RPAREN, ")"),
+        (token::LBRACE, "{"),
+        (token::RBRACE, "}"),
+        (token::IDENT, "inside"),
+        (token::ILLEGAL, "."),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestTokenPositions() {
+    let input = String::from("let x = 5;\n  \"é\" + y;");
+    // (type, start, end, line, column)
+    let expected = vec![
+        (token::LET, 0, 3, 1, 1),
+        (token::IDENT, 4, 5, 1, 5),
+        (token::ASSIGN, 6, 7, 1, 7),
+        (token::INT, 8, 9, 1, 9),
+        (token::SEMICOLON, 9, 10, 1, 10),
+        (token::STRING, 13, 17, 2, 3),
+        (token::PLUS, 18, 19, 2, 7),
+        (token::IDENT, 20, 21, 2, 9),
+        (token::SEMICOLON, 21, 22, 2, 10),
+        (token::EOF, 22, 22, 2, 11),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, start, end, line, column) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(
+            tok.Span,
+            token::Span {
+                Start: *start,
+                End: *end,
+                Line: *line,
+                Column: *column,
+            },
+            "wrong span for {}",
+            tok
+        );
+    }
+}
+
+#[test]
+fn TestComments() {
+    let input = String::from("// first\nlet x = 1; /* a\n * b */ x / 2 // end");
+    let expected = vec![
+        (token::LET, "let"),
+        (token::IDENT, "x"),
+        (token::ASSIGN, "="),
+        (token::INT, "1"),
+        (token::SEMICOLON, ";"),
+        (token::IDENT, "x"),
+        (token::SLASH, "/"),
+        (token::INT, "2"),
+        (token::EOF, ""),
+    ];
+
+    let mut l = lexer::New(input);
+
+    for (expectedType, expectedLiteral) in expected.iter() {
+        let tok = l.NextToken();
+        assert_eq!(tok.Type, *expectedType);
+        assert_eq!(tok.Literal, *expectedLiteral);
+    }
+}
+
+#[test]
+fn TestUnterminatedComment() {
+    let mut l = lexer::New(String::from("1 /* open\n2"));
+    assert_eq!(l.NextToken().Type, token::INT);
+    let tok = l.NextToken();
+    assert_eq!(tok.Type, token::ILLEGAL);
+    assert_eq!(tok.Literal, "/* open\n2");
+    assert_eq!(l.NextToken().Type, token::EOF);
+}
+
+#[test]
+fn TestTakeTrivia() {
+    let mut l = lexer::NewLossless(String::from("  // a\nx /* b */"));
+    let tok = l.NextToken();
+    assert_eq!(tok.Type, token::IDENT);
+    let trivia: Vec<(token::TriviaKind, String)> = l
+        .TakeTrivia()
+        .into_iter()
+        .map(|t| (t.Kind, t.Text))
+        .collect();
+    assert_eq!(
+        trivia,
+        vec![
+            (token::TriviaKind::Whitespace, String::from("  ")),
+            (token::TriviaKind::LineComment, String::from("// a")),
+            (token::TriviaKind::Whitespace, String::from("\n")),
+        ]
+    );
+
+    assert_eq!(l.NextToken().Type, token::EOF);
+    let trivia = l.TakeTrivia();
+    assert_eq!(trivia.len(), 2);
+    assert_eq!(trivia[1].Kind, token::TriviaKind::BlockComment);
+    assert_eq!(trivia[1].Span.Start, 9);
+    assert_eq!(trivia[1].Span.End, 16);
+
+    // a plain lexer does not keep trivia.
+    let mut l = lexer::New(String::from("// a\nx"));
+    l.NextToken();
+    assert!(l.TakeTrivia().is_empty());
+}
@@ -1,3 +1,5 @@
+#![allow(non_snake_case, non_camel_case_types, dead_code, clippy::useless_vec)]
+
 extern crate monkey_interpreter;
 use monkey_interpreter::{ast, lexer, parser, token, token::Token};
 
@@ -13,7 +15,7 @@ fn TestLetStatements() {
         expectedValue: ast::Expression,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("let x = 5;"),
             expectedIdentifier: String::from("x"),
@@ -21,8 +23,9 @@ fn TestLetStatements() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
-                Value: 5 as i64,
+                Value: 5_i64,
             },
         },
         tests_struct {
@@ -32,6 +35,7 @@ fn TestLetStatements() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -43,6 +47,7 @@ fn TestLetStatements() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("y"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("y"),
             }),
@@ -64,11 +69,16 @@ fn TestLetStatements() {
         );
 
         let stmt = &program.Statements[0];
-        assert_eq!(true, testLetStatement(stmt, &tt.expectedIdentifier));
-
-        if let ast::Statement::LetStatement { Token, Name, Value } = stmt {
-            assert_eq!(
-                true,
+        assert!(testLetStatement(stmt, &tt.expectedIdentifier));
+
+        if let ast::Statement::LetStatement {
+            Token: _,
+            Name: _,
+            Value,
+            ..
+        } = stmt
+        {
+            assert!(
                 testLiteralExpression(Value, &tt.expectedValue),
                 "Value not expected"
             );
@@ -80,7 +90,7 @@ fn TestLetStatements() {
 
 fn testLetStatement(s: &ast::Statement, name: &String) -> bool {
     if let ast::Statement::LetStatement { Token, Name, .. } = s {
-        if Token.Literal != String::from("let") {
+        if Token.Literal != "let" {
             println!(
                 "LetStatement.Token.Literal not 'let'. got={}",
                 Token.Literal
@@ -113,13 +123,14 @@ fn TestReturnStatement() {
         expectedValue: ast::Expression,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("return 5;"),
             expectedValue: ast::Expression::IntergerLiteral {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -130,6 +141,7 @@ fn TestReturnStatement() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -140,6 +152,7 @@ fn TestReturnStatement() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -162,14 +175,17 @@ fn TestReturnStatement() {
 
         let stmt = &program.Statements[0];
 
-        if let ast::Statement::ReturnStatement { Token, ReturnValue } = stmt {
+        if let ast::Statement::ReturnStatement {
+            Token, ReturnValue, ..
+        } = stmt
+        {
             assert_eq!(
                 Token.Literal,
                 String::from("return"),
                 "Token.Literal not return. got={}",
                 Token.Literal
             );
-            assert_eq!(testLiteralExpression(ReturnValue, &tt.expectedValue), true);
+            assert!(testLiteralExpression(ReturnValue, &tt.expectedValue));
         } else {
             panic!("stmt not returnStatement. got={}", stmt);
         }
@@ -181,7 +197,7 @@ fn TestIdentifierExpression() {
     let input = String::from("foobar;");
     let l = lexer::New(input);
     let mut p = l.New();
-    let mut program = p.ParseProgram();
+    let program = p.ParseProgram();
     p.checkParserErrors();
 
     assert_eq!(
@@ -193,7 +209,12 @@ fn TestIdentifierExpression() {
 
     let stmt = &program.Statements[0];
 
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::Identifier(x) = Expression {
             assert_eq!(x.Value, String::from("foobar"));
             assert_eq!(x.Token.Literal, String::from("foobar"))
@@ -225,7 +246,12 @@ fn TestIntegerLiteralExpression() {
 
     let stmt = &program.Statements[0];
 
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::IntergerLiteral { Token, Value } = Expression {
             assert_eq!(*Value, 5);
             assert_eq!(Token.Literal, String::from("5"))
@@ -251,7 +277,7 @@ fn TestParsingPrefixExpression() {
         value: ast::Expression,
     }
 
-    let prefixTests = vec![
+    let prefixTests = [
         prefixTests_struct {
             input: String::from("!5;"),
             operator: String::from("!"),
@@ -259,6 +285,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -270,6 +297,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("15"),
+                    Span: token::Span::default(),
                 },
                 Value: 15,
             },
@@ -281,6 +309,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -292,6 +321,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -303,6 +333,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -314,6 +345,7 @@ fn TestParsingPrefixExpression() {
                 Token: Token {
                     Type: token::FALSE,
                     Literal: String::from("false"),
+                    Span: token::Span::default(),
                 },
                 Value: false,
             },
@@ -334,11 +366,17 @@ fn TestParsingPrefixExpression() {
         );
 
         let stmt = &program.Statements[0];
-        if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+        if let ast::Statement::ExpressionStatement {
+            Token: _,
+            Expression,
+            ..
+        } = stmt
+        {
             if let ast::Expression::PrefixExpression {
-                Token,
+                Token: _,
                 Operator,
                 Right,
+                ..
             } = Expression
             {
                 assert_eq!(
@@ -346,7 +384,7 @@ fn TestParsingPrefixExpression() {
                     "Operator is not {}. got={}",
                     tt.operator, Operator
                 );
-                assert_eq!(testLiteralExpression(Right, &tt.value), true);
+                assert!(testLiteralExpression(Right, &tt.value));
             } else {
                 panic!(
                     "stmt is not ast::Expression::PrefixExpression. got={}",
@@ -394,6 +432,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -402,6 +441,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -412,6 +452,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -420,6 +461,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -430,6 +472,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -438,6 +481,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -448,6 +492,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -456,6 +501,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -466,6 +512,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -474,6 +521,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -484,6 +532,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -492,6 +541,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -502,6 +552,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -510,6 +561,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -520,6 +572,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -528,6 +581,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::INT,
                     Literal: String::from("5"),
+                    Span: token::Span::default(),
                 },
                 Value: 5,
             },
@@ -538,6 +592,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -546,6 +601,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -556,6 +612,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -564,6 +621,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -574,6 +632,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -582,6 +641,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -592,6 +652,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -600,6 +661,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -610,6 +672,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -618,6 +681,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -628,6 +692,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -636,6 +701,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -646,6 +712,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -654,6 +721,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -664,6 +732,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("foobar"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("foobar"),
             }),
@@ -672,6 +741,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::IDENT,
                     Literal: String::from("barfoo"),
+                    Span: token::Span::default(),
                 },
                 Value: String::from("barfoo"),
             }),
@@ -682,6 +752,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -690,6 +761,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -700,6 +772,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::TRUE,
                     Literal: String::from("true"),
+                    Span: token::Span::default(),
                 },
                 Value: true,
             },
@@ -708,6 +781,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::FALSE,
                     Literal: String::from("false"),
+                    Span: token::Span::default(),
                 },
                 Value: false,
             },
@@ -718,6 +792,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::FALSE,
                     Literal: String::from("false"),
+                    Span: token::Span::default(),
                 },
                 Value: false,
             },
@@ -726,6 +801,7 @@ fn TestParsingInfixExpressions() {
                 Token: Token {
                     Type: token::FALSE,
                     Literal: String::from("false"),
+                    Span: token::Span::default(),
                 },
                 Value: false,
             },
@@ -747,18 +823,26 @@ fn TestParsingInfixExpressions() {
 
         let stmt = &program.Statements[0];
 
-        if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+        if let ast::Statement::ExpressionStatement {
+            Token: _,
+            Expression,
+            ..
+        } = stmt
+        {
             if let ast::Expression::InfixExpression {
-                Token,
+                Token: _,
                 Left,
                 Operator,
                 Right,
+                ..
             } = Expression
             {
-                assert_eq!(
-                    testInfixExpression(Expression, Left, Operator.clone(), Right),
-                    true
-                );
+                assert!(testInfixExpression(
+                    Expression,
+                    Left,
+                    Operator.clone(),
+                    Right
+                ));
             } else {
                 panic!(
                     "Expression is not ast::Expression::InfixExpression. got={}",
@@ -877,6 +961,38 @@ fn TestOperatorPrecedenceParsing() {
             input: String::from("add(a + b + c * d / f + g)"),
             expected: String::from("add((((a + b) + ((c * d) / f)) + g))"),
         },
+        tests_struct {
+            input: String::from("a * [1, 2, 3, 4][b * c] * d"),
+            expected: String::from("((a * ([1, 2, 3, 4][(b * c)])) * d)"),
+        },
+        tests_struct {
+            input: String::from("add(a * b[2], b[1], 2 * [1, 2][1])"),
+            expected: String::from("add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))"),
+        },
+        tests_struct {
+            input: String::from("a % b * c"),
+            expected: String::from("((a % b) * c)"),
+        },
+        tests_struct {
+            input: String::from("a + b <= c - 1 == d >= e"),
+            expected: String::from("(((a + b) <= (c - 1)) == (d >= e))"),
+        },
+        tests_struct {
+            input: String::from("a || b && c == d"),
+            expected: String::from("(a || (b && (c == d)))"),
+        },
+        tests_struct {
+            input: String::from("a && b || !c && d"),
+            expected: String::from("((a && b) || ((!c) && d))"),
+        },
+        tests_struct {
+            input: String::from("a + 1..b * 2 < c"),
+            expected: String::from("(((a + 1) .. (b * 2)) < c)"),
+        },
+        tests_struct {
+            input: String::from("-a..len(b)"),
+            expected: String::from("((-a) .. len(b))"),
+        },
     ];
 
     for tt in tests.iter() {
@@ -915,9 +1031,9 @@ fn testIdentifier(exp: &ast::Expression, value: String) -> bool {
 
 fn testLiteralExpression(exp: &ast::Expression, expected: &ast::Expression) -> bool {
     match expected {
-        ast::Expression::IntergerLiteral { Token, Value } => testIntegerLiteral(&exp, *Value),
+        ast::Expression::IntergerLiteral { Token: _, Value } => testIntegerLiteral(exp, *Value),
         ast::Expression::Identifier(x) => testIdentifier(exp, x.Value.clone()),
-        ast::Expression::Boolean { Token, Value } => testBooleanLiteral(exp, *Value),
+        ast::Expression::Boolean { Token: _, Value } => testBooleanLiteral(exp, *Value),
         _ => {
             println!("type of exp not handled. got={}", exp);
             false
@@ -932,10 +1048,11 @@ fn testInfixExpression(
     right: &ast::Expression,
 ) -> bool {
     if let ast::Expression::InfixExpression {
-        Token,
+        Token: _,
         Left,
         Operator,
         Right,
+        ..
     } = exp
     {
         if !testLiteralExpression(Left, left) {
@@ -965,7 +1082,7 @@ fn TestBooleanExpression() {
         expectedBoolean: bool,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("true;"),
             expectedBoolean: true,
@@ -992,8 +1109,13 @@ fn TestBooleanExpression() {
 
         let stmt = &program.Statements[0];
 
-        if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
-            if let ast::Expression::Boolean { Token, Value } = Expression {
+        if let ast::Statement::ExpressionStatement {
+            Token: _,
+            Expression,
+            ..
+        } = stmt
+        {
+            if let ast::Expression::Boolean { Token: _, Value } = Expression {
                 assert_eq!(Value, &tt.expectedBoolean);
             } else {
                 panic!("Expression not Boolean. got={}", Expression)
@@ -1040,36 +1162,46 @@ fn TestIfExpression() {
 
     let stmt = &program.Statements[0];
 
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::IfExpression {
-            Token,
+            Token: _,
             Condition,
             Consequence,
             Alternative,
+            ..
         } = Expression
         {
-            assert_eq!(
-                true,
-                testInfixExpression(
-                    &Condition,
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("x")
-                        },
-                        Value: String::from("x")
-                    }),
-                    String::from("<"),
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("y")
-                        },
-                        Value: String::from("y")
-                    })
-                )
-            );
-            if let ast::Statement::BlockStatement { Token, Statements } = Consequence.as_ref() {
+            assert!(testInfixExpression(
+                Condition,
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("x"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("x")
+                }),
+                String::from("<"),
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("y"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("y")
+                })
+            ));
+            if let ast::Statement::BlockStatement {
+                Token: _,
+                Statements,
+                ..
+            } = Consequence.as_ref()
+            {
                 assert_eq!(
                     1,
                     Statements.len(),
@@ -1077,8 +1209,13 @@ fn TestIfExpression() {
                     Statements.len()
                 );
                 let consequence = &Statements[0];
-                if let ast::Statement::ExpressionStatement { Token, Expression } = consequence {
-                    assert_eq!(true, testIdentifier(&Expression, String::from("x")));
+                if let ast::Statement::ExpressionStatement {
+                    Token: _,
+                    Expression,
+                    ..
+                } = consequence
+                {
+                    assert!(testIdentifier(Expression, String::from("x")));
                     if let ast::Statement::Nil = Alternative.as_ref() {
                     } else {
                         println!("Alternative is not Nil. got={}", Alternative);
@@ -1127,36 +1264,46 @@ fn TestIfElseExpression() {
 
     let stmt = &program.Statements[0];
 
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::IfExpression {
-            Token,
+            Token: _,
             Condition,
             Consequence,
             Alternative,
+            ..
         } = Expression
         {
-            assert_eq!(
-                true,
-                testInfixExpression(
-                    &Condition,
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("x")
-                        },
-                        Value: String::from("x")
-                    }),
-                    String::from("<"),
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("y")
-                        },
-                        Value: String::from("y")
-                    })
-                )
-            );
-            if let ast::Statement::BlockStatement { Token, Statements } = Consequence.as_ref() {
+            assert!(testInfixExpression(
+                Condition,
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("x"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("x")
+                }),
+                String::from("<"),
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("y"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("y")
+                })
+            ));
+            if let ast::Statement::BlockStatement {
+                Token: _,
+                Statements,
+                ..
+            } = Consequence.as_ref()
+            {
                 assert_eq!(
                     1,
                     Statements.len(),
@@ -1164,8 +1311,13 @@ fn TestIfElseExpression() {
                     Statements.len()
                 );
                 let consequence = &Statements[0];
-                if let ast::Statement::ExpressionStatement { Token, Expression } = consequence {
-                    assert_eq!(true, testIdentifier(&Expression, String::from("x")));
+                if let ast::Statement::ExpressionStatement {
+                    Token: _,
+                    Expression,
+                    ..
+                } = consequence
+                {
+                    assert!(testIdentifier(Expression, String::from("x")));
                 } else {
                     panic!(
                         "Statements[0] is not ast::Statement::ExpressionStatement. got={}",
@@ -1179,7 +1331,12 @@ fn TestIfElseExpression() {
                 );
             }
 
-            if let ast::Statement::BlockStatement { Token, Statements } = Alternative.as_ref() {
+            if let ast::Statement::BlockStatement {
+                Token: _,
+                Statements,
+                ..
+            } = Alternative.as_ref()
+            {
                 assert_eq!(
                     1,
                     Statements.len(),
@@ -1187,8 +1344,13 @@ fn TestIfElseExpression() {
                     Statements.len()
                 );
                 let alternative = &Statements[0];
-                if let ast::Statement::ExpressionStatement { Token, Expression } = alternative {
-                    assert_eq!(true, testIdentifier(&Expression, String::from("y")));
+                if let ast::Statement::ExpressionStatement {
+                    Token: _,
+                    Expression,
+                    ..
+                } = alternative
+                {
+                    assert!(testIdentifier(Expression, String::from("y")));
                 } else {
                     panic!(
                         "Statements[0] is not ast::Statement::ExpressionStatement. got={}",
@@ -1215,6 +1377,224 @@ fn TestIfElseExpression() {
     }
 }
 
+#[test]
+fn TestWhileStatement() {
+    let input = String::from("while (x < y) { if (x) { break; } continue; x }");
+    let l = lexer::New(input);
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    p.checkParserErrors();
+
+    assert_eq!(
+        1,
+        program.Statements.len(),
+        "program.Statements does not contain 1 statements. got={}",
+        program.Statements.len()
+    );
+
+    if let ast::Statement::WhileStatement {
+        Condition, Body, ..
+    } = &program.Statements[0]
+    {
+        assert_eq!(Condition.into_string(), "(x < y)");
+        if let ast::Statement::BlockStatement { Statements, .. } = Body.as_ref() {
+            assert_eq!(Statements.len(), 3);
+            assert!(matches!(
+                Statements[0],
+                ast::Statement::ExpressionStatement { .. }
+            ));
+            assert!(matches!(
+                Statements[1],
+                ast::Statement::ContinueStatement { .. }
+            ));
+            assert!(matches!(
+                Statements[2],
+                ast::Statement::ExpressionStatement { .. }
+            ));
+        } else {
+            panic!("Body is not ast::Statement::BlockStatement. got={}", Body);
+        }
+    } else {
+        panic!(
+            "program.Statements[0] is not ast::Statement::WhileStatement. got={}",
+            program.Statements[0]
+        );
+    }
+}
+
+#[test]
+fn TestConstStatements() {
+    let tests = vec![
+        ("const x = 5;", "x", "const x = 5;"),
+        ("const limit = max * 2", "limit", "const limit = (max * 2);"),
+        // a const may replace a let of the same scope, and be shadowed in an inner one.
+        ("let y = 1; const y = 2;", "y", "let y = 1;const y = 2;"),
+        (
+            "const z = 1; let f = fn(z) { z = 2; let z = 3; }",
+            "z",
+            "const z = 1;let f = fn(z) z = 2;let z = 3;;",
+        ),
+        (
+            "const w = 1; for (w in xs) { w += 1; const v = w; }",
+            "w",
+            "const w = 1;for(w in xs) w += 1;const v = w;",
+        ),
+    ];
+
+    for (input, name, expected) in tests.iter() {
+        let l = lexer::New(input.to_string());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        p.checkParserErrors();
+
+        assert_eq!(program.into_string(), *expected);
+        let consts: Vec<&str> = program
+            .Statements
+            .iter()
+            .filter_map(|s| match s {
+                ast::Statement::LetStatement { Token, Name, .. } if Token.Type == token::CONST => {
+                    Some(Name.Value.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(consts, vec![*name], "{}", input);
+    }
+}
+
+#[test]
+fn TestAssignStatements() {
+    struct tests_struct {
+        input: String,
+        expectedIdentifier: &'static str,
+        expectedOperator: &'static str,
+        expectedValue: String,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("x = 5;"),
+            expectedIdentifier: "x",
+            expectedOperator: "=",
+            expectedValue: String::from("5"),
+        },
+        tests_struct {
+            input: String::from("total += a * b"),
+            expectedIdentifier: "total",
+            expectedOperator: "+=",
+            expectedValue: String::from("(a * b)"),
+        },
+        tests_struct {
+            input: String::from("n -= 1;"),
+            expectedIdentifier: "n",
+            expectedOperator: "-=",
+            expectedValue: String::from("1"),
+        },
+        tests_struct {
+            input: String::from("n *= f(2)"),
+            expectedIdentifier: "n",
+            expectedOperator: "*=",
+            expectedValue: String::from("f(2)"),
+        },
+        tests_struct {
+            input: String::from("n /= 2 + 1"),
+            expectedIdentifier: "n",
+            expectedOperator: "/=",
+            expectedValue: String::from("(2 + 1)"),
+        },
+    ];
+
+    for tt in tests.iter() {
+        let l = lexer::New(tt.input.clone());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        p.checkParserErrors();
+
+        assert_eq!(
+            1,
+            program.Statements.len(),
+            "program.Statements does not contain 1 statements. got={}",
+            program.Statements.len()
+        );
+
+        if let ast::Statement::AssignStatement {
+            Token, Name, Value, ..
+        } = &program.Statements[0]
+        {
+            assert_eq!(Name.Value, tt.expectedIdentifier);
+            assert_eq!(Token.Literal, tt.expectedOperator);
+            assert_eq!(Value.into_string(), tt.expectedValue);
+        } else {
+            panic!(
+                "program.Statements[0] is not ast::Statement::AssignStatement. got={}",
+                program.Statements[0]
+            );
+        }
+    }
+}
+
+#[test]
+fn TestForStatement() {
+    struct tests_struct {
+        input: String,
+        variables: Vec<&'static str>,
+        iterable: String,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("for (x in xs) { if (x) { break; } continue; }"),
+            variables: vec!["x"],
+            iterable: String::from("xs"),
+        },
+        tests_struct {
+            input: String::from("for (k, v in f(h)) { k };"),
+            variables: vec!["k", "v"],
+            iterable: String::from("f(h)"),
+        },
+        tests_struct {
+            input: String::from("for (i in 0..n + 1) {}"),
+            variables: vec!["i"],
+            iterable: String::from("(0 .. (n + 1))"),
+        },
+    ];
+
+    for tt in tests.iter() {
+        let l = lexer::New(tt.input.clone());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        p.checkParserErrors();
+
+        assert_eq!(
+            1,
+            program.Statements.len(),
+            "program.Statements does not contain 1 statements. got={}",
+            program.Statements.len()
+        );
+
+        if let ast::Statement::ForStatement {
+            Variables,
+            Iterable,
+            Body,
+            ..
+        } = &program.Statements[0]
+        {
+            let names: Vec<&str> = Variables.iter().map(|v| v.Value.as_str()).collect();
+            assert_eq!(names, tt.variables);
+            assert_eq!(Iterable.into_string(), tt.iterable);
+            assert!(matches!(
+                Body.as_ref(),
+                ast::Statement::BlockStatement { .. }
+            ));
+        } else {
+            panic!(
+                "program.Statements[0] is not ast::Statement::ForStatement. got={}",
+                program.Statements[0]
+            );
+        }
+    }
+}
+
 #[test]
 fn TestFunctionLiteralParsing() {
     let input = String::from("fn(x,y) {x + y;}");
@@ -1231,11 +1611,17 @@ fn TestFunctionLiteralParsing() {
     );
 
     let stmt = &program.Statements[0];
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::FunctionLiteral {
-            Token,
+            Token: _,
             Parameters,
             Body,
+            ..
         } = Expression
         {
             assert_eq!(
@@ -1244,34 +1630,35 @@ fn TestFunctionLiteralParsing() {
                 "function literal parameters wrong. want 2, got={}",
                 Parameters.len()
             );
-            assert_eq!(
-                true,
-                testLiteralExpression(
-                    &Parameters[0],
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("x"),
-                        },
-                        Value: String::from("x")
-                    })
-                )
-            );
-            assert_eq!(
-                true,
-                testLiteralExpression(
-                    &Parameters[1],
-                    &ast::Expression::Identifier(ast::Identifier {
-                        Token: Token {
-                            Type: token::IDENT,
-                            Literal: String::from("y"),
-                        },
-                        Value: String::from("y")
-                    })
-                )
-            );
-
-            if let ast::Statement::BlockStatement { Token, Statements } = Body.as_ref() {
+            assert!(testLiteralExpression(
+                &Parameters[0],
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("x"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("x")
+                })
+            ));
+            assert!(testLiteralExpression(
+                &Parameters[1],
+                &ast::Expression::Identifier(ast::Identifier {
+                    Token: Token {
+                        Type: token::IDENT,
+                        Literal: String::from("y"),
+                        Span: token::Span::default(),
+                    },
+                    Value: String::from("y")
+                })
+            ));
+
+            if let ast::Statement::BlockStatement {
+                Token: _,
+                Statements,
+                ..
+            } = Body.as_ref()
+            {
                 assert_eq!(
                     1,
                     Statements.len(),
@@ -1279,28 +1666,32 @@ fn TestFunctionLiteralParsing() {
                     Statements.len()
                 );
                 let bodystmt = &Statements[0];
-                if let ast::Statement::ExpressionStatement { Token, Expression } = bodystmt {
-                    assert_eq!(
-                        true,
-                        testInfixExpression(
-                            &Expression,
-                            &ast::Expression::Identifier(ast::Identifier {
-                                Token: Token {
-                                    Type: token::IDENT,
-                                    Literal: String::from("x"),
-                                },
-                                Value: String::from("x")
-                            }),
-                            String::from("+"),
-                            &ast::Expression::Identifier(ast::Identifier {
-                                Token: Token {
-                                    Type: token::IDENT,
-                                    Literal: String::from("y"),
-                                },
-                                Value: String::from("y")
-                            })
-                        )
-                    );
+                if let ast::Statement::ExpressionStatement {
+                    Token: _,
+                    Expression,
+                    ..
+                } = bodystmt
+                {
+                    assert!(testInfixExpression(
+                        Expression,
+                        &ast::Expression::Identifier(ast::Identifier {
+                            Token: Token {
+                                Type: token::IDENT,
+                                Literal: String::from("x"),
+                                Span: token::Span::default(),
+                            },
+                            Value: String::from("x")
+                        }),
+                        String::from("+"),
+                        &ast::Expression::Identifier(ast::Identifier {
+                            Token: Token {
+                                Type: token::IDENT,
+                                Literal: String::from("y"),
+                                Span: token::Span::default(),
+                            },
+                            Value: String::from("y")
+                        })
+                    ));
                 } else {
                     panic!(
                         "function body stmt is not ast::Statement::ExpressionStatement. got={}",
@@ -1331,7 +1722,7 @@ fn TestFunctionParameterParsing() {
         expectedParams: Vec<String>,
     }
 
-    let tests = vec![
+    let tests = [
         tests_struct {
             input: String::from("fn() {};"),
             expectedParams: vec![],
@@ -1353,11 +1744,17 @@ fn TestFunctionParameterParsing() {
         p.checkParserErrors();
 
         let stmt = &program.Statements[0];
-        if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+        if let ast::Statement::ExpressionStatement {
+            Token: _,
+            Expression,
+            ..
+        } = stmt
+        {
             if let ast::Expression::FunctionLiteral {
-                Token,
+                Token: _,
                 Parameters,
-                Body,
+                Body: _,
+                ..
             } = Expression
             {
                 assert_eq!(
@@ -1368,19 +1765,17 @@ fn TestFunctionParameterParsing() {
                     Parameters.len()
                 );
                 for (i, ident) in tt.expectedParams.iter().enumerate() {
-                    assert_eq!(
-                        true,
-                        testLiteralExpression(
-                            &Parameters[i],
-                            &ast::Expression::Identifier(ast::Identifier {
-                                Token: Token {
-                                    Type: token::IDENT,
-                                    Literal: ident.clone()
-                                },
-                                Value: ident.clone()
-                            })
-                        )
-                    );
+                    assert!(testLiteralExpression(
+                        &Parameters[i],
+                        &ast::Expression::Identifier(ast::Identifier {
+                            Token: Token {
+                                Type: token::IDENT,
+                                Literal: ident.clone(),
+                                Span: token::Span::default(),
+                            },
+                            Value: ident.clone()
+                        })
+                    ));
                 }
             } else {
                 panic!(
@@ -1413,78 +1808,158 @@ fn TestCallExpressionParsing() {
     );
 
     let stmt = &program.Statements[0];
-    if let ast::Statement::ExpressionStatement { Token, Expression } = stmt {
+    if let ast::Statement::ExpressionStatement {
+        Token: _,
+        Expression,
+        ..
+    } = stmt
+    {
         if let ast::Expression::CallExpression {
-            Token,
+            Token: _,
             Function,
             Arguments,
+            ..
         } = Expression
         {
-            assert_eq!(true, testIdentifier(Function, String::from("add")));
+            assert!(testIdentifier(Function, String::from("add")));
             assert_eq!(
                 3,
                 Arguments.len(),
                 "wrong length of arguments. got={}",
                 Arguments.len()
             );
-            assert_eq!(
-                true,
-                testLiteralExpression(
-                    &Arguments[0],
-                    &ast::Expression::IntergerLiteral {
-                        Token: Token {
-                            Type: token::INT,
-                            Literal: String::from("1")
-                        },
-                        Value: 1
-                    }
-                )
-            );
-            assert_eq!(
-                true,
-                testInfixExpression(
-                    &Arguments[1],
-                    &ast::Expression::IntergerLiteral {
-                        Token: Token {
-                            Type: token::INT,
-                            Literal: String::from("2")
-                        },
-                        Value: 2
+            assert!(testLiteralExpression(
+                &Arguments[0],
+                &ast::Expression::IntergerLiteral {
+                    Token: Token {
+                        Type: token::INT,
+                        Literal: String::from("1"),
+                        Span: token::Span::default(),
                     },
-                    String::from("*"),
-                    &ast::Expression::IntergerLiteral {
-                        Token: Token {
-                            Type: token::INT,
-                            Literal: String::from("3")
-                        },
-                        Value: 3
-                    }
-                )
+                    Value: 1
+                }
+            ));
+            assert!(testInfixExpression(
+                &Arguments[1],
+                &ast::Expression::IntergerLiteral {
+                    Token: Token {
+                        Type: token::INT,
+                        Literal: String::from("2"),
+                        Span: token::Span::default(),
+                    },
+                    Value: 2
+                },
+                String::from("*"),
+                &ast::Expression::IntergerLiteral {
+                    Token: Token {
+                        Type: token::INT,
+                        Literal: String::from("3"),
+                        Span: token::Span::default(),
+                    },
+                    Value: 3
+                }
+            ));
+            assert!(testInfixExpression(
+                &Arguments[2],
+                &ast::Expression::IntergerLiteral {
+                    Token: Token {
+                        Type: token::INT,
+                        Literal: String::from("4"),
+                        Span: token::Span::default(),
+                    },
+                    Value: 4
+                },
+                String::from("+"),
+                &ast::Expression::IntergerLiteral {
+                    Token: Token {
+                        Type: token::INT,
+                        Literal: String::from("5"),
+                        Span: token::Span::default(),
+                    },
+                    Value: 5
+                }
+            ));
+        } else {
+            panic!(
+                "Expression is not ast::Expression::CallExpression. got={}",
+                Expression
             );
+        }
+    } else {
+        panic!(
+            "program.Statements[0] is not ast::Statement::ExpressionStatement. got={}",
+            program.Statements[0]
+        );
+    }
+}
+
+#[test]
+fn TestStringLiteralExpression() {
+    let input = String::from(r#""hello world";"#);
+    let l = lexer::New(input);
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    p.checkParserErrors();
+
+    if let ast::Statement::ExpressionStatement { Expression, .. } = &program.Statements[0] {
+        if let ast::Expression::StringLiteral { Value, .. } = Expression {
+            assert_eq!(*Value, String::from("hello world"));
+        } else {
+            panic!("exp not ast::Expression::StringLiteral. got={}", Expression);
+        }
+    } else {
+        panic!(
+            "program.Statements[0] is not ast::Statement::ExpressionStatement. got={}",
+            program.Statements[0]
+        );
+    }
+}
+
+#[test]
+fn TestParsingArrayLiterals() {
+    let input = String::from("[1, 2 * 2, 3 + 3]");
+    let l = lexer::New(input);
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    p.checkParserErrors();
+
+    if let ast::Statement::ExpressionStatement { Expression, .. } = &program.Statements[0] {
+        if let ast::Expression::ArrayLiteral { Elements, .. } = Expression {
             assert_eq!(
-                true,
-                testInfixExpression(
-                    &Arguments[2],
-                    &ast::Expression::IntergerLiteral {
-                        Token: Token {
-                            Type: token::INT,
-                            Literal: String::from("4")
-                        },
-                        Value: 4
-                    },
-                    String::from("+"),
-                    &ast::Expression::IntergerLiteral {
-                        Token: Token {
-                            Type: token::INT,
-                            Literal: String::from("5")
-                        },
-                        Value: 5
-                    }
-                )
+                3,
+                Elements.len(),
+                "len(array.Elements) not 3. got={}",
+                Elements.len()
             );
+            assert!(testIntegerLiteral(&Elements[0], 1));
+            assert_eq!(Elements[1].into_string(), String::from("(2 * 2)"));
+            assert_eq!(Elements[2].into_string(), String::from("(3 + 3)"));
+        } else {
+            panic!("exp not ast::Expression::ArrayLiteral. got={}", Expression);
+        }
+    } else {
+        panic!(
+            "program.Statements[0] is not ast::Statement::ExpressionStatement. got={}",
+            program.Statements[0]
+        );
+    }
+}
+
+#[test]
+fn TestParsingIndexExpressions() {
+    let input = String::from("myArray[1 + 1]");
+    let l = lexer::New(input);
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    p.checkParserErrors();
+
+    if let ast::Statement::ExpressionStatement { Expression, .. } = &program.Statements[0] {
+        if let ast::Expression::IndexExpression { Left, Index, .. } = Expression {
+            assert!(testIdentifier(Left, String::from("myArray")));
+            assert_eq!(Index.into_string(), String::from("(1 + 1)"));
         } else {
             panic!(
-                "Expression is not ast::Expression::CallExpression. got={}",
+                "exp not ast::Expression::IndexExpression. got={}",
                 Expression
             );
         }
@@ -1495,3 +1970,343 @@ fn TestCallExpressionParsing() {
         );
     }
 }
+
+#[test]
+fn TestParsingHashLiterals() {
+    struct tests_struct {
+        input: String,
+        expected: Vec<(String, String)>,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from(r#"{"one": 1, "two": 2, "three": 3}"#),
+            expected: vec![
+                (String::from("one"), String::from("1")),
+                (String::from("two"), String::from("2")),
+                (String::from("three"), String::from("3")),
+            ],
+        },
+        tests_struct {
+            input: String::from("{}"),
+            expected: vec![],
+        },
+        tests_struct {
+            input: String::from(r#"{"one": 0 + 1, 2: 10 - 8, true: 15 / 5}"#),
+            expected: vec![
+                (String::from("one"), String::from("(0 + 1)")),
+                (String::from("2"), String::from("(10 - 8)")),
+                (String::from("true"), String::from("(15 / 5)")),
+            ],
+        },
+    ];
+
+    for tt in tests.iter() {
+        let l = lexer::New(tt.input.clone());
+        let mut p = l.New();
+        let program = p.ParseProgram();
+        p.checkParserErrors();
+
+        if let ast::Statement::ExpressionStatement { Expression, .. } = &program.Statements[0] {
+            if let ast::Expression::HashLiteral { Pairs, .. } = Expression {
+                assert_eq!(
+                    tt.expected.len(),
+                    Pairs.len(),
+                    "hash.Pairs has wrong length. got={}",
+                    Pairs.len()
+                );
+                for ((key, value), (expectedKey, expectedValue)) in
+                    Pairs.iter().zip(tt.expected.iter())
+                {
+                    assert_eq!(key.into_string(), *expectedKey);
+                    assert_eq!(value.into_string(), *expectedValue);
+                }
+            } else {
+                panic!("exp not ast::Expression::HashLiteral. got={}", Expression);
+            }
+        } else {
+            panic!(
+                "program.Statements[0] is not ast::Statement::ExpressionStatement. got={}",
+                program.Statements[0]
+            );
+        }
+    }
+}
+
+#[test]
+fn TestParsingHashLiteralErrors() {
+    let input = String::from(r#"{"one" 1}"#);
+    let l = lexer::New(input);
+    let mut p = l.New();
+    p.ParseProgram();
+    assert_eq!(
+        p.Errors()[0].to_string(),
+        String::from("expected next token to be :, got INT, instead")
+    );
+}
+
+#[test]
+fn TestParseErrors() {
+    struct tests_struct {
+        input: String,
+        expected: parser::ParseError,
+        message: String,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: String::from("let x 5;"),
+            expected: parser::ParseError::UnexpectedToken {
+                Expected: token::ASSIGN,
+                Got: token::INT,
+                Span: token::Span {
+                    Start: 6,
+                    End: 7,
+                    Line: 1,
+                    Column: 7,
+                },
+            },
+            message: String::from("expected next token to be =, got INT, instead"),
+        },
+        tests_struct {
+            input: String::from("add(1, 2"),
+            expected: parser::ParseError::UnexpectedToken {
+                Expected: token::RPAREN,
+                Got: token::EOF,
+                Span: token::Span {
+                    Start: 8,
+                    End: 8,
+                    Line: 1,
+                    Column: 9,
+                },
+            },
+            message: String::from("expected next token to be ), got EOF, instead"),
+        },
+        tests_struct {
+            input: String::from("1 + ;"),
+            expected: parser::ParseError::NoPrefixParseFn {
+                Got: token::SEMICOLON,
+                Span: token::Span {
+                    Start: 4,
+                    End: 5,
+                    Line: 1,
+                    Column: 5,
+                },
+            },
+            message: String::from("no prefix parse function for ; found"),
+        },
+        tests_struct {
+            input: String::from("99999999999999999999"),
+            expected: parser::ParseError::InvalidIntegerLiteral {
+                Literal: String::from("99999999999999999999"),
+                Span: token::Span {
+                    Start: 0,
+                    End: 20,
+                    Line: 1,
+                    Column: 1,
+                },
+            },
+            message: String::from("could not parse 99999999999999999999 as integer"),
+        },
+        tests_struct {
+            input: String::from("\"é\" @"),
+            expected: parser::ParseError::IllegalToken {
+                Literal: String::from("@"),
+                Span: token::Span {
+                    Start: 5,
+                    End: 6,
+                    Line: 1,
+                    Column: 5,
+                },
+            },
+            message: String::from("illegal token @"),
+        },
+        tests_struct {
+            input: String::from("let x = 1; break;"),
+            expected: parser::ParseError::OutsideLoop {
+                Keyword: String::from("break"),
+                Span: token::Span {
+                    Start: 11,
+                    End: 16,
+                    Line: 1,
+                    Column: 12,
+                },
+            },
+            message: String::from("break outside of a loop"),
+        },
+        tests_struct {
+            // a function body is not inside the loop it is defined in.
+            input: String::from("while (true) { fn() { continue } }"),
+            expected: parser::ParseError::OutsideLoop {
+                Keyword: String::from("continue"),
+                Span: token::Span {
+                    Start: 22,
+                    End: 30,
+                    Line: 1,
+                    Column: 23,
+                },
+            },
+            message: String::from("continue outside of a loop"),
+        },
+        tests_struct {
+            // the operands waiting for the if's value would be left behind.
+            input: String::from("while (true) { let y = 1 + if (true) { continue; } else { 2 }; }"),
+            expected: parser::ParseError::InsideExpression {
+                Keyword: String::from("continue"),
+                Span: token::Span {
+                    Start: 39,
+                    End: 47,
+                    Line: 1,
+                    Column: 40,
+                },
+            },
+            message: String::from("continue inside an expression"),
+        },
+        tests_struct {
+            input: String::from("let i = 0; while (i < 3) { i += 1; puts(if (true) { break; }) }"),
+            expected: parser::ParseError::InsideExpression {
+                Keyword: String::from("break"),
+                Span: token::Span {
+                    Start: 52,
+                    End: 57,
+                    Line: 1,
+                    Column: 53,
+                },
+            },
+            message: String::from("break inside an expression"),
+        },
+        tests_struct {
+            input: String::from("a[0] += 1"),
+            expected: parser::ParseError::InvalidAssignment {
+                Span: token::Span {
+                    Start: 0,
+                    End: 4,
+                    Line: 1,
+                    Column: 1,
+                },
+            },
+            message: String::from("can only assign to a name"),
+        },
+        tests_struct {
+            input: String::from("const x = 1; let f = fn() { x += 1 };"),
+            expected: parser::ParseError::ConstAssigned {
+                Name: String::from("x"),
+                Span: token::Span {
+                    Start: 28,
+                    End: 29,
+                    Line: 1,
+                    Column: 29,
+                },
+            },
+            message: String::from("cannot assign to const x"),
+        },
+        tests_struct {
+            // an if block is part of the scope around it.
+            input: String::from("const x = 1; if (a) { let x = 2; }"),
+            expected: parser::ParseError::ConstRedeclared {
+                Name: String::from("x"),
+                Span: token::Span {
+                    Start: 26,
+                    End: 27,
+                    Line: 1,
+                    Column: 27,
+                },
+            },
+            message: String::from("cannot redeclare const x"),
+        },
+        tests_struct {
+            // either branch may have declared the const.
+            input: String::from("if (a) { const x = 1; } else { let y = 2; } let x = 3;"),
+            expected: parser::ParseError::ConstRedeclared {
+                Name: String::from("x"),
+                Span: token::Span {
+                    Start: 48,
+                    End: 49,
+                    Line: 1,
+                    Column: 49,
+                },
+            },
+            message: String::from("cannot redeclare const x"),
+        },
+        tests_struct {
+            // each pass of a loop has a scope of its own, but within a pass a const stays.
+            input: String::from("while (a) { const x = 1; const x = 2; }"),
+            expected: parser::ParseError::ConstRedeclared {
+                Name: String::from("x"),
+                Span: token::Span {
+                    Start: 31,
+                    End: 32,
+                    Line: 1,
+                    Column: 32,
+                },
+            },
+            message: String::from("cannot redeclare const x"),
+        },
+    ];
+
+    for tt in tests.iter() {
+        let l = lexer::New(tt.input.clone());
+        let mut p = l.New();
+        p.ParseProgram();
+
+        let errors = p.Errors();
+        assert!(!errors.is_empty(), "no errors for {}", tt.input);
+        assert_eq!(errors[0], tt.expected, "wrong error for {}", tt.input);
+        assert_eq!(errors[0].to_string(), tt.message);
+    }
+}
+
+#[test]
+fn TestNodeSpans() {
+    let input = String::from("let add = fn(a, b) {\n  a + b;\n};\nadd(1, 2 * 3)[0];");
+    let l = lexer::New(input.clone());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+    p.checkParserErrors();
+
+    let text = |span: token::Span| input[span.Start..span.End].to_string();
+
+    let stmt = &program.Statements[0];
+    assert_eq!(text(stmt.Span()), "let add = fn(a, b) {\n  a + b;\n};");
+    if let ast::Statement::LetStatement { Value, .. } = stmt {
+        assert_eq!(text(Value.Span()), "fn(a, b) {\n  a + b;\n}");
+        if let ast::Expression::FunctionLiteral { Body, .. } = Value {
+            assert_eq!(text(Body.Span()), "{\n  a + b;\n}");
+            if let ast::Statement::BlockStatement { Statements, .. } = Body.as_ref() {
+                assert_eq!(text(Statements[0].Span()), "a + b;");
+                assert_eq!(Statements[0].Span().Line, 2);
+                assert_eq!(Statements[0].Span().Column, 3);
+            } else {
+                panic!("Body is not ast::Statement::BlockStatement. got={}", Body);
+            }
+        } else {
+            panic!(
+                "Value is not ast::Expression::FunctionLiteral. got={}",
+                Value
+            );
+        }
+    } else {
+        panic!("stmt not ast::Statement::LetStatement. got={}", stmt);
+    }
+
+    let stmt = &program.Statements[1];
+    assert_eq!(stmt.Span().Line, 4);
+    if let ast::Statement::ExpressionStatement { Expression, .. } = stmt {
+        assert_eq!(text(Expression.Span()), "add(1, 2 * 3)[0]");
+        if let ast::Expression::IndexExpression { Left, .. } = Expression {
+            assert_eq!(text(Left.Span()), "add(1, 2 * 3)");
+            if let ast::Expression::CallExpression { Arguments, .. } = Left.as_ref() {
+                assert_eq!(text(Arguments[1].Span()), "2 * 3");
+            } else {
+                panic!("Left is not ast::Expression::CallExpression. got={}", Left);
+            }
+        } else {
+            panic!(
+                "Expression is not ast::Expression::IndexExpression. got={}",
+                Expression
+            );
+        }
+    } else {
+        panic!("stmt not ast::Statement::ExpressionStatement. got={}", stmt);
+    }
+}
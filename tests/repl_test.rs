@@ -0,0 +1,88 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::repl;
+
+#[test]
+fn TestIsComplete() {
+    struct tests_struct {
+        input: &'static str,
+        expected: bool,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input: "1 + 2",
+            expected: true,
+        },
+        tests_struct {
+            input: "let add = fn(a, b) {",
+            expected: false,
+        },
+        tests_struct {
+            input: "let add = fn(a, b) {\n a + b\n}",
+            expected: true,
+        },
+        tests_struct {
+            input: "add(1,",
+            expected: false,
+        },
+        tests_struct {
+            input: "[1, 2",
+            expected: false,
+        },
+        tests_struct {
+            input: "{\"a\": 1",
+            expected: false,
+        },
+        tests_struct {
+            input: "1 +",
+            expected: false,
+        },
+        tests_struct {
+            input: "a &&",
+            expected: false,
+        },
+        tests_struct {
+            input: "let x =",
+            expected: false,
+        },
+        tests_struct {
+            input: "if (x) { 1 } else",
+            expected: false,
+        },
+        tests_struct {
+            input: "\"unterminated",
+            expected: false,
+        },
+        tests_struct {
+            input: "\"escaped quote \\\"",
+            expected: false,
+        },
+        tests_struct {
+            input: "\"bad escape \\q\"",
+            expected: true,
+        },
+        tests_struct {
+            // extra closing brackets are an error, not a reason to wait.
+            input: "1 + 2) }",
+            expected: true,
+        },
+        tests_struct {
+            input: "let x = 1; /* unfinished",
+            expected: false,
+        },
+        tests_struct {
+            input: "let x = 1; /* done */ // and a comment",
+            expected: true,
+        },
+        tests_struct {
+            input: "",
+            expected: true,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert_eq!(repl::IsComplete(tt.input), tt.expected, "{:?}", tt.input);
+    }
+}
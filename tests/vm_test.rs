@@ -0,0 +1,130 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::useless_vec)]
+
+extern crate monkey_interpreter;
+use monkey_interpreter::{compiler, lexer, object, vm};
+
+fn testRun(input: &str) -> Result<object::Object, String> {
+    let l = lexer::New(input.to_string());
+    let mut p = l.New();
+    let program = p.ParseProgram();
+
+    let mut comp = compiler::New();
+    comp.Compile(&program)?;
+    let mut machine = vm::New(comp.Bytecode());
+    machine.Run()?;
+    Ok(machine.LastPoppedStackElem())
+}
+
+#[test]
+fn TestClosures() {
+    struct tests_struct {
+        input: &'static str,
+        expected: i64,
+    }
+
+    let tests = vec![
+        tests_struct {
+            input:
+                "let newClosure = fn(a) { fn() { a; }; }; let closure = newClosure(99); closure();",
+            expected: 99,
+        },
+        tests_struct {
+            input: "let newAdderOuter = fn(a, b) {
+                let c = a + b;
+                fn(d) {
+                    let e = d + c;
+                    fn(f) { e + f; };
+                };
+            };
+            let newAdderInner = newAdderOuter(1, 2);
+            let adder = newAdderInner(3);
+            adder(8);",
+            expected: 14,
+        },
+        tests_struct {
+            input: "let wrapper = fn() {
+                let countDown = fn(x) { if (x == 0) { return 0; } else { countDown(x - 1); } };
+                countDown(1);
+            };
+            wrapper();",
+            expected: 0,
+        },
+        tests_struct {
+            input: "let fibonacci = fn(x) {
+                if (x == 0) { return 0; }
+                if (x == 1) { return 1; }
+                fibonacci(x - 1) + fibonacci(x - 2);
+            };
+            fibonacci(15);",
+            expected: 610,
+        },
+    ];
+
+    for tt in tests.iter() {
+        assert_eq!(
+            testRun(tt.input),
+            Ok(object::Object::Integer { Value: tt.expected }),
+            "{}",
+            tt.input
+        );
+    }
+}
+
+#[test]
+fn TestLocalsDoNotLeakBetweenCalls() {
+    let input = "let f = fn(set) { if (set) { let x = 1; } x }; f(true); f(false)";
+    assert_eq!(testRun(input), Ok(object::Object::Null));
+}
+
+#[test]
+fn TestRuntimeErrors() {
+    let tests = vec![
+        (
+            "fn() { 1; }(1);",
+            "wrong number of arguments. got=1, want=0",
+        ),
+        (
+            "fn(a, b) { a + b; }(1);",
+            "wrong number of arguments. got=1, want=2",
+        ),
+        ("1(2)", "not a function: INTEGER"),
+        ("10 / 0", "division by zero"),
+        (
+            "let m = -9223372036854775807 - 1; m % -1",
+            "integer overflow",
+        ),
+        ("9223372036854775807 + 1", "integer overflow"),
+        ("-9223372036854775807 - 2", "integer overflow"),
+        ("4611686018427387904 * 2", "integer overflow"),
+        ("-(-9223372036854775807 - 1)", "integer overflow"),
+        ("let n = 9223372036854775807; n += 1; n", "integer overflow"),
+        ("let f = fn() { f() }; f()", "stack overflow"),
+        (r#"len(1)"#, "argument to `len` not supported, got INTEGER"),
+    ];
+
+    for (input, expected) in tests.iter() {
+        assert_eq!(testRun(input), Err(expected.to_string()), "{}", input);
+    }
+}
+
+#[test]
+fn TestGlobalsStore() {
+    // globals survive between runs when the compiler state and store are carried over.
+    let mut comp = compiler::New();
+    let program = lexer::New(String::from("let a = 40;")).New().ParseProgram();
+    comp.Compile(&program).unwrap();
+    let mut machine = vm::New(comp.Bytecode());
+    machine.Run().unwrap();
+    let globals = machine.Globals();
+
+    let (symbols, constants) = comp.State();
+    let mut comp = compiler::NewWithState(symbols, constants);
+    let program = lexer::New(String::from("a + 2")).New().ParseProgram();
+    comp.Compile(&program).unwrap();
+    let mut machine = vm::NewWithGlobalsStore(comp.Bytecode(), globals);
+    machine.Run().unwrap();
+    assert_eq!(
+        machine.LastPoppedStackElem(),
+        object::Object::Integer { Value: 42 }
+    );
+}